@@ -7,6 +7,33 @@
 #include <stdint.h>
 #include <stdbool.h>
 
+/**
+ * Default uncompressed size of each frame written by [`Bindle::seekable_writer`], 1 MiB.
+ */
+#define DEFAULT_FRAME_SIZE (1 << 20)
+
+/**
+ * Zstd compression level used when none is given, such as by [`Bindle::add`] and [`Bindle::writer`].
+ *
+ * Pass another level in `1..=22` to [`Bindle::writer_with_level`] and the other `_with_level` methods.
+ */
+#define DEFAULT_ZSTD_LEVEL 3
+
+/**
+ * Newest archive format version this crate reads, and the version it writes.
+ *
+ * Archives with a newer version fail to open with [`BindleError::UnsupportedVersion`], see
+ * [`Bindle::version()`] for the version of an open archive.
+ */
+#define FORMAT_VERSION 4
+
+/**
+ * Longest entry name in bytes, names are stored with a 16-bit length.
+ *
+ * Adding, packing or renaming to a longer name fails with [`std::io::ErrorKind::InvalidInput`].
+ */
+#define MAX_NAME_LEN (size_t)UINT16_MAX
+
 /**
  * Compression mode for entries.
  */
@@ -20,18 +47,72 @@ typedef enum BindleCompress {
    */
   BindleCompressZstd = 1,
   /**
-   * Automatically compress if entry is larger than 2KB threshold.
+   * Automatically compress entries larger than 2KB if a sample of the data compresses well.
+   * The size cutoff can be changed with [`Bindle::set_auto_compress_threshold()`](crate::Bindle::set_auto_compress_threshold).
    * Note: This is never stored on disk, only used as a policy hint.
    */
   BindleCompressAuto = 2,
+  /**
+   * Gzip (deflate) compression.
+   */
+  BindleCompressGzip = 3,
 } BindleCompress;
 
+/**
+ * Error codes reported by `bindle_last_error()`.
+ */
+typedef enum BindleErrorCode {
+  /**
+   * The last operation succeeded.
+   */
+  BindleErrorCodeOk = 0,
+  /**
+   * A required pointer was NULL or a string wasn't valid UTF-8.
+   */
+  BindleErrorCodeInvalidArgument = 1,
+  /**
+   * No entry exists with the given name.
+   */
+  BindleErrorCodeNotFound = 2,
+  /**
+   * An entry's data doesn't match its stored CRC32.
+   */
+  BindleErrorCodeCrcMismatch = 3,
+  /**
+   * The file isn't a valid archive or its index is corrupt.
+   */
+  BindleErrorCodeInvalidFormat = 4,
+  /**
+   * An entry with the given name already exists.
+   */
+  BindleErrorCodeAlreadyExists = 5,
+  /**
+   * The archive was opened read-only.
+   */
+  BindleErrorCodeReadOnly = 6,
+  /**
+   * The operation isn't supported for this entry.
+   */
+  BindleErrorCodeUnsupported = 7,
+  /**
+   * Any other I/O error.
+   */
+  BindleErrorCodeIo = 8,
+  /**
+   * A buffer for the result couldn't be allocated.
+   */
+  BindleErrorCodeOutOfMemory = 9,
+} BindleErrorCode;
+
 /**
  * A binary archive for collecting files.
  *
- * Uses memory-mapped I/O for fast reads, supports optional zstd compression, and handles updates via shadowing.
+ * Uses memory-mapped I/O for fast reads by default (see [`Backend`]), supports optional zstd compression, and handles updates via shadowing.
  * Files can be added incrementally without rewriting the entire archive.
  *
+ * Writes are appended after the committed footer, so other processes that already opened the archive keep
+ * reading the last committed state while a writer works. Only one process should write at a time.
+ *
  * # Example
  *
  * ```no_run
@@ -70,6 +151,10 @@ typedef struct BindleReader BindleReader;
  *
  * The writer must be closed with [`close()`](Writer::close) or will be automatically closed when dropped. After closing, call [`Bindle::save()`] to commit the index.
  *
+ * Call [`abort()`](Writer::abort) to discard the entry instead. If a write fails, the entry is
+ * discarded when the writer is dropped and [`close()`](Writer::close) returns an error, so a
+ * failed stream never adds a truncated entry.
+ *
  * # Example
  *
  * ```no_run
@@ -86,6 +171,22 @@ typedef struct BindleReader BindleReader;
  */
 typedef struct BindleWriter BindleWriter;
 
+/**
+ * Returns the error code of the last failed or succeeded operation on this thread.
+ *
+ * Operations that return a failure sentinel (`false`, `NULL`, `0` or `-1`) set this before
+ * returning, so it can tell a missing entry from a CRC32 mismatch or an I/O error. The value is
+ * one of the `BindleErrorCode` constants, 0 means success.
+ */
+int32_t bindle_last_error(void);
+
+/**
+ * Returns a static, NUL-terminated description of an error code from `bindle_last_error()`.
+ *
+ * Unknown codes return "unknown error". Do NOT free the returned pointer.
+ */
+const char *bindle_error_message(int32_t code);
+
 /**
  * Creates a new archive, overwriting any existing file.
  *
@@ -127,7 +228,7 @@ struct Bindle *bindle_load(const char *path);
  * * `name` - NUL-terminated entry name
  * * `data` - Data bytes (may contain NUL bytes)
  * * `data_len` - Length of data in bytes
- * * `compress` - Compression mode (BindleCompressNone, BindleCompressZstd, or BindleCompressAuto)
+ * * `compress` - Compression mode (BindleCompressNone, BindleCompressZstd, BindleCompressGzip, or BindleCompressAuto)
  *
  * # Returns
  * True on success. Call `bindle_save()` to commit changes.
@@ -211,6 +312,20 @@ const uint8_t *bindle_read_uncompressed_direct(struct Bindle *ctx,
  */
 size_t bindle_length(const struct Bindle *ctx);
 
+/**
+ * Returns the format version of the archive, at most `FORMAT_VERSION`, or 0 if `ctx` is NULL.
+ */
+uint8_t bindle_version(const struct Bindle *ctx);
+
+/**
+ * Sets the size limits enforced when reading and unpacking.
+ *
+ * Reads of entries larger than `max_entry_size` bytes uncompressed fail before anything is decoded,
+ * and unpacking fails if the entries add up to more than `max_total_size` bytes. The defaults are
+ * 1 GiB per entry and no total limit.
+ */
+bool bindle_set_limits(struct Bindle *ctx, uint64_t max_entry_size, uint64_t max_total_size);
+
 /**
  * Returns the name of the entry at the given index as a null-terminated C string.
  *
@@ -239,6 +354,18 @@ bool bindle_unpack(struct Bindle *ctx, const char *dest_path);
  */
 bool bindle_pack(struct Bindle *ctx, const char *src_path, enum BindleCompress compress);
 
+/**
+ * Recursively adds the files from a directory for which `filter` returns true.
+ *
+ * `filter` is called with the entry name of each file, relative to `src_path`, and the name is only
+ * valid during the call. Every file is filtered before the archive is modified, so the callback may
+ * report progress or call other functions on `ctx`. Call `bindle_save()` to commit changes.
+ */
+bool bindle_pack_filtered(struct Bindle *ctx,
+                          const char *src_path,
+                          enum BindleCompress compress,
+                          bool (*filter)(const char*));
+
 /**
  * Returns true if an entry with the given name exists.
  */
@@ -252,6 +379,14 @@ bool bindle_exists(const struct Bindle *ctx, const char *name);
  */
 bool bindle_remove(struct Bindle *ctx, const char *name);
 
+/**
+ * Renames an entry without copying its data.
+ *
+ * Returns false if `old_name` doesn't exist, either name is invalid, or `new_name` is already taken
+ * and `overwrite` is false. Call `bindle_save()` to commit changes.
+ */
+bool bindle_rename(struct Bindle *ctx, const char *old_name, const char *new_name, bool overwrite);
+
 /**
  * Creates a streaming writer for adding an entry.
  *
@@ -262,6 +397,16 @@ struct BindleWriter *bindle_writer_new(struct Bindle *ctx,
                                        const char *name,
                                        enum BindleCompress compress);
 
+/**
+ * Creates a streaming writer for a zstd entry that supports seeking.
+ *
+ * Data is split into independent frames of `frame_size` uncompressed bytes, pass 0 for the 1 MiB default.
+ * The writer must be closed with `bindle_writer_close()`, then call `bindle_save()` to commit.
+ */
+struct BindleWriter *bindle_writer_new_seekable(struct Bindle *ctx,
+                                                const char *name,
+                                                size_t frame_size);
+
 /**
  * Writes data to the writer.
  */
@@ -308,7 +453,8 @@ void bindle_reader_close(struct BindleReader *reader);
  *
  * # Returns
  * The uncompressed size in bytes, or 0 if the entry doesn't exist.
- * Note: Returns 0 for both non-existent entries and zero-length entries.
+ * Note: Returns 0 for both non-existent entries and zero-length entries, use `bindle_exists()` to
+ * tell the cases apart.
  */
 size_t bindle_entry_size(const struct Bindle *ctx, const char *name);
 
@@ -320,10 +466,36 @@ size_t bindle_entry_size(const struct Bindle *ctx, const char *name);
  * * `name` - NUL-terminated entry name
  *
  * # Returns
- * The Compress value (0 = None, 1 = Zstd), or 0 if the entry doesn't exist.
+ * The Compress value (0 = None, 1 = Zstd, 3 = Gzip), or 0 if the entry doesn't exist.
  */
 enum BindleCompress bindle_entry_compress(const struct Bindle *ctx, const char *name);
 
+/**
+ * Gets the compressed size of an entry by name, the number of bytes it occupies in the archive.
+ *
+ * # Parameters
+ * * `ctx` - Bindle handle
+ * * `name` - NUL-terminated entry name
+ *
+ * # Returns
+ * The compressed size in bytes, or 0 if the entry doesn't exist.
+ * For uncompressed entries this equals the uncompressed size.
+ */
+size_t bindle_entry_compressed_size(const struct Bindle *ctx, const char *name);
+
+/**
+ * Gets the stored CRC32 of an entry's uncompressed data by name.
+ *
+ * # Parameters
+ * * `ctx` - Bindle handle
+ * * `name` - NUL-terminated entry name
+ *
+ * # Returns
+ * The CRC32 checksum, or 0 if the entry doesn't exist.
+ * Note: 0 is also a valid checksum, use `bindle_exists()` to tell the cases apart.
+ */
+uint32_t bindle_entry_crc32(const struct Bindle *ctx, const char *name);
+
 /**
  * Reads an entry into a pre-existing buffer.
  *
@@ -337,7 +509,9 @@ enum BindleCompress bindle_entry_compress(const struct Bindle *ctx, const char *
  *
  * # Returns
  * The number of bytes actually read, or 0 if the entry doesn't exist or CRC32 check fails.
- * If the entry is larger than `buffer_len`, only `buffer_len` bytes are read.
+ * Empty entries also read 0 bytes, `bindle_last_error()` returns `BindleErrorCodeOk` for them.
+ * If the entry is larger than `buffer_len`, only `buffer_len` bytes are read and the CRC32
+ * isn't checked, since it covers the whole entry.
  */
 size_t bindle_read(const struct Bindle *ctx, const char *name, uint8_t *buffer, size_t buffer_len);
 
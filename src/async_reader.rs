@@ -0,0 +1,110 @@
+use std::io::{self, Read};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::mpsc;
+
+use crate::view::ReadOnlyView;
+
+/// Size of the chunks decoded on the blocking thread pool.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Decoded chunks buffered ahead of the async side.
+const CHUNKS_AHEAD: usize = 4;
+
+/// An [`AsyncRead`] stream of an entry's uncompressed data, available with the `async` feature.
+///
+/// Created by [`Bindle::async_reader()`](crate::Bindle::async_reader) or
+/// [`ReadOnlyView::async_reader()`]. The entry is read and decompressed by a [`Reader`](crate::Reader)
+/// on tokio's blocking thread pool, so page faults and decompression never stall the runtime. A few
+/// chunks are decoded ahead of the consumer. The CRC32 is verified at the end of the entry and a
+/// mismatch is returned as an [`io::ErrorKind::InvalidData`] error from the last read.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use bindle_file::Bindle;
+/// use tokio::io::AsyncReadExt;
+///
+/// let archive = Bindle::open("data.bndl")?;
+/// let mut reader = archive.async_reader("large.bin")?;
+/// let mut data = Vec::new();
+/// reader.read_to_end(&mut data).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncReader {
+    chunks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    current: Vec<u8>,
+    pos: usize,
+}
+
+impl AsyncReader {
+    /// Starts decoding `name` from `view` on the blocking thread pool.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub(crate) fn new(view: ReadOnlyView, name: &str) -> io::Result<Self> {
+        if !view.exists(name) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found"));
+        }
+        let (tx, rx) = mpsc::channel(CHUNKS_AHEAD);
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || {
+            let result = (|| {
+                let mut reader = view.reader(&name)?;
+                loop {
+                    let mut chunk = vec![0u8; CHUNK_SIZE];
+                    let n = match reader.read(&mut chunk) {
+                        Ok(n) => n,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    };
+                    if n == 0 {
+                        return reader.verify_crc32();
+                    }
+                    chunk.truncate(n);
+                    if tx.blocking_send(Ok(chunk)).is_err() {
+                        // The reader was dropped, stop decoding
+                        return Ok(());
+                    }
+                }
+            })();
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+        Ok(AsyncReader {
+            chunks: rx,
+            current: Vec::new(),
+            pos: 0,
+        })
+    }
+}
+
+impl AsyncRead for AsyncReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.pos == this.current.len() {
+            match this.chunks.poll_recv(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    this.current = chunk;
+                    this.pos = 0;
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                // The decoder finished, end of the entry
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let n = buf.remaining().min(this.current.len() - this.pos);
+        buf.put_slice(&this.current[this.pos..this.pos + n]);
+        this.pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
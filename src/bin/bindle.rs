@@ -1,9 +1,13 @@
-use clap::{Parser, Subcommand};
-use std::io::{self};
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
-use bindle_file::{Bindle, Compress};
+use bindle_file::{
+    Bindle, Compress, Conflict, DEFAULT_FRAME_SIZE, DEFAULT_ZSTD_LEVEL, PackEvent, PackOptions,
+    Symlinks, ZstdParams,
+};
 
 #[derive(Parser)]
 #[command(name = "bindle")]
@@ -11,6 +15,10 @@ use bindle_file::{Bindle, Compress};
 #[command(author = "zshipko")]
 #[command(about = "Append-only file collection")]
 struct Cli {
+    /// Don't print status lines such as OK, only data and errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -19,9 +27,21 @@ struct Cli {
 enum Commands {
     /// List all entries in the archive
     List {
-        /// Bindle archive file
+        /// Bindle archive file, `-` reads it from stdin
         #[arg(value_name = "BINDLE_FILE")]
         bindle_file: PathBuf,
+        /// Order of the listed entries
+        #[arg(long, value_enum, default_value_t = Sort::Name)]
+        sort: Sort,
+        /// Only list entries whose names start with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Print only entry names, one per line, such as for piping into `read`
+        #[arg(long, conflicts_with = "format")]
+        names: bool,
+        /// Output format, `json` prints an array of {name, size, packed, ratio, compression}
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
     },
 
     /// Add a file to the archive
@@ -37,25 +57,61 @@ enum Commands {
         /// Use zstd compression
         #[arg(short, long)]
         compress: bool,
+        /// Zstd compression level (1-22), implies --compress
+        #[arg(short, long, value_name = "N")]
+        level: Option<i32>,
+        /// Write zstd frames of 1 MiB so readers can seek, implies --compress
+        #[arg(long, conflicts_with = "level")]
+        seekable: bool,
+        /// Enable zstd long-distance matching, optionally with a window of 2^N bytes (10-31),
+        /// implies --compress
+        #[arg(
+            long,
+            value_name = "N",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "27",
+            conflicts_with = "seekable"
+        )]
+        long: Option<u32>,
         /// Pass data directly as an argument
         #[arg(short, long, conflicts_with = "file_path")]
         data: Option<String>,
         /// Run vacuum after adding
         #[arg(long)]
         vacuum: bool,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
     },
 
     #[command(visible_alias = "cat")]
-    /// Extract an entry's data
+    /// Extract the data of one or more entries
     Read {
-        /// Bindle archive file
+        /// Bindle archive file, `-` reads it from stdin
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Names of the entries to extract, missing entries are reported and skipped
+        #[arg(required = true)]
+        names: Vec<String>,
+        /// Output path, `-` or omitted writes the raw bytes to stdout one entry after another
+        #[arg(short, long, conflicts_with = "output_dir")]
+        output: Option<PathBuf>,
+        /// Write each entry to its own file below this directory, keeping its path
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+    },
+
+    /// Extract a single entry into a directory, keeping its path
+    Extract {
+        /// Bindle archive file, `-` reads it from stdin
         #[arg(value_name = "BINDLE_FILE")]
         bindle_file: PathBuf,
         /// Name of the entry to extract
         name: String,
-        /// Output path
-        #[arg(short, long)]
-        output: Option<PathBuf>,
+        /// Destination directory
+        #[arg(value_name = "DEST_DIR", default_value = ".")]
+        dest_dir: PathBuf,
     },
 
     /// Remove an entry from the archive
@@ -68,6 +124,42 @@ enum Commands {
         /// Run vacuum after removing
         #[arg(long)]
         vacuum: bool,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
+    },
+
+    /// Rename an entry without copying its data
+    Rename {
+        /// Bindle archive file
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Current name of the entry
+        old: String,
+        /// New name for the entry
+        new: String,
+        /// Replace an existing entry with the new name
+        #[arg(short, long)]
+        force: bool,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
+    },
+
+    /// Copy every entry from other archives without recompressing
+    Merge {
+        /// Bindle archive file to merge into
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Archives to copy entries from, in order, `-` reads one from stdin
+        #[arg(value_name = "SRC_FILE", required = true)]
+        sources: Vec<PathBuf>,
+        /// What to do when an entry name already exists
+        #[arg(long, value_enum, default_value_t = OnConflict::Error)]
+        on_conflict: OnConflict,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
     },
 
     /// Pack an entire directory into the archive
@@ -81,22 +173,97 @@ enum Commands {
         /// Use zstd compression
         #[arg(short, long)]
         compress: bool,
+        /// Zstd compression level (1-22), implies --compress
+        #[arg(short, long, value_name = "N")]
+        level: Option<i32>,
         /// Append to existing file
         #[arg(short, long)]
         append: bool,
+        /// Skip files and directories matching this glob (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// How to handle symbolic links
+        #[arg(long, value_enum, default_value_t = SymlinkMode::Follow)]
+        symlinks: SymlinkMode,
+        /// Store files with identical contents once
+        #[arg(long)]
+        dedup: bool,
         /// Run vacuum after packing
         #[arg(long)]
         vacuum: bool,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
+    },
+
+    #[cfg(feature = "tar")]
+    /// Add the files from a tar archive without unpacking it to disk
+    ImportTar {
+        /// Bindle archive file
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Tar archive to import, `-` or nothing reads from stdin
+        #[arg(value_name = "TARBALL")]
+        tarball: Option<PathBuf>,
+        /// Use zstd compression
+        #[arg(short, long)]
+        compress: bool,
+        /// Vacuum when dead space exceeds this fraction of the file size
+        #[arg(long, value_name = "RATIO")]
+        auto_vacuum: Option<f64>,
+    },
+
+    #[cfg(feature = "tar")]
+    /// Write every entry to a tar archive, for tools that don't read bindle archives
+    ExportTar {
+        /// Bindle archive file, `-` reads it from stdin
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Tar archive to create, `-` or nothing writes to stdout
+        #[arg(value_name = "OUT")]
+        out: Option<PathBuf>,
+    },
+
+    /// Split the archive into volumes of bounded size that can each be read on their own
+    Split {
+        /// Bindle archive file, `-` reads it from stdin
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Path prefix of the volumes, written as PREFIX.001.bndl, PREFIX.002.bndl and so on
+        #[arg(value_name = "PREFIX")]
+        prefix: PathBuf,
+        /// Largest size of a volume in bytes, an entry larger than this gets a volume of its own
+        #[arg(long, value_name = "BYTES")]
+        max_bytes: u64,
+    },
+
+    /// Join volumes made by split into a new archive
+    Join {
+        /// Bindle archive file to create
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Volumes to copy entries from, in order
+        #[arg(value_name = "VOLUME", required = true)]
+        volumes: Vec<PathBuf>,
     },
 
     /// Unpack the archive to a local directory
     Unpack {
-        /// Bindle archive file
+        /// Bindle archive file, `-` reads it from stdin
         #[arg(value_name = "BINDLE_FILE")]
         bindle_file: PathBuf,
         /// Destination directory
         #[arg(value_name = "DEST_DIR")]
         dest_dir: PathBuf,
+        /// Only extract entries matching this glob (repeatable)
+        #[arg(long, visible_alias = "glob", value_name = "GLOB")]
+        include: Vec<String>,
+        /// Skip entries matching this glob (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Only extract entries whose names start with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
     },
 
     /// Reclaim space by removing shadowed/deleted data
@@ -105,57 +272,346 @@ enum Commands {
         #[arg(value_name = "BINDLE_FILE")]
         bindle_file: PathBuf,
     },
+
+    #[command(visible_alias = "info")]
+    /// Summarize the archive layout and reclaimable space
+    Stats {
+        /// Bindle archive file, `-` reads it from stdin
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+        /// Output format, `json` prints one object with the same fields in snake_case
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Check the CRC32 of every entry
+    Verify {
+        /// Bindle archive file, `-` reads it from stdin
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+    },
+
+    /// Repair a damaged archive, keeping the entries that pass their CRC32 check
+    Recover {
+        /// Bindle archive file
+        #[arg(value_name = "BINDLE_FILE")]
+        bindle_file: PathBuf,
+    },
+
+    /// List entries added, removed or changed between two archives
+    Diff {
+        /// Archive to compare from, `-` reads it from stdin
+        #[arg(value_name = "LEFT")]
+        left: PathBuf,
+        /// Archive to compare to, `-` reads it from stdin
+        #[arg(value_name = "RIGHT")]
+        right: PathBuf,
+        /// Output format, `json` prints an array of {name, status, left_crc, right_crc}
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OnConflict {
+    /// Keep the existing entry
+    Skip,
+    /// Replace the existing entry
+    Overwrite,
+    /// Fail before copying anything
+    Error,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Sort {
+    /// Alphabetical by name
+    Name,
+    /// Largest packed size first
+    Size,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Aligned columns for reading
+    Text,
+    /// JSON for scripts
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SymlinkMode {
+    /// Pack what the link points to
+    Follow,
+    /// Store the link itself
+    Store,
+    /// Leave links out
+    Skip,
+}
+
+/// Exit status for failures without a status of their own, clap uses 2 for usage errors.
+const EXIT_ERROR: i32 = 1;
+/// Exit status when `read` or `extract` names an entry that doesn't exist.
+const EXIT_READ_MISSING: i32 = 3;
+/// Exit status when `remove` or `rename` names an entry that doesn't exist, so scripts can treat
+/// removing twice as done.
+const EXIT_REMOVE_MISSING: i32 = 4;
+
+/// An entry named on the command line doesn't exist, the process exits with `code`.
+#[derive(Debug)]
+struct MissingEntry {
+    code: i32,
+    message: String,
+}
+
+impl std::fmt::Display for MissingEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for MissingEntry {}
+
+fn missing_entry(code: i32, message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, MissingEntry { code, message })
+}
+
+fn compress_mode(compress: bool, level: Option<i32>) -> Compress {
+    if compress || level.is_some() {
+        Compress::Zstd
+    } else {
+        Compress::None
+    }
+}
+
+fn glob_set(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Prints a running file count to stderr, when it's a terminal, as each file finishes.
+fn print_progress(event: PackEvent) {
+    if !event.finished || !io::stderr().is_terminal() {
+        return;
+    }
+    eprint!(
+        "\r{}/{} files, {} bytes",
+        event.done, event.total, event.bytes
+    );
+    if event.done == event.total {
+        eprintln!();
+    }
+}
+
+/// Warns that the symlink entry `name` was unpacked as a regular file holding its target, because
+/// the platform couldn't create the link. Printed even with `--quiet`, like errors.
+fn warn_link_as_file(name: &str) {
+    eprintln!(
+        "WARNING '{}' written as a regular file, unable to create a symbolic link",
+        name
+    );
+}
+
+/// Calls `read` for each name, reporting failures to stderr and carrying on with the rest.
+///
+/// Returns an error after the last name if any of them failed.
+fn read_each(names: &[String], mut read: impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+    let (mut missing, mut failed) = (0, 0);
+    for name in names {
+        match read(name) {
+            Ok(()) => {}
+            // The reader of our output went away, there's no point in going on
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Err(e),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                eprintln!("MISSING '{}'", name);
+                missing += 1;
+            }
+            Err(e) => {
+                eprintln!("ERROR '{}': {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    let message = format!(
+        "{} of {} entries could not be read",
+        missing + failed,
+        names.len()
+    );
+    match (missing, failed) {
+        (0, 0) => Ok(()),
+        // Only report missing entries with their own status when nothing else went wrong
+        (_, 0) => Err(missing_entry(EXIT_READ_MISSING, message)),
+        _ => Err(io::Error::other(message)),
+    }
+}
+
+/// Opens an archive for reading, `-` reads the whole archive from stdin into memory.
+fn open_readonly(path: &Path) -> io::Result<Bindle> {
+    if path.as_os_str() != "-" {
+        return Bindle::open_readonly(path);
+    }
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Bindle::from_vec(bytes)
+}
+
+/// Rejects `-` as the archive of a command that writes, since stdin can't be written back.
+fn writable(path: &Path) -> io::Result<()> {
+    if path.as_os_str() == "-" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "'-' reads from stdin and can only be used by commands that don't write",
+        ));
+    }
+    Ok(())
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns the lowercase name of a compression mode.
+fn compression_name(compress: Compress) -> &'static str {
+    match compress {
+        Compress::None => "none",
+        Compress::Zstd => "zstd",
+        Compress::Gzip => "gzip",
+        Compress::Auto => "auto",
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
 
-    if let Err(e) = handle_command(cli.command) {
+    if let Err(e) = handle_command(cli.command, cli.quiet) {
         eprintln!("ERROR {}", e);
-        process::exit(1);
+        let missing = e.get_ref().and_then(|e| e.downcast_ref::<MissingEntry>());
+        process::exit(missing.map_or(EXIT_ERROR, |m| m.code));
     }
 }
 
-fn handle_command(command: Commands) -> io::Result<()> {
-    let init = |path: PathBuf| match Bindle::open(&path) {
+fn handle_command(command: Commands, quiet: bool) -> io::Result<()> {
+    // Status lines go to stderr so they never mix with data written to stdout
+    macro_rules! status {
+        ($($arg:tt)*) => {
+            if !quiet {
+                eprintln!($($arg)*);
+            }
+        };
+    }
+    let progress = |event: PackEvent| {
+        if !quiet {
+            print_progress(event);
+        }
+    };
+
+    let init = |path: PathBuf| match writable(&path).and_then(|()| Bindle::open(&path)) {
         Ok(bindle) => bindle,
         Err(e) => {
             eprintln!("ERROR unable to open {}: {}", path.display(), e);
-            process::exit(1);
+            process::exit(EXIT_ERROR);
         }
     };
 
-    let init_load = |path: PathBuf| match Bindle::load(&path) {
+    let init_load = |path: PathBuf| match writable(&path).and_then(|()| Bindle::load(&path)) {
         Ok(bindle) => bindle,
         Err(e) => {
             eprintln!("ERROR unable to open {}: {}", path.display(), e);
-            process::exit(1);
+            process::exit(EXIT_ERROR);
+        }
+    };
+
+    let init_readonly = |path: PathBuf| match open_readonly(&path) {
+        Ok(bindle) => bindle,
+        Err(e) => {
+            eprintln!("ERROR unable to open {}: {}", path.display(), e);
+            process::exit(EXIT_ERROR);
         }
     };
 
     match command {
-        Commands::List { bindle_file } => {
-            println!(
-                "{:<30} {:<12} {:<12} {:<10}",
-                "NAME", "SIZE", "PACKED", "RATIO"
-            );
-            println!("{}", "-".repeat(70));
-            if !bindle_file.exists() {
-                return Ok(());
+        Commands::List {
+            bindle_file,
+            sort,
+            prefix,
+            names,
+            format,
+        } => {
+            if !names && let Format::Text = format {
+                println!(
+                    "{:<30} {:<12} {:<12} {:<10}",
+                    "NAME", "SIZE", "PACKED", "RATIO"
+                );
+                println!("{}", "-".repeat(70));
             }
-            let b = init_load(bindle_file);
-
-            for (name, entry) in b.index().iter() {
-                let size = entry.uncompressed_size();
-                let packed = entry.compressed_size();
-
-                let ratio = if size > 0 {
-                    (packed as f64 / size as f64) * 100.0
-                } else {
-                    100.0
-                };
+            let entries: Vec<_> = if bindle_file.as_os_str() == "-" || bindle_file.exists() {
+                let b = init_readonly(bindle_file);
+                let prefix = prefix.as_deref().unwrap_or("");
+                match sort {
+                    Sort::Name => b
+                        .names_with_prefix(prefix)
+                        .filter_map(|name| b.stat(name))
+                        .collect(),
+                    Sort::Size => b
+                        .entries_by_size(true)
+                        .into_iter()
+                        .filter(|info| info.name.starts_with(prefix))
+                        .collect(),
+                }
+            } else {
+                Vec::new()
+            };
 
-                println!("{:<30} {:<12} {:<12} {:.1}%", name, size, packed, ratio);
+            if let Format::Json = format {
+                let items: Vec<_> = entries
+                    .iter()
+                    .map(|info| {
+                        format!(
+                            "{{\"name\":{},\"size\":{},\"packed\":{},\"ratio\":{},\"compression\":\"{}\"}}",
+                            json_string(&info.name),
+                            info.uncompressed_size,
+                            info.compressed_size,
+                            info.ratio(),
+                            compression_name(info.compression)
+                        )
+                    })
+                    .collect();
+                println!("[{}]", items.join(","));
+                return Ok(());
+            }
+            for info in entries {
+                if names {
+                    println!("{}", info.name);
+                    continue;
+                }
+                println!(
+                    "{:<30} {:<12} {:<12} {:.1}%",
+                    info.name,
+                    info.uncompressed_size,
+                    info.compressed_size,
+                    info.ratio() * 100.0
+                );
             }
         }
 
@@ -164,143 +620,513 @@ fn handle_command(command: Commands) -> io::Result<()> {
             file_path,
             data: data_arg,
             compress,
+            level,
+            seekable,
+            long,
             bindle_file,
             vacuum,
+            auto_vacuum,
         } => {
             let mut b = init(bindle_file.clone());
-            let compress_mode = if compress {
-                Compress::Zstd
-            } else {
-                Compress::None
-            };
+            let compress_mode = compress_mode(compress, level);
+            let level = level.unwrap_or(DEFAULT_ZSTD_LEVEL);
 
             // Determine data source and method: --data flag, file path, or stdin
-            let size = if let Some(d) = data_arg {
+            let size = if seekable || long.is_some() {
+                // Seekable and long window entries always go through a streaming writer
+                let mut src: Box<dyn io::Read> = match (data_arg, &file_path) {
+                    (Some(d), _) => Box::new(io::Cursor::new(d.into_bytes())),
+                    (None, Some(path)) => Box::new(std::fs::File::open(path)?),
+                    (None, None) => Box::new(io::stdin()),
+                };
+                let mut writer = match long {
+                    Some(window_log) => b.writer_with_params(
+                        &name,
+                        ZstdParams {
+                            level,
+                            window_log: Some(window_log),
+                            long_mode: true,
+                        },
+                    )?,
+                    None => b.seekable_writer(&name, DEFAULT_FRAME_SIZE)?,
+                };
+                let size = io::copy(&mut src, &mut writer)?;
+                writer.close()?;
+                size as usize
+            } else if let Some(d) = data_arg {
                 // Direct data from argument
                 let bytes = d.into_bytes();
                 let len = bytes.len();
-                b.add(&name, &bytes, compress_mode)?;
+                b.add_with_level(&name, &bytes, compress_mode, level)?;
                 len
             } else if let Some(path) = file_path {
                 // Use add_file to avoid loading entire file into memory
-                b.add_file(&name, &path, compress_mode)?;
+                b.add_file_with_level(&name, &path, compress_mode, level)?;
                 std::fs::metadata(&path)?.len() as usize
             } else {
-                // Stream from stdin using writer
-                let mut writer = b.writer(&name, compress_mode)?;
-                let size = io::copy(&mut io::stdin(), &mut writer)?;
-                writer.close()?;
-                size as usize
+                // Stream from stdin without buffering it
+                b.add_reader_with_level(&name, io::stdin(), compress_mode, level)? as usize
             };
 
-            println!(
+            status!(
                 "ADD '{}' -> {} ({} bytes)",
                 name,
                 bindle_file.display(),
                 size
             );
+            b.set_auto_vacuum(auto_vacuum)?;
             b.save()?;
 
             if vacuum {
-                println!("VACUUM {}", bindle_file.display());
+                status!("VACUUM {}", bindle_file.display());
                 b.vacuum()?;
             }
 
-            println!("OK");
+            status!("OK");
         }
 
         Commands::Read {
-            name,
+            names,
             bindle_file,
             output,
+            output_dir,
         } => {
-            let b = init_load(bindle_file.clone());
-            let res = if let Some(output) = &output {
-                b.read_to(name.as_str(), std::fs::File::create(output)?)
-            } else {
-                b.read_to(name.as_str(), io::stdout())
-            };
-            match res {
-                Ok(_n) => {
-                    if output.is_some() {
-                        println!("OK")
+            let b = init_readonly(bindle_file.clone());
+            let output = output.filter(|path| path.as_os_str() != "-");
+            if let Some(dir) = &output_dir {
+                read_each(&names, |name| {
+                    let path = b.extract(name, dir)?;
+                    if b.entry(name).is_some_and(|e| e.is_symlink()) && !path.is_symlink() {
+                        warn_link_as_file(name);
                     }
-                }
-                Err(e) => {
-                    return Err(io::Error::new(io::ErrorKind::NotFound, e));
-                }
+                    status!("EXTRACT '{}' -> {}", name, path.display());
+                    Ok(())
+                })?;
+                status!("OK");
+            } else if let Some(output) = &output {
+                let mut file = std::fs::File::create(output)?;
+                read_each(&names, |name| b.read_to(name, &mut file).map(|_| ()))?;
+                status!("OK");
+            } else {
+                // Rust writes stdout unchanged, with no newline translation on Windows, so binary
+                // entries come through intact. Locking once avoids relocking for every chunk.
+                let mut stdout = io::stdout().lock();
+                read_each(&names, |name| {
+                    b.read_to(name, &mut stdout).and_then(|_| stdout.flush())
+                })?;
             }
         }
 
+        Commands::Extract {
+            bindle_file,
+            name,
+            dest_dir,
+        } => {
+            let b = init_readonly(bindle_file.clone());
+            if !b.exists(&name) {
+                return Err(missing_entry(
+                    EXIT_READ_MISSING,
+                    format!("'{}' not found in {}", name, bindle_file.display()),
+                ));
+            }
+            let path = b.extract(&name, &dest_dir)?;
+            if b.entry(&name).is_some_and(|e| e.is_symlink()) && !path.is_symlink() {
+                warn_link_as_file(&name);
+            }
+            status!("EXTRACT '{}' -> {}", name, path.display());
+            status!("OK");
+        }
+
         Commands::Remove {
             name,
             bindle_file,
             vacuum,
+            auto_vacuum,
         } => {
             let mut b = init(bindle_file.clone());
             if b.remove(&name) {
-                println!("REMOVE '{}' from {}", name, bindle_file.display());
+                status!("REMOVE '{}' from {}", name, bindle_file.display());
+                b.set_auto_vacuum(auto_vacuum)?;
                 b.save()?;
 
                 if vacuum {
-                    println!("VACUUM {}", bindle_file.display());
+                    status!("VACUUM {}", bindle_file.display());
                     b.vacuum()?;
                 }
 
-                println!("OK");
+                status!("OK");
             } else {
-                return Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("ERROR '{}' not found in {}", name, bindle_file.display()),
+                return Err(missing_entry(
+                    EXIT_REMOVE_MISSING,
+                    format!("'{}' not found in {}", name, bindle_file.display()),
+                ));
+            }
+        }
+
+        Commands::Rename {
+            bindle_file,
+            old,
+            new,
+            force,
+            auto_vacuum,
+        } => {
+            let mut b = init_load(bindle_file.clone());
+            let renamed = if force {
+                b.rename_overwrite(&old, &new)?
+            } else {
+                b.rename(&old, &new)?
+            };
+            if !renamed {
+                return Err(missing_entry(
+                    EXIT_REMOVE_MISSING,
+                    format!("'{}' not found in {}", old, bindle_file.display()),
                 ));
             }
+            status!("RENAME '{}' -> '{}' in {}", old, new, bindle_file.display());
+            b.set_auto_vacuum(auto_vacuum)?;
+            b.save()?;
+            status!("OK");
+        }
+
+        Commands::Merge {
+            bindle_file,
+            sources,
+            on_conflict,
+            auto_vacuum,
+        } => {
+            let on_conflict = match on_conflict {
+                OnConflict::Skip => Conflict::Skip,
+                OnConflict::Overwrite => Conflict::Overwrite,
+                OnConflict::Error => Conflict::Error,
+            };
+            let mut b = init(bindle_file.clone());
+            for src in sources {
+                let other = init_readonly(src.clone());
+                let copied = b.merge(&other, on_conflict)?;
+                status!(
+                    "MERGE {} -> {} ({} entries)",
+                    src.display(),
+                    bindle_file.display(),
+                    copied
+                );
+            }
+            b.set_auto_vacuum(auto_vacuum)?;
+            b.save()?;
+            status!("OK");
         }
 
         Commands::Pack {
             bindle_file,
             src_dir,
             compress,
+            level,
             append,
+            exclude,
+            symlinks,
+            dedup,
             vacuum,
+            auto_vacuum,
         } => {
-            println!("PACK {} -> {}", src_dir.display(), bindle_file.display());
+            let symlinks = match symlinks {
+                SymlinkMode::Follow => Symlinks::Follow,
+                SymlinkMode::Store => Symlinks::Store,
+                SymlinkMode::Skip => Symlinks::Skip,
+            };
+            let options = PackOptions::new()
+                .exclude(&exclude)?
+                .symlinks(symlinks)
+                .dedup(dedup);
+            status!("PACK {} -> {}", src_dir.display(), bindle_file.display());
             let mut b = init(bindle_file.clone());
             if !append {
                 b.clear();
             }
-            b.pack(
+            let stats = b.pack_with_options_and_progress(
                 src_dir,
-                if compress {
-                    Compress::Zstd
-                } else {
-                    Compress::None
-                },
+                compress_mode(compress, level),
+                level.unwrap_or(DEFAULT_ZSTD_LEVEL),
+                &options,
+                progress,
             )?;
+            status!(
+                "PACKED {} files, {} -> {} bytes ({:.1}%), {} skipped",
+                stats.files,
+                stats.total_uncompressed,
+                stats.total_compressed,
+                stats.ratio() * 100.0,
+                stats.skipped
+            );
+            b.set_auto_vacuum(auto_vacuum)?;
             b.save()?;
 
             if vacuum {
-                println!("VACUUM {}", bindle_file.display());
+                status!("VACUUM {}", bindle_file.display());
                 b.vacuum()?;
             }
 
-            println!("OK");
+            status!("OK");
+        }
+
+        #[cfg(feature = "tar")]
+        Commands::ImportTar {
+            bindle_file,
+            tarball,
+            compress,
+            auto_vacuum,
+        } => {
+            let tarball = tarball.filter(|path| path.as_os_str() != "-");
+            let source = tarball
+                .as_ref()
+                .map_or("stdin".into(), |path| path.display().to_string());
+            status!("IMPORT {} -> {}", source, bindle_file.display());
+            let mut b = init(bindle_file);
+            let compress = compress_mode(compress, None);
+            let stats = match &tarball {
+                Some(path) => {
+                    b.import_tar(io::BufReader::new(std::fs::File::open(path)?), compress)?
+                }
+                None => b.import_tar(io::stdin().lock(), compress)?,
+            };
+            status!(
+                "IMPORTED {} files, {} -> {} bytes ({:.1}%), {} skipped",
+                stats.files,
+                stats.total_uncompressed,
+                stats.total_compressed,
+                stats.ratio() * 100.0,
+                stats.skipped
+            );
+            b.set_auto_vacuum(auto_vacuum)?;
+            b.save()?;
+            status!("OK");
+        }
+
+        #[cfg(feature = "tar")]
+        Commands::ExportTar { bindle_file, out } => {
+            let out = out.filter(|path| path.as_os_str() != "-");
+            let b = init_readonly(bindle_file.clone());
+            match &out {
+                Some(path) => {
+                    status!("EXPORT {} -> {}", bindle_file.display(), path.display());
+                    let file = std::fs::File::create(path)?;
+                    b.export_tar(io::BufWriter::new(file))?;
+                }
+                None => b.export_tar(io::BufWriter::new(io::stdout().lock()))?,
+            }
+            status!("OK");
+        }
+
+        Commands::Split {
+            bindle_file,
+            prefix,
+            max_bytes,
+        } => {
+            let b = init_readonly(bindle_file.clone());
+            for volume in b.split(&prefix, max_bytes)? {
+                status!("VOLUME {}", volume.display());
+            }
+            status!("OK");
+        }
+
+        Commands::Join {
+            bindle_file,
+            volumes,
+        } => {
+            writable(&bindle_file)?;
+            let b = Bindle::join(&volumes, &bindle_file)?;
+            status!(
+                "JOIN {} volumes -> {} ({} entries)",
+                volumes.len(),
+                bindle_file.display(),
+                b.len()
+            );
+            status!("OK");
         }
 
         Commands::Unpack {
             bindle_file,
             dest_dir,
+            include,
+            exclude,
+            prefix,
         } => {
-            println!("UNPACK {} -> {}", bindle_file.display(), dest_dir.display());
-            let b = init_load(bindle_file);
-            b.unpack(dest_dir)?;
-            println!("OK");
+            let include_set = glob_set(&include)?;
+            let exclude_set = glob_set(&exclude)?;
+            status!("UNPACK {} -> {}", bindle_file.display(), dest_dir.display());
+            let b = init_readonly(bindle_file);
+            let prefix = prefix.unwrap_or_default();
+            b.unpack_matching_with_progress(
+                dest_dir,
+                |name| {
+                    name.starts_with(&prefix)
+                        && (include.is_empty() || include_set.is_match(name))
+                        && !exclude_set.is_match(name)
+                },
+                |event| {
+                    if event.link_as_file {
+                        warn_link_as_file(event.name);
+                    }
+                    progress(event);
+                },
+            )?;
+            status!("OK");
         }
 
         Commands::Vacuum { bindle_file } => {
-            println!("VACUUM {}", bindle_file.display());
+            status!("VACUUM {}", bindle_file.display());
             let mut b = init_load(bindle_file);
             b.vacuum()?;
-            println!("OK");
+            status!("OK");
+        }
+
+        Commands::Stats {
+            bindle_file,
+            format,
+        } => {
+            let file_size = if bindle_file.as_os_str() == "-" {
+                None
+            } else {
+                Some(std::fs::metadata(&bindle_file)?.len())
+            };
+            let b = init_readonly(bindle_file);
+            let file_size = file_size.unwrap_or_else(|| b.committed_len());
+            let (size, packed) = b.index().values().fold((0, 0), |(size, packed), entry| {
+                (
+                    size + entry.uncompressed_size(),
+                    packed + entry.compressed_size(),
+                )
+            });
+            let ratio = if size > 0 {
+                packed as f64 / size as f64
+            } else {
+                1.0
+            };
+
+            match format {
+                Format::Text => {
+                    println!("{:<14} BINDL{:03}", "FORMAT", b.version());
+                    println!("{:<14} {}", "INDEX OFFSET", b.index_offset());
+                    println!("{:<14} {}", "ENTRIES", b.len());
+                    println!("{:<14} {}", "SIZE", size);
+                    println!("{:<14} {}", "PACKED", packed);
+                    println!("{:<14} {:.1}%", "RATIO", ratio * 100.0);
+                    println!("{:<14} {}", "FILE SIZE", file_size);
+                    println!("{:<14} {}", "RECLAIMABLE", b.dead_space());
+                    println!(
+                        "{:<14} {:.1}%",
+                        "FRAGMENTATION",
+                        b.fragmentation_ratio() * 100.0
+                    );
+                }
+                Format::Json => println!(
+                    "{{\"format\":{},\"index_offset\":{},\"entries\":{},\"size\":{},\"packed\":{},\"ratio\":{},\"file_size\":{},\"reclaimable\":{},\"fragmentation\":{}}}",
+                    b.version(),
+                    b.index_offset(),
+                    b.len(),
+                    size,
+                    packed,
+                    ratio,
+                    file_size,
+                    b.dead_space(),
+                    b.fragmentation_ratio()
+                ),
+            }
+        }
+
+        Commands::Verify { bindle_file } => {
+            let b = init_readonly(bindle_file.clone());
+            if b.version() < 3 {
+                eprintln!(
+                    "WARNING {} uses format version {}, the index has no checksum",
+                    bindle_file.display(),
+                    b.version()
+                );
+            }
+            let mut failed = 0;
+            for (name, ok) in b.verify()? {
+                if ok {
+                    println!("OK     {}", name);
+                } else {
+                    println!("FAILED {}", name);
+                    failed += 1;
+                }
+            }
+
+            if failed > 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} corrupt entries in {}", failed, bindle_file.display()),
+                ));
+            }
+        }
+
+        Commands::Recover { bindle_file } => {
+            status!("RECOVER {}", bindle_file.display());
+            let mut b = Bindle::recover(&bindle_file)?;
+            for (name, ok) in b.verify()? {
+                if !ok {
+                    status!("DROP    {}", name);
+                    b.remove(&name);
+                }
+            }
+            for name in b.names().filter(|name| name.starts_with("recovered/")) {
+                status!("FOUND   {}", name);
+            }
+            b.vacuum()?;
+            status!("OK {} entries", b.len());
+        }
+
+        Commands::Diff {
+            left,
+            right,
+            format,
+        } => {
+            let left = init_readonly(left);
+            let right = init_readonly(right);
+
+            // Only the index is compared, entries are never decompressed
+            let mut changes = Vec::new();
+            for (name, l) in left.entries() {
+                match right.entry(name) {
+                    None => changes.push((name, "removed", Some(l.crc32()), None)),
+                    Some(r)
+                        if l.crc32() != r.crc32()
+                            || l.uncompressed_size() != r.uncompressed_size() =>
+                    {
+                        changes.push((name, "changed", Some(l.crc32()), Some(r.crc32())))
+                    }
+                    Some(_) => {}
+                }
+            }
+            for (name, r) in right.entries() {
+                if !left.exists(name) {
+                    changes.push((name, "added", None, Some(r.crc32())));
+                }
+            }
+            changes.sort_by_key(|(name, ..)| *name);
+
+            match format {
+                Format::Text => {
+                    for (name, status, _, _) in changes {
+                        println!("{:<8} {}", status.to_uppercase(), name);
+                    }
+                }
+                Format::Json => {
+                    let crc = |crc: Option<u32>| crc.map_or("null".to_string(), |c| c.to_string());
+                    let items: Vec<_> = changes
+                        .into_iter()
+                        .map(|(name, status, l, r)| {
+                            format!(
+                                "{{\"name\":{},\"status\":\"{}\",\"left_crc\":{},\"right_crc\":{}}}",
+                                json_string(name),
+                                status,
+                                crc(l),
+                                crc(r)
+                            )
+                        })
+                        .collect();
+                    println!("[{}]", items.join(","));
+                }
+            }
         }
     }
     Ok(())
@@ -1,26 +1,55 @@
+use aes_gcm::Aes256Gcm;
 use crc32fast::Hasher;
+use flate2::write::GzEncoder;
 use memmap2::Mmap;
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zerocopy::{FromBytes, IntoBytes};
 
-use crate::compress::Compress;
-use crate::entry::{Entry, Footer};
-use crate::reader::{Either, Reader};
-use crate::writer::Writer;
+use crate::bindle_ref::BindleRef;
+use crate::compress::{Compress, ZstdParams};
+use crate::conflict::Conflict;
+use crate::crypto;
+use crate::entry::{
+    ENTRY_FLAG_DICT, ENTRY_FLAG_ENCRYPTED, ENTRY_FLAG_LONG_WINDOW, ENTRY_FLAG_SYMLINK, Entry,
+    EntryMeta, Footer, MAX_USER_META,
+};
+use crate::entry_info::EntryInfo;
+use crate::error::BindleError;
+use crate::limits::Limits;
+use crate::options::{Backend, Options, SyncPolicy};
+use crate::owned_reader::OwnedReader;
+use crate::pack::{PackEvent, PackOptions, PackStats, Symlinks};
+use crate::read_only::ReadOnlyBindle;
+use crate::reader::Reader;
+use crate::seekable::MAX_FRAME_SIZE;
+use crate::storage::{Mapping, Region, Storage};
+use crate::tree::{TreeNode, dir_prefix};
+use crate::view::{ReadOnlyView, ViewData};
+use crate::writer::{Encoder, Frames, Writer};
 use crate::{
-    AUTO_COMPRESS_THRESHOLD, BNDL_ALIGN, BNDL_MAGIC, ENTRY_SIZE, FOOTER_MAGIC, FOOTER_SIZE,
-    HEADER_SIZE, pad, write_padding,
+    AUTO_COMPRESS_THRESHOLD, BNDL_ALIGN, BNDL_MAGIC, DEFAULT_ZSTD_LEVEL, DICT_ENTRY, ENTRY_SIZE,
+    FOOTER_MAGIC, FOOTER_SIZE, FORMAT_VERSION, HEADER_SIZE, INDEX_CRC_SIZE, MAX_PREALLOC,
+    ZSTD_DEFAULT_WINDOW_LOG_MAX, ZSTD_WINDOW_LOGS, check_level, check_name, header_version, pad,
+    write_padding, zstd_window_log,
 };
 
 /// A binary archive for collecting files.
 ///
-/// Uses memory-mapped I/O for fast reads, supports optional zstd compression, and handles updates via shadowing.
+/// Uses memory-mapped I/O for fast reads by default (see [`Backend`]), supports optional zstd compression, and handles updates via shadowing.
 /// Files can be added incrementally without rewriting the entire archive.
 ///
+/// Writes are appended after the committed footer, so other processes that already opened the archive keep
+/// reading the last committed state while a writer works. Only one process should write at a time.
+///
 /// # Example
 ///
 /// ```no_run
@@ -33,10 +62,32 @@ use crate::{
 /// ```
 pub struct Bindle {
     pub(crate) path: PathBuf,
-    pub(crate) file: File,
-    pub(crate) mmap: Option<Mmap>,
+    pub(crate) storage: Storage,
+    pub(crate) mapping: Mapping,
     pub(crate) index: BTreeMap<String, Entry>,
+    pub(crate) meta: BTreeMap<String, EntryMeta>,
     pub(crate) data_end: u64,
+    pub(crate) committed_len: u64,
+    pub(crate) version: u8,
+    pub(crate) writable: bool,
+    pub(crate) auto_vacuum_ratio: Option<f64>,
+    /// Entries no larger than this are stored uncompressed by [`Compress::Auto`].
+    pub(crate) auto_compress_threshold: usize,
+    pub(crate) hash_sha256: bool,
+    pub(crate) dedup: Option<HashMap<[u8; 32], String>>,
+    /// Lowercased names mapped to the stored name, set by [`set_case_insensitive()`](Bindle::set_case_insensitive).
+    folded_names: Option<HashMap<String, String>>,
+    /// True if the index has changes that haven't been saved.
+    pub(crate) dirty: bool,
+    pub(crate) autosave: bool,
+    sync: SyncPolicy,
+    limits: Limits,
+    /// Encrypts new entries and decrypts encrypted ones, set by [`open_encrypted()`](Bindle::open_encrypted).
+    pub(crate) cipher: Option<Aes256Gcm>,
+    /// Index record of the zstd dictionary, kept out of `index` so it isn't listed as an entry.
+    pub(crate) dict_entry: Option<Entry>,
+    /// The zstd dictionary, `None` if there is none or it's encrypted and no key was given.
+    pub(crate) dict: Option<Arc<[u8]>>,
 }
 
 impl Bindle {
@@ -63,6 +114,52 @@ impl Bindle {
         Self::new(path_buf, opts)
     }
 
+    /// Opens or creates an archive like [`open()`](Bindle::open) with the given options.
+    pub fn open_with<P: AsRef<Path>>(path: P, options: Options) -> io::Result<Self> {
+        let path_buf = path.as_ref().to_path_buf();
+        let opts = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .to_owned();
+        let mut bindle = Self::with_options(path_buf, opts, true, options.backend)?;
+        bindle.autosave = options.autosave;
+        bindle.sync = options.sync;
+        Ok(bindle)
+    }
+
+    /// Opens or creates an archive like [`open()`](Bindle::open), encrypting entry data with `key`.
+    ///
+    /// New entries are compressed, then encrypted with AES-256-GCM under a random nonce stored in front
+    /// of the data, so streamed entries are buffered in memory until their writer closes and no
+    /// plaintext is ever written to the file. The entry name is authenticated with the data, so data
+    /// moved to another entry fails to decrypt. Names, sizes and metadata in the index are not
+    /// encrypted; SHA-256 hashes and deduplication are turned off, since a plaintext hash would give
+    /// away which entries hold the same contents. Entries copied or merged from another archive keep
+    /// their encryption, so both archives need the same key. A wrong key fails authentication on the
+    /// first read of an encrypted entry with an [`io::ErrorKind::InvalidData`] error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let key = [7u8; 32];
+    /// let mut archive = Bindle::open_encrypted("secret.bndl", &key)?;
+    /// archive.add("token.txt", b"hunter2", Compress::Zstd)?;
+    /// archive.save()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn open_encrypted<P: AsRef<Path>>(path: P, key: &[u8; 32]) -> io::Result<Self> {
+        let mut bindle = Self::open(path)?;
+        let cipher = crypto::cipher(key);
+        if let (Some(entry), None) = (bindle.dict_entry, &bindle.dict) {
+            bindle.dict = load_dictionary(bindle.stored(&entry)?, &entry, Some(&cipher))?;
+        }
+        bindle.cipher = Some(cipher);
+        Ok(bindle)
+    }
+
     /// Opens an existing archive. Returns an error if the file doesn't exist.
     pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         let path_buf = path.as_ref().to_path_buf();
@@ -70,473 +167,4049 @@ impl Bindle {
         Self::new(path_buf, opts)
     }
 
+    /// Opens an existing archive without requesting write access.
+    ///
+    /// Works on read-only mounts and can be shared by many processes. Reads behave as usual, while
+    /// [`add()`](Bindle::add), [`writer()`](Bindle::writer), [`save()`](Bindle::save) and
+    /// [`vacuum()`](Bindle::vacuum) return [`io::ErrorKind::PermissionDenied`].
+    pub fn open_readonly<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path_buf = path.as_ref().to_path_buf();
+        let opts = OpenOptions::new().read(true).to_owned();
+        Self::with_options(path_buf, opts, false, Backend::Mmap)
+    }
+
     /// Create a new `Bindle` from a path and file, the path must match the file
     pub fn new(path: PathBuf, opts: OpenOptions) -> io::Result<Self> {
+        Self::with_options(path, opts, true, Backend::Mmap)
+    }
+
+    fn with_options(
+        path: PathBuf,
+        opts: OpenOptions,
+        writable: bool,
+        backend: Backend,
+    ) -> io::Result<Self> {
         let mut file = opts.open(&path)?;
         file.lock_shared()?;
         let len = file.metadata()?.len();
 
         // Handle completely new/empty files
-        if len == 0 {
+        if len == 0 && writable {
             file.write_all(BNDL_MAGIC)?;
+            file.unlock()?;
             return Ok(Self {
                 path,
-                file,
-                mmap: None,
+                storage: Storage::File(file),
+                mapping: Mapping::new(backend),
                 index: BTreeMap::new(),
+                meta: BTreeMap::new(),
                 data_end: HEADER_SIZE as u64,
+                committed_len: HEADER_SIZE as u64,
+                version: FORMAT_VERSION,
+                writable,
+                auto_vacuum_ratio: None,
+                auto_compress_threshold: AUTO_COMPRESS_THRESHOLD,
+                hash_sha256: false,
+                dedup: None,
+                folded_names: None,
+                dirty: false,
+                autosave: false,
+                sync: SyncPolicy::Data,
+                limits: Limits::default(),
+                cipher: None,
+                dict_entry: None,
+                dict: None,
             });
         }
 
-        // Safety check: File must be at least HEADER + FOOTER size (24 bytes)
-        // This prevents "attempt to subtract with overflow" when calculating footer_pos
-        if len < (HEADER_SIZE + FOOTER_SIZE) as u64 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "File too small to be a valid bindle",
-            ));
-        }
+        // Anything after the last complete commit is left over from an interrupted save, the next
+        // write starts over from the end of that commit
+        let (mapping, parsed) = match backend {
+            Backend::Mmap => {
+                let m = unsafe { Mmap::map(&file)? };
+                let parsed = parse_archive(Region::Memory(&m))?;
+                (Mapping::mapped(m), parsed)
+            }
+            Backend::Pread => {
+                let region = Region::File {
+                    file: &file,
+                    offset: 0,
+                    len,
+                };
+                (Mapping::new(backend), parse_archive(region)?)
+            }
+        };
 
-        let mut header = [0u8; 8];
-        file.read_exact(&mut header)?;
-        if &header != BNDL_MAGIC {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid header"));
-        }
+        // Committed bytes are only modified in place by replace_in_place() and compact_in_place(),
+        // which hold an exclusive lock until they commit, so the lock is only needed while the
+        // footer and index are read
+        file.unlock()?;
 
-        let m = unsafe { Mmap::map(&file)? };
+        Self::from_parsed(path, Storage::File(file), mapping, parsed, writable)
+    }
 
-        // Calculate footer position. Subtraction is now safe due to the check above.
-        let footer_pos = m.len() - FOOTER_SIZE;
-        let footer = Footer::read_from_bytes(&m[footer_pos..])
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Failed to read footer"))?;
+    /// Opens an archive whose last save may have been interrupted, repairing the file.
+    ///
+    /// [`open()`](Bindle::open) already falls back to the last complete save, but leaves the bytes
+    /// after it in place until the next save. `recover` truncates a partial trailing write and saves
+    /// right away, and also works when no complete save is left at all. Data written after the last
+    /// save is scanned for zstd and gzip streams at 8-byte aligned offsets, and each one that decodes
+    /// is added back as an entry named `recovered/` followed by its offset in hex. Original names,
+    /// metadata and uncompressed entries from that region can't be recovered, since only the index
+    /// records them. When streams are found the new index is appended after the end of the file, so
+    /// none of the existing bytes are overwritten or truncated.
+    ///
+    /// Returns an error if the file doesn't start with a valid header, and the error that kept it
+    /// from opening if there's neither a complete save nor a stream to recover, such as an archive
+    /// of uncompressed entries with a damaged footer. The file is left unchanged in both cases.
+    ///
+    /// Recovery is best effort: entries that can't be found are dropped, and entries from the last
+    /// complete save are kept without being checked. Use [`verify()`](Bindle::verify) to find
+    /// damaged ones, as `bindle recover` does before rewriting the archive with
+    /// [`vacuum()`](Bindle::vacuum).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::recover("data.bndl")?;
+    /// for name in archive.names().filter(|name| name.starts_with("recovered/")) {
+    ///     println!("recovered {}", name);
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn recover<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        file.lock()?;
+        let m = unsafe { Mmap::map(&file)? };
 
-        if footer.magic() != FOOTER_MAGIC {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Invalid footer, the file may be corrupt",
-            ));
+        let header = m
+            .first_chunk::<HEADER_SIZE>()
+            .ok_or(BindleError::TooSmall)?;
+        let version = header_version(header)?;
+        let (parsed, damaged) = match parse_archive(Region::Memory(&m)) {
+            Ok(parsed) => (parsed, None),
+            Err(
+                e @ (BindleError::InvalidFooter
+                | BindleError::IndexCrcMismatch
+                | BindleError::EntryOutOfBounds { .. }
+                | BindleError::InvalidName(_)
+                | BindleError::TooSmall),
+            ) => {
+                let parsed = Parsed {
+                    version,
+                    committed_len: HEADER_SIZE as u64,
+                    data_end: HEADER_SIZE as u64,
+                    index: BTreeMap::new(),
+                    meta: BTreeMap::new(),
+                    dictionary: None,
+                };
+                (parsed, Some(e))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let recovered = scan_streams(&m, parsed.committed_len as usize);
+        if let Some(e) = damaged
+            && recovered.is_empty()
+            && m.len() > HEADER_SIZE
+        {
+            // Saving would write an empty index over data that may still be salvaged by hand
+            return Err(e.into());
+        }
+        if recovered.is_empty() && parsed.committed_len > HEADER_SIZE as u64 {
+            // Nothing after the last save could be read back, drop the partial write
+            let m = if parsed.committed_len < m.len() as u64 {
+                drop(m);
+                file.set_len(parsed.committed_len)?;
+                file.sync_all()?;
+                unsafe { Mmap::map(&file)? }
+            } else {
+                m
+            };
+            file.unlock()?;
+            let storage = Storage::File(file);
+            return Self::from_parsed(path, storage, Mapping::mapped(m), parsed, true);
         }
+        file.unlock()?;
 
-        let data_end = footer.index_offset();
-        let count = footer.entry_count();
-        let mut index = BTreeMap::new();
+        // The new index goes after everything in the file, uncompressed data between or after the
+        // recovered streams isn't indexed but stays in place
+        let file_len = m.len() as u64;
+        let storage = Storage::File(file);
+        let mut bindle = Self::from_parsed(path, storage, Mapping::mapped(m), parsed, true)?;
+        bindle.data_end = file_len + pad::<BNDL_ALIGN, u64>(file_len);
+        for mut entry in recovered {
+            let name = format!("recovered/{:x}", entry.offset());
+            entry.set_name_len(name.len() as u16);
+            bindle.index.insert(name, entry);
+        }
+        bindle.save()?;
+        Ok(bindle)
+    }
 
-        let mut cursor = data_end as usize;
-        for _ in 0..count {
-            // Ensure there is enough data left for an Entry header
-            if cursor + ENTRY_SIZE > footer_pos {
-                break;
-            }
+    /// Creates an archive from a parsed file, loading its zstd dictionary.
+    fn from_parsed(
+        path: PathBuf,
+        storage: Storage,
+        mapping: Mapping,
+        parsed: Parsed,
+        writable: bool,
+    ) -> io::Result<Self> {
+        let Parsed {
+            version,
+            committed_len,
+            data_end,
+            index,
+            meta,
+            dictionary,
+        } = parsed;
+        let mut bindle = Self {
+            path,
+            storage,
+            mapping,
+            index,
+            meta,
+            data_end,
+            committed_len,
+            version,
+            writable,
+            auto_vacuum_ratio: None,
+            auto_compress_threshold: AUTO_COMPRESS_THRESHOLD,
+            hash_sha256: false,
+            dedup: None,
+            folded_names: None,
+            dirty: false,
+            autosave: false,
+            sync: SyncPolicy::Data,
+            limits: Limits::default(),
+            cipher: None,
+            dict_entry: dictionary,
+            dict: None,
+        };
+        if let Some(entry) = &bindle.dict_entry {
+            bindle.dict = load_dictionary(bindle.stored(entry)?, entry, None)?;
+        }
+        Ok(bindle)
+    }
 
-            let entry = match Entry::read_from_bytes(&m[cursor..cursor + ENTRY_SIZE]) {
-                Ok(e) => e,
-                Err(_) => break, // Corrupted entry, stop reading
-            };
-            let n_start = cursor + ENTRY_SIZE;
+    /// Creates an empty archive backed by a growable buffer instead of a file.
+    ///
+    /// Supports the same operations as a file-backed archive. Use [`into_bytes()`](Bindle::into_bytes)
+    /// to extract the finished archive after calling [`save()`](Bindle::save).
+    pub fn in_memory() -> Self {
+        let mut buf = io::Cursor::new(BNDL_MAGIC.to_vec());
+        buf.set_position(HEADER_SIZE as u64);
+        Self {
+            path: PathBuf::new(),
+            storage: Storage::Memory(buf),
+            mapping: Mapping::new(Backend::Mmap),
+            index: BTreeMap::new(),
+            meta: BTreeMap::new(),
+            data_end: HEADER_SIZE as u64,
+            committed_len: HEADER_SIZE as u64,
+            version: FORMAT_VERSION,
+            writable: true,
+            auto_vacuum_ratio: None,
+            auto_compress_threshold: AUTO_COMPRESS_THRESHOLD,
+            hash_sha256: false,
+            dedup: None,
+            folded_names: None,
+            dirty: false,
+            autosave: false,
+            sync: SyncPolicy::Data,
+            limits: Limits::default(),
+            cipher: None,
+            dict_entry: None,
+            dict: None,
+        }
+    }
 
-            // Validate that the filename exists within the mapped bounds
-            if n_start + entry.name_len() > footer_pos {
-                break;
+    /// Consumes the archive and returns its raw bytes.
+    ///
+    /// Changes that haven't been committed with [`save()`](Bindle::save) are not included.
+    pub fn into_bytes(mut self) -> io::Result<Vec<u8>> {
+        let committed_len = self.committed_len;
+        match std::mem::replace(&mut self.storage, Storage::Memory(io::Cursor::default())) {
+            Storage::Memory(buf) => {
+                let mut buf = buf.into_inner();
+                buf.truncate(committed_len as usize);
+                Ok(buf)
+            }
+            Storage::File(mut file) => {
+                let mut buf = Vec::new();
+                file.seek(SeekFrom::Start(0))?;
+                (&mut file).take(committed_len).read_to_end(&mut buf)?;
+                let _ = file.unlock();
+                Ok(buf)
             }
+        }
+    }
 
-            let name =
-                String::from_utf8_lossy(&m[n_start..n_start + entry.name_len()]).into_owned();
-            index.insert(name, entry);
+    /// Opens an archive from its raw bytes, such as the output of
+    /// [`into_bytes()`](Bindle::into_bytes) or an archive received over the network.
+    ///
+    /// The archive is backed by the buffer like one made with [`in_memory()`](Bindle::in_memory),
+    /// so it can be changed and turned back into bytes. Bytes after the last complete save are
+    /// ignored like [`open()`](Bindle::open) does. Use [`from_bytes()`](Bindle::from_bytes) to read
+    /// a borrowed slice without copying it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("a.txt", b"data", Compress::None)?;
+    /// archive.save()?;
+    ///
+    /// let mut copy = Bindle::from_vec(archive.into_bytes()?)?;
+    /// assert_eq!(copy.read("a.txt")?.as_ref(), b"data");
+    /// copy.add("b.txt", b"more", Compress::Zstd)?;
+    /// copy.save()?;
+    /// assert_eq!(copy.len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_vec(bytes: Vec<u8>) -> io::Result<Self> {
+        let parsed = parse_archive(Region::Memory(&bytes))?;
+        let storage = Storage::Memory(io::Cursor::new(bytes));
+        Self::from_parsed(
+            PathBuf::new(),
+            storage,
+            Mapping::new(Backend::Mmap),
+            parsed,
+            true,
+        )
+    }
 
-            let total = ENTRY_SIZE + entry.name_len();
-            cursor += (total + (BNDL_ALIGN - 1)) & !(BNDL_ALIGN - 1);
-        }
+    /// Opens a read-only archive from any [`Read`] + [`Seek`] source, such as an in-memory buffer
+    /// or a region of a larger file.
+    ///
+    /// The footer and index are parsed up front and entry data is read from the source on demand.
+    pub fn from_reader<R: Read + Seek>(r: R) -> io::Result<ReadOnlyBindle<R>> {
+        ReadOnlyBindle::new(r)
+    }
 
-        Ok(Self {
-            path,
-            file,
-            mmap: Some(m),
-            index,
-            data_end,
-        })
+    /// Opens a read-only archive borrowed from a byte slice, without copying it.
+    ///
+    /// Unlike [`from_reader()`](Bindle::from_reader), uncompressed entries are read straight from
+    /// the slice. Same as `BindleRef::try_from(bytes)`.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<BindleRef<'_>> {
+        BindleRef::try_from(bytes)
     }
 
-    fn should_auto_compress(&self, compress: Compress, len: usize) -> bool {
-        compress == Compress::Zstd || (compress == Compress::Auto && len > AUTO_COMPRESS_THRESHOLD)
+    fn check_writable(&self) -> io::Result<()> {
+        if self.writable {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Archive was opened read-only",
+            ))
+        }
     }
 
     /// Adds data to the archive with the given name.
     ///
     /// If an entry with the same name exists, it will be shadowed. Call [`save()`](Bindle::save) to commit changes.
+    /// Names can be up to [`MAX_NAME_LEN`](crate::MAX_NAME_LEN) bytes, longer names and the reserved
+    /// dictionary name `"\0dict"` are rejected with an [`io::ErrorKind::InvalidInput`] error.
     pub fn add(&mut self, name: &str, data: &[u8], compress: Compress) -> io::Result<()> {
-        let mut stream = self.writer(name, compress)?;
-        stream.write_all(data)?;
-        stream.close()?;
-        Ok(())
+        self.add_with_level(name, data, compress, DEFAULT_ZSTD_LEVEL)
     }
 
-    /// Adds a file from the filesystem to the archive.
+    /// Adds data to the archive, compressing with the given zstd level.
     ///
-    /// Reads the file at `path` and stores it with the given `name`. Call [`save()`](Bindle::save) to commit changes.
-    pub fn add_file(
+    /// See [`writer_with_level()`](Bindle::writer_with_level) for the accepted levels.
+    pub fn add_with_level(
         &mut self,
         name: &str,
-        path: impl AsRef<Path>,
+        data: &[u8],
         compress: Compress,
+        level: i32,
     ) -> io::Result<()> {
-        let mut stream = self.writer(name, compress)?;
-        let mut src = std::fs::File::open(path)?;
-        std::io::copy(&mut src, &mut stream)?;
+        let mut stream = self.writer_with_level(name, compress, level)?;
+        stream.write_all(data)?;
+        stream.close()?;
         Ok(())
     }
 
-    /// Commits all pending changes by writing the index and footer to disk.
+    /// Replaces the data of an entry, reusing its slot when it was written since the last save.
     ///
-    /// Must be called after add/remove operations to make changes persistent.
-    pub fn save(&mut self) -> io::Result<()> {
-        self.file.lock()?;
-        self.file.seek(SeekFrom::Start(self.data_end))?;
-        let index_start = self.data_end;
-
-        // Use buffered writer to batch index writes
-        {
-            let mut writer = BufWriter::new(&mut self.file);
-            for (name, entry) in &self.index {
-                writer.write_all(entry.as_bytes())?;
-                writer.write_all(name.as_bytes())?;
-                let pad = pad::<BNDL_ALIGN, usize>(ENTRY_SIZE + name.len());
-                if pad > 0 {
-                    write_padding(&mut writer, pad)?;
-                }
-            }
-
-            let footer = Footer::new(index_start, self.index.len() as u32, FOOTER_MAGIC);
-            writer.write_all(footer.as_bytes())?;
-            writer.flush()?;
-        } // Drop writer here to release borrow
-
-        // Truncate file to current position to remove any old data
-        let current_pos = self.file.stream_position()?;
-        self.file.set_len(current_pos)?;
-
-        let mmap = unsafe { Mmap::map(&self.file)? };
-        self.mmap = Some(mmap);
-        self.file.lock_shared()?;
-        Ok(())
+    /// Shadowing with [`add()`](Bindle::add) always appends, leaving the old data behind as dead
+    /// space until [`vacuum()`](Bindle::vacuum). Here the data is compressed in memory first, and if
+    /// the result is no larger than the existing entry's slot, its stored size rounded up to the
+    /// 8-byte alignment, and the slot hasn't been committed by [`save()`](Bindle::save) yet, it's
+    /// written over the old data and the index record is updated without appending anything. A slot
+    /// only shrinks, the space freed inside it becomes dead space. Committed data is never modified,
+    /// see [`replace_in_place()`](Bindle::replace_in_place) to overwrite it too.
+    ///
+    /// Otherwise this works like `add()`: the data is appended and shadows the old entry. That is
+    /// also the case when there's no entry named `name`, when the old data is shared with another
+    /// entry by deduplication, and while [views](Bindle::view) or
+    /// [owned readers](Bindle::owned_reader) of this archive are open, since they would read the new
+    /// bytes against the old index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("counter", b"0001", Compress::None)?;
+    /// let offset = archive.entry("counter").unwrap().offset();
+    /// archive.replace("counter", b"0002", Compress::None)?;
+    /// archive.save()?;
+    /// assert_eq!(archive.entry("counter").unwrap().offset(), offset);
+    /// assert_eq!(archive.read("counter")?.as_ref(), b"0002");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn replace(&mut self, name: &str, data: &[u8], compress: Compress) -> io::Result<()> {
+        self.replace_with(name, data, compress, false)
     }
 
-    /// Reclaims space by removing shadowed data.
+    /// Replaces the data of an entry like [`replace()`](Bindle::replace), also overwriting data
+    /// committed by an earlier save when the new data fits its slot.
     ///
-    /// Rebuilds the archive with only live entries, removing old versions of updated files.
-    pub fn vacuum(&mut self) -> io::Result<()> {
-        let temp_path = self.path.with_extension("tmp");
-
-        // Create temp file and keep handle to reuse after rename
-        let mut temp_file = OpenOptions::new()
-            .write(true)
-            .read(true)
-            .create(true)
-            .truncate(true)
-            .open(&temp_path)?;
-
-        temp_file.lock()?;
-        temp_file.write_all(BNDL_MAGIC)?;
-        let mut current_offset = HEADER_SIZE as u64;
+    /// This gives up the append-only guarantee that makes shadowing safe, so only use it while no
+    /// other process is reading the archive. Readers that mapped the file read the new bytes against
+    /// their old index and fail CRC32 checks, or worse. Until the next [`save()`](Bindle::save) the
+    /// committed index describes the old data, so after a crash the entry fails its CRC32 check, and
+    /// the footers of earlier saves that opening falls back to keep describing the old data for
+    /// good. An exclusive lock is held from the write until the next save, so processes opening the
+    /// archive in the meantime wait for it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("counter", b"0001", Compress::None)?;
+    /// archive.save()?;
+    /// let offset = archive.entry("counter").unwrap().offset();
+    /// archive.replace_in_place("counter", b"0002", Compress::None)?;
+    /// archive.save()?;
+    /// assert_eq!(archive.entry("counter").unwrap().offset(), offset);
+    /// assert_eq!(archive.read("counter")?.as_ref(), b"0002");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn replace_in_place(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compress: Compress,
+    ) -> io::Result<()> {
+        self.replace_with(name, data, compress, true)
+    }
 
-        // Copy only live entries from original to temp
-        for entry in self.index.values_mut() {
-            self.file.seek(SeekFrom::Start(entry.offset()))?;
-            temp_file.seek(SeekFrom::Start(current_offset))?;
+    /// Replaces the data of an entry, overwriting committed data only if `committed` is set.
+    fn replace_with(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compress: Compress,
+        committed: bool,
+    ) -> io::Result<()> {
+        check_name(name)?;
+        self.check_writable()?;
+        let Some(existing) = self.entry(name).copied() else {
+            return self.add(name, data, compress);
+        };
 
-            // Stream data without allocating full buffer
-            let mut limited = (&mut self.file).take(entry.compressed_size());
-            io::copy(&mut limited, &mut temp_file)?;
+        let sha256: Option<[u8; 32]> = self.hashes_sha256().then(|| Sha256::digest(data).into());
+        let (encoded, compress) = self.encode(data, compress, DEFAULT_ZSTD_LEVEL)?;
+        let mut entry = Entry::default();
+        entry.set_uncompressed_size(data.len() as u64);
+        entry.set_crc32(crc32fast::hash(data));
+        entry.compression_type = compress as u8;
+        entry.set_flags(self.new_entry_flags(compress));
 
-            entry.set_offset(current_offset);
-            let pad = pad::<8, u64>(entry.compressed_size());
-            if pad > 0 {
-                write_padding(&mut temp_file, pad as usize)?;
+        if let Some(mut shared) = sha256.and_then(|hash| self.dedup_target(&hash, &entry)) {
+            shared.set_name_len(name.len() as u16);
+            self.meta.remove(name);
+            self.insert_entry(name.to_string(), shared);
+            self.dirty = true;
+        } else {
+            let stored = self.seal(name, &encoded, &mut entry)?;
+            let size = stored.len() as u64;
+            let slot =
+                existing.compressed_size() + pad::<BNDL_ALIGN, u64>(existing.compressed_size());
+            let pending = existing.offset() >= self.committed_len;
+            if size <= slot && (pending || committed) && self.owns_slot(name, &existing) {
+                if !pending {
+                    // Released by the next save, which makes the index match the data again
+                    self.storage.lock()?;
+                }
+                self.storage.seek(SeekFrom::Start(existing.offset()))?;
+                self.storage.write_all(&stored)?;
+                write_padding(&mut self.storage, pad::<BNDL_ALIGN, u64>(size) as usize)?;
+                entry.set_offset(existing.offset());
+                entry.set_compressed_size(size);
+                entry.set_name_len(name.len() as u16);
+                self.meta.remove(name);
+                self.insert_entry(name.to_string(), entry);
+                self.dirty = true;
+            } else {
+                self.append_stored(name, &stored, entry)?;
             }
-            current_offset += entry.compressed_size() + pad;
         }
-
-        // Write the index and footer
-        let index_start = current_offset;
-        for (name, entry) in &self.index {
-            temp_file.write_all(entry.as_bytes())?;
-            temp_file.write_all(name.as_bytes())?;
-            let pad = pad::<BNDL_ALIGN, usize>(ENTRY_SIZE + name.len());
-            if pad > 0 {
-                write_padding(&mut temp_file, pad)?;
-            }
+        if let Some(hash) = sha256 {
+            self.record_sha256(name, hash);
         }
-
-        let footer = Footer::new(index_start, self.index.len() as u32, FOOTER_MAGIC);
-        temp_file.write_all(footer.as_bytes())?;
-        temp_file.sync_all()?;
-
-        // Acquire exclusive lock just before rename to prevent concurrent access
-        self.file.lock()?;
-
-        // Release locks and close current file
-        drop(self.mmap.take());
-        let _ = self.file.unlock();
-
-        // Atomically replace original with temp
-        std::fs::rename(&temp_path, &self.path)?;
-
-        // Reuse temp_file handle (still valid after rename)
-        temp_file.lock_shared()?;
-        let mmap = unsafe { Mmap::map(&temp_file)? };
-
-        let footer_pos = mmap.len() - FOOTER_SIZE;
-        let footer = Footer::read_from_bytes(&mmap[footer_pos..]).map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Failed to read footer after vacuum",
-            )
-        })?;
-
-        self.file = temp_file;
-        self.mmap = Some(mmap);
-        self.data_end = footer.index_offset();
-
         Ok(())
     }
 
-    /// Reads an entry from the archive, decompressing if needed.
-    ///
-    /// Returns `None` if the entry doesn't exist or if CRC32 verification fails.
-    pub fn read<'a>(&'a self, name: &str) -> Option<Cow<'a, [u8]>> {
-        let entry = self.index.get(name)?;
-        let mmap = self.mmap.as_ref()?;
-
-        let data = if entry.compression_type() == Compress::Zstd {
-            let compressed_data = mmap.get(
-                entry.offset() as usize..(entry.offset() + entry.compressed_size()) as usize,
-            )?;
-            let mut out = Vec::with_capacity(entry.uncompressed_size() as usize);
-            zstd::Decoder::new(compressed_data)
-                .ok()?
-                .read_to_end(&mut out)
-                .ok()?;
-            Cow::Owned(out)
-        } else {
-            let uncompressed_data = mmap.get(
-                entry.offset() as usize..(entry.offset() + entry.uncompressed_size()) as usize,
-            )?;
-            Cow::Borrowed(uncompressed_data)
-        };
-
-        // Verify CRC32
-        let computed_crc = crc32fast::hash(&data);
-        if computed_crc != entry.crc32() {
-            return None;
-        }
-
-        Some(data)
+    /// Returns true if the data of `existing`, the entry stored as `name`, can be overwritten
+    /// without anything else noticing.
+    fn owns_slot(&self, name: &str, existing: &Entry) -> bool {
+        let name = self.stored_name(name);
+        let others = self.index.iter().filter(|(other, _)| *other != name);
+        let shared = self
+            .dict_entry
+            .iter()
+            .chain(others.map(|(_, entry)| entry))
+            .any(|entry| entry.offset() == existing.offset() && entry.compressed_size() > 0);
+        !shared && !self.mapping.is_shared()
     }
 
-    /// Reads an entry into a provided buffer, avoiding allocation.
+    /// Adds data under the lowercase hex SHA-256 of its contents and returns the name.
     ///
-    /// Decompresses if needed and verifies CRC32. Returns the number of bytes read.
-    /// If the buffer is too small, only reads up to buffer.len() bytes.
+    /// Meant for content-addressed stores such as build caches. If an entry with that name already
+    /// exists nothing is written, assuming that equal hashes mean equal contents, so the existing
+    /// entry keeps its compression. Call [`save()`](Bindle::save) to commit changes.
     ///
     /// # Example
     ///
-    /// ```no_run
-    /// use bindle_file::Bindle;
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
     ///
-    /// let archive = Bindle::open("data.bndl")?;
-    /// let mut buffer = vec![0u8; 1024];
-    /// let bytes_read = archive.read_into("file.txt", &mut buffer)?;
+    /// let mut archive = Bindle::in_memory();
+    /// let name = archive.add_cas(b"object", Compress::Zstd)?;
+    /// assert_eq!(name.len(), 64);
+    /// assert_eq!(archive.add_cas(b"object", Compress::Zstd)?, name);
+    /// assert_eq!(archive.len(), 1);
     /// # Ok::<(), std::io::Error>(())
     /// ```
-    pub fn read_into(&self, name: &str, buffer: &mut [u8]) -> io::Result<usize> {
-        let mut reader = self.reader(name)?;
-        let bytes_read = reader.read(buffer)?;
-        reader.verify_crc32()?;
-        Ok(bytes_read)
+    pub fn add_cas(&mut self, data: &[u8], compress: Compress) -> io::Result<String> {
+        let name = Sha256::digest(data)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        if !self.exists(&name) {
+            self.add(&name, data, compress)?;
+        }
+        Ok(name)
     }
 
-    /// Reads an entry and writes it to the given writer.
+    /// Adds several entries at once, writing their data back to back.
     ///
-    /// Returns the number of bytes written. Verifies CRC32 after reading.
-    pub fn read_to<W: std::io::Write>(&self, name: &str, mut w: W) -> std::io::Result<u64> {
-        let mut reader = self.reader(name)?;
-        let bytes_copied = std::io::copy(&mut reader, &mut w)?;
-        reader.verify_crc32()?;
-        Ok(bytes_copied)
+    /// Stores exactly what a loop of [`add()`](Bindle::add) would, but seeks to the end of the data
+    /// region only once. Items can be borrowed, such as
+    /// `(&str, &[u8], Compress)`, or owned, such as `(String, Vec<u8>, Compress)`. If an entry fails,
+    /// the ones before it have already been added and the partly written one is dropped, so the next
+    /// write starts where it began. Call [`save()`](Bindle::save) to commit changes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add_many([
+    ///     ("a.txt", b"first".as_slice(), Compress::None),
+    ///     ("b.txt", b"second".as_slice(), Compress::Zstd),
+    /// ])?;
+    /// assert_eq!(archive.len(), 2);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn add_many<N, D>(
+        &mut self,
+        items: impl IntoIterator<Item = (N, D, Compress)>,
+    ) -> io::Result<()>
+    where
+        N: AsRef<str>,
+        D: AsRef<[u8]>,
+    {
+        self.check_writable()?;
+        self.append_offset()?;
+        for (name, data, compress) in items {
+            // Each writer leaves the position at the end of the data region for the next one
+            let start_offset = self.data_end;
+            let mut stream =
+                self.writer_at(name.as_ref(), compress, DEFAULT_ZSTD_LEVEL, start_offset)?;
+            if let Err(e) = stream.write_all(data.as_ref()) {
+                stream.abort()?;
+                return Err(e);
+            }
+            stream.close()?;
+        }
+        Ok(())
     }
 
-    /// Returns a streaming reader for an entry.
+    /// Adds data to the archive, sharing the stored data of an existing entry with identical content.
     ///
-    /// Automatically decompresses if the entry is compressed. Call [`Reader::verify_crc32()`] after reading to verify integrity.
-    pub fn reader<'a>(&'a self, name: &str) -> io::Result<Reader<'a>> {
-        let entry = self
-            .index
-            .get(name)
-            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+    /// Works like [`add()`](Bindle::add) with [`set_dedup()`](Bindle::set_dedup) enabled for this one
+    /// entry, except that the data is hashed up front so nothing is written for a duplicate. Entries
+    /// are matched by SHA-256, which also has to match in size and compression. Without `set_dedup`
+    /// every entry is searched, so prefer enabling it when adding many entries.
+    ///
+    /// Like `add()`, shadowing an entry drops its [`meta()`](Bindle::meta) and
+    /// [`mtime()`](Bindle::mtime), whether or not the data is shared.
+    pub fn add_dedup(&mut self, name: &str, data: &[u8], compress: Compress) -> io::Result<()> {
+        if self.cipher.is_some() {
+            return self.add(name, data, compress);
+        }
+        let hash: [u8; 32] = Sha256::digest(data).into();
+        let mut entry = Entry::default();
+        entry.set_uncompressed_size(data.len() as u64);
+        let resolved = compress.resolve(data, data.len(), self.auto_compress_threshold);
+        entry.compression_type = resolved as u8;
+        entry.set_flags(self.new_entry_flags(resolved));
 
-        let start = entry.offset() as usize;
-        let end = start + entry.compressed_size() as usize;
-        let mmap = self
-            .mmap
-            .as_ref()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing mmap"))?;
-        let data_slice = &mmap[start..end];
-
-        let cursor = io::Cursor::new(data_slice);
-
-        if entry.compression_type() == Compress::Zstd {
-            // Zstd streaming decoder
-            let decoder = zstd::Decoder::new(cursor)?;
-            Ok(Reader {
-                decoder: Either::Left(decoder),
-                crc32_hasher: Hasher::new(),
-                expected_crc32: entry.crc32(),
-            })
-        } else {
-            Ok(Reader {
-                decoder: Either::Right(cursor),
-                crc32_hasher: Hasher::new(),
-                expected_crc32: entry.crc32(),
-            })
+        let shared = match self.dedup {
+            Some(_) => self.dedup_target(&hash, &entry),
+            None => self.find_shared(&hash, &entry),
+        };
+        match shared {
+            Some(mut shared) => {
+                check_name(name)?;
+                self.check_writable()?;
+                shared.set_name_len(name.len() as u16);
+                self.meta.remove(name);
+                self.insert_entry(name.to_string(), shared);
+                self.dirty = true;
+            }
+            None => self.add(name, data, compress)?,
         }
+        self.record_sha256(name, hash);
+        Ok(())
     }
 
-    /// Returns the number of entries in the archive.
-    pub fn len(&self) -> usize {
-        self.index.len()
+    /// Adds data to the archive along with an opaque metadata blob, such as a MIME type or source URL.
+    ///
+    /// The blob is stored in the index next to the entry name and read back with [`meta()`](Bindle::meta).
+    /// Returns an [`io::ErrorKind::InvalidInput`] error if it is larger than about 64 KiB.
+    /// Call [`save()`](Bindle::save) to commit changes.
+    pub fn add_with_meta(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compress: Compress,
+        meta: &[u8],
+    ) -> io::Result<()> {
+        if meta.len() > MAX_USER_META {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Entry metadata too large",
+            ));
+        }
+        self.add(name, data, compress)?;
+        self.meta.entry(name.to_string()).or_default().user = Some(meta.to_vec());
+        Ok(())
     }
 
-    /// Returns true if the archive contains no entries.
+    /// Adds an entry by streaming from any [`Read`] source, such as stdin, a socket or a decoder.
+    ///
+    /// Avoids buffering the whole payload in memory. Returns the number of uncompressed bytes written.
+    /// If reading fails the partly written entry is dropped and the error returned. Pass `&mut src`
+    /// to keep using the source afterwards. Call [`save()`](Bindle::save) to commit changes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// let n = archive.add_reader("data.txt", &b"streamed"[..], Compress::Zstd)?;
+    /// assert_eq!(n, 8);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn add_reader<R: Read>(
+        &mut self,
+        name: &str,
+        src: R,
+        compress: Compress,
+    ) -> io::Result<u64> {
+        self.add_reader_with_level(name, src, compress, DEFAULT_ZSTD_LEVEL)
+    }
+
+    /// Adds an entry by streaming from a [`Read`] source, compressing with the given zstd level.
+    pub fn add_reader_with_level<R: Read>(
+        &mut self,
+        name: &str,
+        mut src: R,
+        compress: Compress,
+        level: i32,
+    ) -> io::Result<u64> {
+        let mut stream = self.writer_with_level(name, compress, level)?;
+        match io::copy(&mut src, &mut stream) {
+            Ok(n) => {
+                stream.close()?;
+                Ok(n)
+            }
+            Err(e) => {
+                stream.abort()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Adds a file from the filesystem to the archive.
+    ///
+    /// Reads the file at `path` and stores it with the given `name`. Call [`save()`](Bindle::save) to commit changes.
+    pub fn add_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+        compress: Compress,
+    ) -> io::Result<()> {
+        self.add_file_with_level(name, path, compress, DEFAULT_ZSTD_LEVEL)
+    }
+
+    /// Adds a file from the filesystem to the archive, compressing with the given zstd level.
+    ///
+    /// The file's modification time is stored alongside the entry.
+    pub fn add_file_with_level(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+        compress: Compress,
+        level: i32,
+    ) -> io::Result<()> {
+        let mut src = std::fs::File::open(path)?;
+        let modified = src.metadata()?.modified().ok();
+        let mut stream = self.writer_with_level(name, compress, level)?;
+        if let Err(e) = io::copy(&mut src, &mut stream) {
+            stream.abort()?;
+            return Err(e);
+        }
+        stream.close()?;
+        if let Some(t) = modified {
+            self.set_mtime(name, t);
+        }
+        Ok(())
+    }
+
+    /// Returns the stored modification time of an entry, if it has one.
+    pub fn mtime(&self, name: &str) -> Option<SystemTime> {
+        let nanos = self.meta.get(self.stored_name(name))?.mtime?;
+        Some(UNIX_EPOCH + Duration::from_nanos(nanos))
+    }
+
+    /// Returns the metadata blob stored with an entry by [`add_with_meta()`](Bindle::add_with_meta).
+    pub fn meta(&self, name: &str) -> Option<&[u8]> {
+        self.meta.get(self.stored_name(name))?.user.as_deref()
+    }
+
+    /// Returns the SHA-256 of an entry's data, if it was written with [`set_sha256()`](Bindle::set_sha256) enabled.
+    pub fn sha256(&self, name: &str) -> Option<[u8; 32]> {
+        self.meta.get(self.stored_name(name))?.sha256
+    }
+
+    /// Returns the name of an entry whose data has the given SHA-256.
+    ///
+    /// Only entries that store a hash are considered. If several match, the first in name order is returned.
+    pub fn find_by_hash(&self, hash: &[u8; 32]) -> Option<&str> {
+        self.meta
+            .iter()
+            .find(|(name, meta)| {
+                meta.sha256.as_ref() == Some(hash) && self.index.contains_key(*name)
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Sets the modification time restored by [`unpack()`](Bindle::unpack).
+    ///
+    /// Returns false if the entry doesn't exist or the time is before the Unix epoch.
+    /// Call [`save()`](Bindle::save) to commit changes.
+    pub fn set_mtime(&mut self, name: &str, time: SystemTime) -> bool {
+        let Ok(since_epoch) = time.duration_since(UNIX_EPOCH) else {
+            return false;
+        };
+        let name = self.stored_name(name);
+        if !self.index.contains_key(name) {
+            return false;
+        }
+        self.meta.entry(name.to_string()).or_default().mtime = Some(since_epoch.as_nanos() as u64);
+        self.dirty = true;
+        true
+    }
+
+    /// Commits all pending changes by writing the index and footer to disk.
+    ///
+    /// Must be called after add/remove operations to make changes persistent. An exclusive lock is
+    /// only held while the new footer is written, so readers never see a half-written one.
+    ///
+    /// The new index is appended after the previous footer, which stays intact, and is synced to
+    /// disk before the new footer is written. If the process dies part way through, opening the
+    /// archive finds the new footer missing or its index checksum wrong and falls back to the
+    /// previous footer, so the archive reads as of the last complete save and the next save
+    /// overwrites the partial data.
+    ///
+    /// By default the new footer isn't synced, so a save that returned can still be lost on power
+    /// failure. Use [`save_durable()`](Bindle::save_durable) or
+    /// [`save_atomic()`](Bindle::save_atomic) when it must be on disk, or change the
+    /// [`SyncPolicy`] with [`set_sync_policy()`](Bindle::set_sync_policy).
+    pub fn save(&mut self) -> io::Result<()> {
+        self.save_with(self.sync)
+    }
+
+    /// Commits pending changes like [`save()`](Bindle::save) and syncs the file, including the
+    /// footer, to disk before returning.
+    ///
+    /// Same as saving with [`SyncPolicy::All`], whatever the archive's policy is.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::open("ledger.bndl")?;
+    /// archive.add("2024-06-01.csv", b"id,amount\n1,100\n", Compress::Zstd)?;
+    /// archive.save_durable()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn save_durable(&mut self) -> io::Result<()> {
+        self.save_with(SyncPolicy::All)
+    }
+
+    /// Sets when [`save()`](Bindle::save) syncs the file to disk, [`SyncPolicy::Data`] by default.
+    pub fn set_sync_policy(&mut self, policy: SyncPolicy) {
+        self.sync = policy;
+    }
+
+    fn save_with(&mut self, sync: SyncPolicy) -> io::Result<()> {
+        self.commit_index(sync)?;
+
+        if let Storage::File(file) = &self.storage {
+            self.mapping.map(file)?;
+        }
+
+        if let Some(ratio) = self.auto_vacuum_ratio
+            && self.dead_space() as f64 / self.committed_len as f64 > ratio
+        {
+            self.vacuum()?;
+        }
+        Ok(())
+    }
+
+    /// Commits pending changes like [`save()`](Bindle::save) and syncs them to disk, without remapping.
+    ///
+    /// Meant for frequent checkpoints while adding many entries: the file is mapped again on the
+    /// next read instead of on every commit. Doesn't run automatic vacuuming.
+    pub fn commit(&mut self) -> io::Result<()> {
+        self.commit_index(SyncPolicy::All)?;
+        self.mapping.clear_remap();
+        Ok(())
+    }
+
+    /// Commits pending changes by writing a complete new archive to a temporary file and renaming
+    /// it over the original.
+    ///
+    /// Unlike [`save()`](Bindle::save), the original file is never modified, so after a crash the
+    /// path holds either the previous or the new archive in full. The data region is copied as is,
+    /// including data shadowed by later writes, which makes this as slow as copying the archive; use
+    /// [`vacuum()`](Bindle::vacuum) to drop shadowed data at the same time.
+    ///
+    /// Durability: the temporary file is synced with `fsync` once the data, index and footer are
+    /// written, before the rename. On Unix the parent directory is synced after the rename, so the
+    /// new archive is on disk when this returns. Elsewhere the rename itself may not be durable until
+    /// the file system flushes it. In-memory archives are saved with [`save()`](Bindle::save).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::open("ledger.bndl")?;
+    /// archive.add("2024-06-01.csv", b"id,amount\n1,100\n", Compress::Zstd)?;
+    /// archive.save_atomic()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn save_atomic(&mut self) -> io::Result<()> {
+        self.check_writable()?;
+        if let Storage::Memory(_) = self.storage {
+            return self.save();
+        }
+
+        let (temp, mut temp_file) = TempPath::create(self.path.with_extension("tmp"))?;
+        temp_file.lock()?;
+
+        // Entry offsets stay the same, so everything before the index is copied unchanged
+        let index_start = self.data_end;
+        self.storage.lock()?;
+        if let Err(e) = self.write_copy(&mut temp_file, index_start) {
+            let _ = self.storage.unlock();
+            return Err(e);
+        }
+        self.replace_file(temp, temp_file)?;
+        self.version = FORMAT_VERSION;
+        self.dirty = false;
+
+        #[cfg(unix)]
+        if let Some(parent) = self.path.parent() {
+            let dir = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the data before `index_start`, the current header and a new index and footer to
+    /// `out` for [`save_atomic()`](Bindle::save_atomic), and syncs it.
+    fn write_copy(&mut self, out: &mut File, index_start: u64) -> io::Result<()> {
+        self.storage.seek(SeekFrom::Start(0))?;
+        io::copy(&mut (&mut self.storage).take(index_start), out)?;
+        out.seek(SeekFrom::Start(0))?;
+        out.write_all(BNDL_MAGIC)?;
+        out.seek(SeekFrom::Start(index_start))?;
+        {
+            let mut writer = BufWriter::new(&mut *out);
+            write_index(
+                &mut writer,
+                &self.index,
+                &self.meta,
+                self.dict_entry.as_ref(),
+            )?;
+            write_footer(&mut writer, index_start, self.record_count())?;
+            writer.flush()?;
+        }
+        out.sync_all()
+    }
+
+    /// Renames the finished `temp` file over the archive and switches to `file`, its open handle.
+    ///
+    /// Must be called with the archive locked, the lock is released in every case. The archive
+    /// keeps its old file if the rename fails.
+    fn replace_file(&mut self, temp: TempPath, file: File) -> io::Result<()> {
+        // Release locks and close current file
+        self.mapping.unmap();
+        let _ = self.storage.unlock();
+
+        // Atomically replace original with temp
+        if let Err(e) = std::fs::rename(&temp.path, &self.path) {
+            if let Storage::File(old) = &self.storage {
+                self.mapping.map(old)?;
+            }
+            return Err(e);
+        }
+        temp.keep();
+
+        // Reuse the temp file handle, still valid after the rename
+        file.unlock()?;
+        self.mapping.map(&file)?;
+        self.committed_len = file.metadata()?.len();
+        self.storage = Storage::File(file);
+        Ok(())
+    }
+
+    /// Returns true if there are changes that haven't been committed with [`save()`](Bindle::save).
+    ///
+    /// Adding, removing, renaming and clearing entries and changing their modification times all
+    /// count as changes. [`save()`](Bindle::save), [`save_atomic()`](Bindle::save_atomic),
+    /// [`commit()`](Bindle::commit) and [`vacuum()`](Bindle::vacuum) reset it.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Writes the index and footer after the data region.
+    fn commit_index(&mut self, sync: SyncPolicy) -> io::Result<()> {
+        self.check_writable()?;
+        let index_start = self.append_offset()?;
+
+        // Use buffered writer to batch index writes
+        {
+            let mut writer = BufWriter::new(&mut self.storage);
+            let dictionary = self.dict_entry.as_ref();
+            write_index(&mut writer, &self.index, &self.meta, dictionary)?;
+            writer.flush()?;
+        } // Drop writer here to release borrow
+
+        // The footer makes the new index live, so the data and index must reach the disk first
+        if sync != SyncPolicy::None {
+            self.storage.sync()?;
+        }
+        self.storage.lock()?;
+        let committed = self.write_commit(index_start, sync);
+        let unlocked = self.storage.unlock();
+        committed.and(unlocked)
+    }
+
+    /// Writes the footer for the index at `index_start` and truncates anything after it, with the
+    /// exclusive lock held.
+    fn write_commit(&mut self, index_start: u64, sync: SyncPolicy) -> io::Result<()> {
+        let count = self.record_count();
+        write_footer(&mut self.storage, index_start, count)?;
+
+        // Truncate file to current position to remove any old data
+        let current_pos = self.storage.stream_position()?;
+        self.storage.set_len(current_pos)?;
+
+        // The index was written in the current layout, upgrade older headers to match. The header
+        // only describes the new index, so it's written once the footer is on disk. Versions 1 and
+        // 2 hold the lock from the first append, so no reader sees the new index under the old
+        // header either.
+        if self.version != FORMAT_VERSION {
+            self.storage.sync_all()?;
+            self.storage.seek(SeekFrom::Start(0))?;
+            self.storage.write_all(BNDL_MAGIC)?;
+            self.version = FORMAT_VERSION;
+        }
+        if sync == SyncPolicy::All {
+            self.storage.sync_all()?;
+        }
+
+        self.data_end = index_start;
+        self.committed_len = current_pos;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Returns the number of index records, including the dictionary's.
+    fn record_count(&self) -> u32 {
+        (self.index.len() + self.dict_entry.is_some() as usize) as u32
+    }
+
+    /// Sets the size in bytes above which [`Compress::Auto`] considers compressing an entry.
+    ///
+    /// Entries no larger than `bytes` are stored uncompressed, larger ones are compressed if a sample
+    /// of their data compresses well. The default is 2048. Streaming writers buffer up to the
+    /// threshold, or a 4 KiB sample if that's larger, before picking the compression, so the
+    /// threshold applies to streamed entries too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.set_auto_compress_threshold(256);
+    /// archive.add("config.json", "{\"enabled\": true}".repeat(30).as_bytes(), Compress::Auto)?;
+    /// assert_eq!(archive.compression_of("config.json"), Some(Compress::Zstd));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn set_auto_compress_threshold(&mut self, bytes: usize) {
+        self.auto_compress_threshold = bytes;
+    }
+
+    /// Sets the size limits enforced when reading and unpacking, see [`Limits`].
+    ///
+    /// Reading an entry over the limit returns [`BindleError::TooLarge`] before anything is decoded.
+    /// [`read_range()`](Bindle::read_range) isn't limited, since it only decodes the requested range.
+    /// [Views](Bindle::view) created afterwards start with the same limits.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the size limits enforced when reading and unpacking.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Makes [`save()`](Bindle::save) run [`vacuum()`](Bindle::vacuum) when dead space exceeds a
+    /// fraction of the file size.
+    ///
+    /// `ratio` is compared against dead bytes divided by the file size after the save, `None`
+    /// disables automatic vacuuming, which is the default. Returns an
+    /// [`io::ErrorKind::InvalidInput`] error if `ratio` is outside `0.0..=1.0`, or NaN.
+    pub fn set_auto_vacuum(&mut self, ratio: Option<f64>) -> io::Result<()> {
+        if let Some(ratio) = ratio
+            && !(0.0..=1.0).contains(&ratio)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid auto vacuum ratio {}", ratio),
+            ));
+        }
+        self.auto_vacuum_ratio = ratio;
+        Ok(())
+    }
+
+    /// Makes new entries store a SHA-256 of their data alongside the CRC32, off by default.
+    ///
+    /// The hash is computed while writing and read back with [`sha256()`](Bindle::sha256). Entries
+    /// written while this is disabled, or to an archive opened with a key, have no hash.
+    pub fn set_sha256(&mut self, enabled: bool) {
+        self.hash_sha256 = enabled;
+    }
+
+    /// Makes new entries share the stored data of an existing entry with identical content.
+    ///
+    /// Entries are matched by SHA-256, so while enabled every new entry stores its hash as if
+    /// [`set_sha256()`](Bindle::set_sha256) was on. A match must also have the same size, compression
+    /// and flags, otherwise the data is written as usual. The bytes of a duplicate are still written
+    /// and compressed, then dropped, since the hash isn't known until the entry is closed. Shared
+    /// data stays in the archive until every entry referencing it is gone. Archives opened with a key
+    /// don't hash their entries, so nothing is shared there.
+    pub fn set_dedup(&mut self, enabled: bool) {
+        self.dedup = enabled.then(|| {
+            self.meta
+                .iter()
+                .filter_map(|(name, meta)| Some((meta.sha256?, name.clone())))
+                .collect()
+        });
+    }
+
+    /// Makes name lookups ignore case, off by default.
+    ///
+    /// Meant for archives packed on case-insensitive file systems, where `README.md` and `readme.md`
+    /// are the same file. Lookups such as [`read()`](Bindle::read), [`exists()`](Bindle::exists),
+    /// [`reader()`](Bindle::reader) and [`remove()`](Bindle::remove) compare lowercased names, while
+    /// the index keeps each name as it was written. Writing a name that differs from an existing one
+    /// only in case shadows it, like rewriting the same name: the last write wins and its spelling is
+    /// stored. Archives that already hold such names keep all of them, lookups find the one stored
+    /// last in the file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.set_case_insensitive(true);
+    /// archive.add("README.md", b"# Docs", Compress::None)?;
+    /// assert_eq!(archive.read("readme.md")?.as_ref(), b"# Docs");
+    /// assert_eq!(archive.names().collect::<Vec<_>>(), ["README.md"]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn set_case_insensitive(&mut self, enabled: bool) {
+        self.folded_names = enabled.then(|| {
+            let mut names: Vec<(&String, &Entry)> = self.index.iter().collect();
+            names.sort_by_key(|(_, entry)| entry.offset());
+            names
+                .into_iter()
+                .map(|(name, _)| (name.to_lowercase(), name.clone()))
+                .collect()
+        });
+    }
+
+    /// Returns the stored spelling of `name`, which differs only in case when lookups ignore case.
+    fn stored_name<'a>(&'a self, name: &'a str) -> &'a str {
+        match &self.folded_names {
+            Some(names) => names.get(&name.to_lowercase()).map_or(name, String::as_str),
+            None => name,
+        }
+    }
+
+    /// Inserts an index record, shadowing an entry whose name differs only in case when lookups
+    /// ignore case.
+    pub(crate) fn insert_entry(&mut self, name: String, entry: Entry) {
+        if let Some(names) = &mut self.folded_names
+            && let Some(old) = names.insert(name.to_lowercase(), name.clone())
+            && old != name
+        {
+            self.index.remove(&old);
+            self.meta.remove(&old);
+        }
+        self.index.insert(name, entry);
+    }
+
+    /// Returns true if new entries should be hashed with SHA-256.
+    ///
+    /// Encrypted archives never hash, a plaintext hash in the index would reveal which entries hold
+    /// the same or known contents.
+    pub(crate) fn hashes_sha256(&self) -> bool {
+        (self.hash_sha256 || self.dedup.is_some()) && self.cipher.is_none()
+    }
+
+    /// Returns a live entry with the given hash whose stored data can stand in for `entry`.
+    ///
+    /// Returns `None` unless deduplication is enabled. The map only remembers one name per hash, if
+    /// that entry was removed or doesn't match the other entries are searched.
+    pub(crate) fn dedup_target(&self, hash: &[u8; 32], entry: &Entry) -> Option<Entry> {
+        let name = self.dedup.as_ref()?.get(hash)?;
+        self.shared_entry(name, hash, entry)
+            .or_else(|| self.find_shared(hash, entry))
+    }
+
+    /// Searches every entry for one whose stored data can stand in for `entry`.
+    fn find_shared(&self, hash: &[u8; 32], entry: &Entry) -> Option<Entry> {
+        self.meta
+            .iter()
+            .filter(|(_, meta)| meta.sha256.as_ref() == Some(hash))
+            .find_map(|(name, _)| self.shared_entry(name, hash, entry))
+    }
+
+    /// Returns the entry `name` if it has the given hash and the same size, compression and flags as `entry`.
+    fn shared_entry(&self, name: &str, hash: &[u8; 32], entry: &Entry) -> Option<Entry> {
+        let existing = self.index.get(name)?;
+        let matches = self.meta.get(name)?.sha256.as_ref() == Some(hash)
+            && existing.uncompressed_size() == entry.uncompressed_size()
+            && existing.compression_type == entry.compression_type
+            && existing.flags() == entry.flags();
+        matches.then_some(*existing)
+    }
+
+    /// Stores the hash of an entry and makes it available to later duplicates.
+    pub(crate) fn record_sha256(&mut self, name: &str, hash: [u8; 32]) {
+        self.meta.entry(name.to_string()).or_default().sha256 = Some(hash);
+        // Keep pointing at an earlier entry while it still holds this content
+        let current = self.dedup.as_ref().and_then(|dedup| dedup.get(&hash));
+        let live = current.is_some_and(|n| self.meta.get(n).and_then(|m| m.sha256) == Some(hash));
+        if let Some(dedup) = &mut self.dedup
+            && !live
+        {
+            dedup.insert(hash, name.to_string());
+        }
+    }
+
+    /// Moves the write position past the committed footer and returns it.
+    ///
+    /// Data written since the last commit continues where it left off, otherwise writing starts
+    /// after the footer so the committed index stays valid for readers that already opened the
+    /// archive. Until the next save the footer is no longer at the end of the file, readers opening
+    /// it in the meantime fall back to the last committed footer, so no lock is needed. Files older
+    /// than version 3 have no index checksum to find that footer by, for those an exclusive lock
+    /// makes new readers wait for the save that upgrades them.
+    fn append_offset(&mut self) -> io::Result<u64> {
+        if self.version < 3 {
+            self.storage.lock()?;
+        }
+        self.data_end = self.data_end.max(self.committed_len);
+        self.storage.seek(SeekFrom::Start(self.data_end))?;
+        Ok(self.data_end)
+    }
+
+    /// Reclaims space by removing shadowed data.
+    ///
+    /// Rebuilds the archive with only live entries, removing old versions of updated files.
+    /// Data shared by deduplicated entries is copied once and stays shared. Entries are written in
+    /// name order, see [`vacuum_ordered()`](Bindle::vacuum_ordered) to choose the layout.
+    ///
+    /// The new archive is written to a temporary file and renamed over the original, so a crash
+    /// leaves one or the other intact but needs room for both. See
+    /// [`compact_in_place()`](Bindle::compact_in_place) when disk space is short.
+    pub fn vacuum(&mut self) -> io::Result<()> {
+        self.vacuum_ordered(&[])
+    }
+
+    /// Reclaims space like [`vacuum()`](Bindle::vacuum), writing the entries named in `order` first.
+    ///
+    /// Entries are laid out in the order given, followed by the remaining entries in name order.
+    /// Names that don't exist or repeat are ignored. Placing entries that are read together next to
+    /// each other at the front of the file keeps them in fewer pages of the page cache. Entries
+    /// sharing deduplicated data are placed where the first of them is.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let mut archive = Bindle::open("site.bndl")?;
+    /// let hot = ["index.html".to_string(), "style.css".to_string()];
+    /// archive.vacuum_ordered(&hot)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn vacuum_ordered(&mut self, order: &[String]) -> io::Result<()> {
+        self.check_writable()?;
+        if let Storage::Memory(_) = self.storage {
+            let mut buf = io::Cursor::new(Vec::new());
+            self.data_end = self.write_live(&mut buf, order)?;
+            self.committed_len = buf.get_ref().len() as u64;
+            self.storage = Storage::Memory(buf);
+            self.version = FORMAT_VERSION;
+            self.dirty = false;
+            return Ok(());
+        }
+
+        // Create temp file and keep handle to reuse after rename
+        let (temp, mut temp_file) = TempPath::create(self.path.with_extension("tmp"))?;
+
+        temp_file.lock()?;
+        let index_start = self.write_live(&mut temp_file, order)?;
+        temp_file.sync_all()?;
+
+        // Acquire exclusive lock just before rename to prevent concurrent access
+        self.storage.lock()?;
+        self.replace_file(temp, temp_file)?;
+        self.data_end = index_start;
+        self.version = FORMAT_VERSION;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Reclaims space like [`vacuum()`](Bindle::vacuum) without writing a second copy of the archive.
+    ///
+    /// Live entries are moved toward the front of the file in the order they are stored, each one
+    /// only ever moving left, then the file is truncated and the index and footer are rewritten.
+    /// Peak disk usage never grows, which helps when the archive nearly fills its volume. Pending
+    /// changes are committed along the way.
+    ///
+    /// This is not crash-safe: if the process dies or the power fails partway through, entries may be
+    /// overwritten before the new index is written and the archive is left corrupt. Prefer
+    /// [`vacuum()`](Bindle::vacuum) unless disk space rules it out. Unlike other writes this modifies
+    /// committed data, so views, readers and other processes must not be reading the archive. An
+    /// exclusive lock makes processes opening it wait until the compaction is committed, and an
+    /// [`io::ErrorKind::ResourceBusy`] error is returned if this archive still has views or owned
+    /// readers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let mut archive = Bindle::open("huge.bndl")?;
+    /// archive.compact_in_place()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn compact_in_place(&mut self) -> io::Result<()> {
+        self.check_writable()?;
+        if self.mapping.is_shared() {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceBusy,
+                "Archive has open views or readers, drop them before compacting in place",
+            ));
+        }
+
+        // Plan every move before touching the file, data shared by deduplicated entries moves once
+        let mut blocks: Vec<(u64, u64)> = self
+            .dict_entry
+            .iter()
+            .chain(self.index.values())
+            .map(|entry| (entry.offset(), entry.compressed_size()))
+            .collect();
+        blocks.sort_unstable();
+        blocks.dedup();
+        let mut moves = HashMap::with_capacity(blocks.len());
+        let mut end = HEADER_SIZE as u64;
+        for &(offset, size) in &blocks {
+            if end > offset {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Entries share overlapping data, use vacuum() instead",
+                ));
+            }
+            moves.insert((offset, size), end);
+            end += size + pad::<BNDL_ALIGN, u64>(size);
+        }
+
+        // Mapped pages past the new end would fault once the file is truncated
+        self.mapping.unmap();
+        self.storage.lock()?;
+
+        let mut buf = vec![0u8; 64 * 1024];
+        for &(offset, size) in &blocks {
+            let to = moves[&(offset, size)];
+            if to != offset {
+                move_data(&mut self.storage, offset, to, size, &mut buf)?;
+            }
+            let pad_len = pad::<BNDL_ALIGN, u64>(size);
+            if pad_len > 0 {
+                self.storage.seek(SeekFrom::Start(to + size))?;
+                write_padding(&mut self.storage, pad_len as usize)?;
+            }
+        }
+        for entry in self.dict_entry.iter_mut().chain(self.index.values_mut()) {
+            entry.set_offset(moves[&(entry.offset(), entry.compressed_size())]);
+        }
+
+        self.storage.set_len(end)?;
+        self.data_end = end;
+        self.committed_len = end;
+        self.commit_index(self.sync)?;
+        if let Storage::File(file) = &self.storage {
+            self.mapping.map(file)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a fresh archive containing only live entries to `out`, returning the index offset.
+    ///
+    /// Entry offsets in the index are updated to point into the new archive. The dictionary is
+    /// written first, then the entries named in `order` and the rest in name order.
+    fn write_live<W: Write + Seek>(&mut self, out: &mut W, order: &[String]) -> io::Result<u64> {
+        out.write_all(BNDL_MAGIC)?;
+        let mut current_offset = HEADER_SIZE as u64;
+
+        let mut remaining: BTreeMap<&str, &mut Entry> = self
+            .index
+            .iter_mut()
+            .map(|(name, entry)| (name.as_str(), entry))
+            .collect();
+        let mut entries: Vec<&mut Entry> = self.dict_entry.iter_mut().collect();
+        entries.extend(
+            order
+                .iter()
+                .filter_map(|name| remaining.remove(name.as_str())),
+        );
+        entries.extend(remaining.into_values());
+
+        // Copy only live entries from original to temp, data shared by deduplicated entries once
+        let mut copied = HashMap::new();
+        for entry in entries {
+            let key = (entry.offset(), entry.compressed_size());
+            if let Some(&offset) = copied.get(&key) {
+                entry.set_offset(offset);
+                continue;
+            }
+            copied.insert(key, current_offset);
+            self.storage.seek(SeekFrom::Start(entry.offset()))?;
+            out.seek(SeekFrom::Start(current_offset))?;
+
+            // Stream data without allocating full buffer
+            let mut limited = (&mut self.storage).take(entry.compressed_size());
+            io::copy(&mut limited, out)?;
+
+            entry.set_offset(current_offset);
+            let pad = pad::<8, u64>(entry.compressed_size());
+            if pad > 0 {
+                write_padding(out, pad as usize)?;
+            }
+            current_offset += entry.compressed_size() + pad;
+        }
+
+        // Write the index and footer
+        let index_start = current_offset;
+        write_index(out, &self.index, &self.meta, self.dict_entry.as_ref())?;
+        write_footer(out, index_start, self.record_count())?;
+        Ok(index_start)
+    }
+
+    /// Reads an entry from the archive, decompressing if needed.
+    ///
+    /// Returns [`BindleError::NotFound`] if the entry doesn't exist or [`BindleError::CrcMismatch`]
+    /// if CRC32 verification fails.
+    pub fn read<'a>(&'a self, name: &str) -> Result<Cow<'a, [u8]>, BindleError> {
+        let name = self.stored_name(name);
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        self.read_entry(name, entry)
+    }
+
+    /// Reads an entry, distinguishing missing entries from corrupt ones.
+    ///
+    /// Returns `Ok(None)` if no entry has the given name, and an [`io::ErrorKind::InvalidData`] error
+    /// if the entry exists but can't be decoded or fails CRC32 verification.
+    pub fn try_read<'a>(&'a self, name: &str) -> io::Result<Option<Cow<'a, [u8]>>> {
+        let name = self.stored_name(name);
+        match self.index.get(name) {
+            Some(entry) => Ok(Some(self.read_entry(name, entry)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn read_entry<'a>(&'a self, name: &str, entry: &Entry) -> Result<Cow<'a, [u8]>, BindleError> {
+        self.limits.check_entry(name, entry)?;
+        if entry.is_encrypted() {
+            return Ok(Cow::Owned(self.decrypted(name, entry)?));
+        }
+        decode_entry(self.stored(entry)?, name, entry, self.dict.as_deref())
+    }
+
+    /// Decrypts and decodes an encrypted entry.
+    ///
+    /// The CRC32 of the stored bytes is checked first so corruption isn't reported as a wrong key.
+    fn decrypted(&self, name: &str, entry: &Entry) -> Result<Vec<u8>, BindleError> {
+        let cipher = self.cipher.as_ref().ok_or_else(crypto::missing_key)?;
+        let stored = self.stored(entry)?.read_all()?;
+        let computed_crc = crc32fast::hash(&stored);
+        if computed_crc != entry.crc32() {
+            return Err(BindleError::CrcMismatch {
+                name: name.to_string(),
+                expected: entry.crc32(),
+                got: computed_crc,
+            });
+        }
+
+        let plain = crypto::decrypt(cipher, name, &stored)?;
+        let mut out = Vec::with_capacity(entry.uncompressed_size().min(MAX_PREALLOC) as usize);
+        Reader::new(&plain, name, entry, self.dict.as_deref())?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    /// Reads bytes `start..end` of an entry's uncompressed data.
+    ///
+    /// Uncompressed entries are borrowed straight from the memory map. Compressed entries are decoded
+    /// only up to `end`, starting from the nearest frame for seekable zstd entries. `end` is clamped
+    /// to the entry size. Returns `None` if the entry doesn't exist, `start` is past `end` or the entry
+    /// size, or the data can't be decoded. The CRC32 covers the whole entry, so ranges aren't verified.
+    pub fn read_range<'a>(&'a self, name: &str, start: u64, end: u64) -> Option<Cow<'a, [u8]>> {
+        let name = self.stored_name(name);
+        let entry = self.index.get(name)?;
+        let end = end.min(entry.uncompressed_size());
+        if start > end {
+            return None;
+        }
+
+        match entry.compression_type() {
+            Compress::None | Compress::Auto if !entry.is_encrypted() => {
+                self.stored(entry).ok()?.read(start..end).ok()
+            }
+            _ => {
+                let mut reader = self.entry_reader(name, entry).ok()?;
+                if entry.is_seekable() {
+                    reader.seek(SeekFrom::Start(start)).ok()?;
+                } else {
+                    io::copy(&mut (&mut reader).take(start), &mut io::sink()).ok()?;
+                }
+                let mut out = Vec::with_capacity((end - start) as usize);
+                reader.take(end - start).read_to_end(&mut out).ok()?;
+                Some(Cow::Owned(out))
+            }
+        }
+    }
+
+    /// Checks the CRC32 of every entry without extracting it.
+    ///
+    /// Entries are streamed through the decoder and hashed incrementally, so large entries are never
+    /// held in memory. All entries are checked, even after a failure.
+    /// Returns each entry name in order with `true` if it passed.
+    pub fn verify(&self) -> io::Result<Vec<(String, bool)>> {
+        let report = self
+            .index
+            .keys()
+            .map(|name| {
+                let ok = self
+                    .reader(name)
+                    .and_then(|mut reader| {
+                        io::copy(&mut reader, &mut io::sink())?;
+                        reader.verify_crc32()
+                    })
+                    .is_ok();
+                (name.clone(), ok)
+            })
+            .collect();
+        Ok(report)
+    }
+
+    /// Iterates over every entry in name order, yielding its name and decoded data.
+    ///
+    /// Each entry is decompressed and CRC32 verified like [`read()`](Bindle::read). If `skip_corrupt` is true,
+    /// entries that fail to decode or verify are skipped, otherwise they are yielded as errors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// for item in archive.contents(false) {
+    ///     let (name, data) = item?;
+    ///     println!("{}: {} bytes", name, data.len());
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn contents(
+        &self,
+        skip_corrupt: bool,
+    ) -> impl Iterator<Item = io::Result<(&str, Cow<'_, [u8]>)>> {
+        self.index
+            .iter()
+            .map(|(name, entry)| {
+                self.read_entry(name, entry)
+                    .map(|data| (name.as_str(), data))
+                    .map_err(io::Error::from)
+            })
+            .filter(move |item| !(skip_corrupt && item.is_err()))
+    }
+
+    /// Reads an entry into a provided buffer, avoiding allocation.
+    ///
+    /// Decompresses if needed and returns the number of bytes read. Compressed entries are streamed
+    /// through the decoder only until the buffer is full. If the buffer is too small, only the first
+    /// `buffer.len()` bytes are read and, since the CRC32 covers the whole entry, they aren't
+    /// verified. Entries that fit are verified.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// let mut buffer = vec![0u8; 1024];
+    /// let bytes_read = archive.read_into("file.txt", &mut buffer)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_into(&self, name: &str, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut reader = self.reader(name)?;
+        let mut bytes_read = 0;
+        while bytes_read < buffer.len() {
+            match reader.read(&mut buffer[bytes_read..]) {
+                Ok(0) => break,
+                Ok(n) => bytes_read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let size = self.size(name).unwrap_or_default();
+        if size <= buffer.len() as u64 {
+            reader.verify_crc32()?;
+        }
+        Ok(bytes_read)
+    }
+
+    /// Borrows an uncompressed entry straight from the memory map, without copying it.
+    ///
+    /// Returns `None` if the entry doesn't exist, is compressed, fails CRC32 verification, or the
+    /// archive was opened with [`Backend::Pread`]. Use [`decompress_into()`](Bindle::decompress_into)
+    /// for compressed entries.
+    pub fn read_ref(&self, name: &str) -> Option<&[u8]> {
+        let name = self.stored_name(name);
+        let entry = self.index.get(name)?;
+        if entry.compression_type() != Compress::None {
+            return None;
+        }
+        match self.read_entry(name, entry).ok()? {
+            Cow::Borrowed(data) => Some(data),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    /// Decompresses an entry into `buffer`, replacing its contents.
+    ///
+    /// Reusing one buffer across calls avoids allocating for every read once it has grown to fit the
+    /// largest entry. Verifies CRC32 and returns the number of bytes read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// let mut buffer = Vec::new();
+    /// for name in ["a.json", "b.json", "c.json"] {
+    ///     archive.decompress_into(name, &mut buffer)?;
+    ///     println!("{}: {} bytes", name, buffer.len());
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn decompress_into(&self, name: &str, buffer: &mut Vec<u8>) -> io::Result<usize> {
+        let mut reader = self.reader(name)?;
+        buffer.clear();
+        buffer.reserve(self.size(name).unwrap_or_default() as usize);
+        let n = reader.read_to_end(buffer)?;
+        reader.verify_crc32()?;
+        Ok(n)
+    }
+
+    /// Reads an entry and writes it to the given writer.
+    ///
+    /// Returns the number of bytes written. Verifies CRC32 after reading.
+    pub fn read_to<W: std::io::Write>(&self, name: &str, mut w: W) -> std::io::Result<u64> {
+        let mut reader = self.reader(name)?;
+        let bytes_copied = std::io::copy(&mut reader, &mut w)?;
+        reader.verify_crc32()?;
+        Ok(bytes_copied)
+    }
+
+    /// Reads an entry and writes it to the given writer, reporting progress as it goes.
+    ///
+    /// Data is copied in chunks of up to `buf_size` bytes and `progress` is called after each chunk
+    /// with the total number of bytes written so far. Returns the number of bytes written and
+    /// verifies CRC32 after reading like [`read_to()`](Bindle::read_to).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// let total = archive.size("large.bin").unwrap_or(0);
+    /// let out = std::fs::File::create("large.bin")?;
+    /// archive.read_to_with_progress("large.bin", out, 1 << 20, |done| {
+    ///     eprint!("\r{}/{} bytes", done, total);
+    /// })?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn read_to_with_progress<W: std::io::Write>(
+        &self,
+        name: &str,
+        mut w: W,
+        buf_size: usize,
+        mut progress: impl FnMut(u64),
+    ) -> std::io::Result<u64> {
+        let mut reader = self.reader(name)?;
+        let mut buf = vec![0u8; buf_size.max(1)];
+        let mut bytes_copied = 0u64;
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            w.write_all(&buf[..n])?;
+            bytes_copied += n as u64;
+            progress(bytes_copied);
+        }
+        reader.verify_crc32()?;
+        Ok(bytes_copied)
+    }
+
+    /// Returns a streaming reader for an entry.
+    ///
+    /// Automatically decompresses if the entry is compressed. Call [`Reader::verify_crc32()`] after reading to verify integrity.
+    pub fn reader<'a>(&'a self, name: &str) -> io::Result<Reader<'a>> {
+        let name = self.stored_name(name);
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+        self.limits.check_entry(name, entry)?;
+        self.entry_reader(name, entry)
+    }
+
+    /// Returns a streaming reader for an entry that keeps the memory map alive instead of borrowing
+    /// the archive.
+    ///
+    /// Unlike [`reader()`](Bindle::reader) the result is `'static` and `Send`, so it can be moved to
+    /// a worker thread or returned from a function that owns the archive. For in-memory archives the
+    /// entry's stored bytes are copied. Call [`OwnedReader::verify_crc32()`] after reading to verify
+    /// integrity.
+    pub fn owned_reader(&self, name: &str) -> io::Result<OwnedReader> {
+        let name = self.stored_name(name);
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+        self.limits.check_entry(name, entry)?;
+        if entry.is_encrypted() {
+            return Ok(OwnedReader::decoded(name, self.decrypted(name, entry)?));
+        }
+        let dict = self.dict.as_deref();
+        match &self.storage {
+            Storage::File(_) => OwnedReader::new(self.view_data()?, name, entry, dict),
+            Storage::Memory(buf) => {
+                let stored = stored_bytes(buf.get_ref(), entry)?;
+                let mut local = *entry;
+                local.set_offset(0);
+                OwnedReader::new(ViewData::Memory(Arc::from(stored)), name, &local, dict)
+            }
+        }
+    }
+
+    /// Returns a streaming reader for an entry without checking its size against the limits.
+    fn entry_reader<'a>(&'a self, name: &str, entry: &Entry) -> io::Result<Reader<'a>> {
+        if entry.is_encrypted() {
+            return Ok(Reader::decoded(name, self.decrypted(name, entry)?));
+        }
+        entry_reader(self.stored(entry)?, name, entry, self.dict.as_deref())
+    }
+
+    /// Returns a read-only view of the archive that can be shared between threads.
+    ///
+    /// The view holds the memory map and a snapshot of the index, so reads don't touch the file
+    /// handle and the view stays valid while this archive keeps writing. Entries added after the last
+    /// [`save()`](Bindle::save) fail to read from the view, since their data isn't mapped yet.
+    /// In-memory archives copy their buffer into the view. With [`Backend::Pread`] the view shares a
+    /// handle to the file and reads from it instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// let view = archive.view()?;
+    /// std::thread::scope(|s| {
+    ///     for name in ["a.txt", "b.txt"] {
+    ///         let view = view.clone();
+    ///         s.spawn(move || view.read(name).map(|data| data.len()));
+    ///     }
+    /// });
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn view(&self) -> io::Result<ReadOnlyView> {
+        Ok(ReadOnlyView {
+            data: self.view_data()?,
+            index: Arc::new(self.index.clone()),
+            dict: self.dict.clone(),
+            limits: self.limits,
+        })
+    }
+
+    /// Returns the archive bytes in a form that can be shared, copying in-memory archives.
+    fn view_data(&self) -> io::Result<ViewData> {
+        Ok(match &self.storage {
+            Storage::File(file) => match self.mapping.get(file, self.committed_len)? {
+                Some(mmap) => ViewData::Mmap(Arc::clone(mmap)),
+                None => ViewData::File(self.mapping.shared_file(file, &self.path)?),
+            },
+            Storage::Memory(buf) => ViewData::Memory(Arc::from(buf.get_ref().as_slice())),
+        })
+    }
+
+    /// Reads an entry without blocking the async runtime, available with the `async` feature.
+    ///
+    /// Reads from a [`view()`](Bindle::view) on tokio's blocking thread pool, so the future doesn't
+    /// borrow the archive and has the same limits as the view: entries added since the last
+    /// [`save()`](Bindle::save) and encrypted entries can't be read. Must be awaited within a tokio
+    /// runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> std::io::Result<()> {
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// let data = archive.read_async("large.bin").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn read_async(&self, name: &str) -> impl Future<Output = io::Result<Vec<u8>>> + 'static {
+        let view = self.view();
+        let name = name.to_string();
+        async move { view?.read_async(&name).await }
+    }
+
+    /// Returns an [`AsyncReader`](crate::AsyncReader) streaming an entry, available with the `async`
+    /// feature.
+    ///
+    /// Reads from a [`view()`](Bindle::view) like [`read_async()`](Bindle::read_async). Returns
+    /// [`io::ErrorKind::NotFound`] if the entry doesn't exist. Must be called within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub fn async_reader(&self, name: &str) -> io::Result<crate::AsyncReader> {
+        self.view()?.async_reader(name)
+    }
+
+    /// Returns the stored bytes of `entry`, from the memory map, the file or the in-memory buffer.
+    fn stored(&self, entry: &Entry) -> io::Result<Region<'_>> {
+        match &self.storage {
+            Storage::File(file) => match self.mapping.get(file, self.committed_len)? {
+                Some(mmap) => Region::Memory(mmap).entry(entry),
+                None => Ok(Region::file(file, entry)),
+            },
+            Storage::Memory(buf) => Region::Memory(buf.get_ref()).entry(entry),
+        }
+    }
+
+    /// Returns the format version of the archive.
+    ///
+    /// Archives before version 3 have no index checksum, so index corruption can't be detected
+    /// when they are opened. The version is upgraded the next time the index is saved.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the offset where the index starts, which is also the end of the data region.
+    ///
+    /// Entries written since the last [`save()`](Bindle::save) move this forward.
+    pub fn index_offset(&self) -> u64 {
+        self.data_end
+    }
+
+    /// Returns the length of the archive as of the last [`save()`](Bindle::save), from the header
+    /// through the footer.
+    ///
+    /// Entries written since then aren't included until the next save. Only in-memory state is read.
+    pub fn committed_len(&self) -> u64 {
+        self.committed_len
+    }
+
+    /// Returns the number of stored bytes referenced by live entries and the zstd dictionary.
+    ///
+    /// Data shared by deduplicated entries is counted once. Alignment padding isn't counted, so this
+    /// plus [`dead_space()`](Bindle::dead_space) can fall a few bytes per entry short of the data
+    /// region. Only the index is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("a.txt", b"old", Compress::None)?;
+    /// archive.add("a.txt", b"new!", Compress::None)?;
+    /// archive.save()?;
+    /// assert_eq!(archive.live_data_bytes(), 4);
+    /// assert_eq!(archive.dead_space(), 8);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn live_data_bytes(&self) -> u64 {
+        self.live_blocks().map(|(_, size)| size).sum()
+    }
+
+    /// Returns the number of bytes in the data region not referenced by any live entry.
+    ///
+    /// This is the space [`vacuum()`](Bindle::vacuum) would reclaim, left behind by shadowed and removed entries.
+    pub fn dead_space(&self) -> u64 {
+        let live: u64 = self
+            .live_blocks()
+            .map(|(_, size)| size + pad::<BNDL_ALIGN, u64>(size))
+            .sum();
+        (self.data_end - HEADER_SIZE as u64).saturating_sub(live)
+    }
+
+    /// Iterates over the offset and size of the data referenced by the index and the dictionary.
+    fn live_blocks(&self) -> impl Iterator<Item = (u64, u64)> {
+        // Deduplicated entries share their data, count it once
+        let mut seen = HashSet::new();
+        self.dict_entry
+            .iter()
+            .chain(self.index.values())
+            .map(|entry| (entry.offset(), entry.compressed_size()))
+            .filter(move |block| seen.insert(*block))
+    }
+
+    /// Returns the fraction of the data region taken up by dead space, from `0.0` to `1.0`.
+    ///
+    /// An archive with no data reports `0.0`. Useful as a threshold for deciding when to vacuum.
+    pub fn fragmentation_ratio(&self) -> f64 {
+        let data_len = self.data_end - HEADER_SIZE as u64;
+        if data_len > 0 {
+            self.dead_space() as f64 / data_len as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the number of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the archive contains no entries.
     pub fn is_empty(&self) -> bool {
         self.index.is_empty()
     }
 
-    /// Returns a reference to the archive index.
+    /// Iterates over entry names and their metadata in name order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.index
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Iterates over entry names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(|name| name.as_str())
+    }
+
+    /// Iterates over entry names starting with `prefix` in sorted order.
+    ///
+    /// Only the matching part of the index is visited, so listing a "directory" doesn't walk every name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("users/alice", b"1", Compress::None)?;
+    /// archive.add("users/bob", b"2", Compress::None)?;
+    /// archive.add("videos/cat.mp4", b"3", Compress::None)?;
+    /// let users: Vec<_> = archive.names_with_prefix("users/").collect();
+    /// assert_eq!(users, ["users/alice", "users/bob"]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn names_with_prefix(&self, prefix: &str) -> impl Iterator<Item = &str> {
+        self.entries_with_prefix(prefix).map(|(name, _)| name)
+    }
+
+    /// Returns the entries as a tree of directories, splitting names on `/`.
+    ///
+    /// Builds the whole tree at once, see [`children_of()`](Bindle::children_of) to list one
+    /// directory at a time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("docs/index.html", b"<html>", Compress::None)?;
+    /// archive.add("docs/img/logo.png", b"png", Compress::None)?;
+    /// archive.add("readme.txt", b"hi", Compress::None)?;
+    /// let tree = archive.tree();
+    /// assert!(tree.files.contains_key("readme.txt"));
+    /// let docs = &tree.dirs["docs"];
+    /// assert!(docs.files.contains_key("index.html"));
+    /// assert!(docs.dirs["img"].files.contains_key("logo.png"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn tree(&self) -> TreeNode {
+        let mut root = TreeNode::default();
+        for (name, entry) in &self.index {
+            root.insert(name, *entry);
+        }
+        root
+    }
+
+    /// Returns the immediate children of the directory `dir`, in name order.
+    ///
+    /// `dir` is a name prefix such as `docs` or `docs/`, an empty string lists the top level. Files are
+    /// returned by name and subdirectories with a trailing `/`, such as `index.html` and `img/`, so a
+    /// name that is both a file and a directory is listed twice. Names are split on every `/` like in
+    /// [`tree()`](Bindle::tree), so `a//b` is listed as the empty-named subdirectory `/` of `a`. Only
+    /// the start of each subdirectory is looked up in the index, its contents are skipped, so this
+    /// stays fast for large archives.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("docs/index.html", b"<html>", Compress::None)?;
+    /// archive.add("docs/img/logo.png", b"png", Compress::None)?;
+    /// archive.add("docs/img/icon.png", b"png", Compress::None)?;
+    /// assert_eq!(archive.children_of("docs"), ["img/", "index.html"]);
+    /// assert_eq!(archive.children_of(""), ["docs/"]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn children_of(&self, dir: &str) -> Vec<&str> {
+        let prefix = dir_prefix(dir);
+        let mut children = Vec::new();
+        let mut from = prefix.to_string();
+        while let Some((name, _)) = self
+            .index
+            .range::<str, _>((Bound::Included(from.as_str()), Bound::Unbounded))
+            .next()
+        {
+            let Some(rest) = name.strip_prefix(prefix.as_ref()) else {
+                break;
+            };
+            match rest.find('/') {
+                Some(i) => {
+                    children.push(&name[prefix.len()..prefix.len() + i + 1]);
+                    // '0' follows '/', so this sorts after everything in the subdirectory
+                    from = format!("{}{}0", prefix, &rest[..i]);
+                }
+                None => {
+                    children.push(rest);
+                    from = format!("{}\0", name);
+                }
+            }
+        }
+        children
+    }
+
+    /// Iterates over the entries whose names start with `prefix`, in name order.
+    ///
+    /// Like [`names_with_prefix()`](Bindle::names_with_prefix), only the matching range of the index
+    /// is visited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("logs/a.txt", b"first", Compress::None)?;
+    /// archive.add("logs/b.txt", b"second", Compress::None)?;
+    /// archive.add("readme.txt", b"docs", Compress::None)?;
+    /// let size: u64 = archive
+    ///     .entries_with_prefix("logs/")
+    ///     .map(|(_, entry)| entry.uncompressed_size())
+    ///     .sum();
+    /// assert_eq!(size, 11);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn entries_with_prefix(&self, prefix: &str) -> impl Iterator<Item = (&str, &Entry)> {
+        self.index
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(name, entry)| (name.as_str(), entry))
+            .take_while(move |(name, _)| name.starts_with(prefix))
+    }
+
+    /// Returns true if any entry name starts with `prefix`.
+    pub fn contains_prefix(&self, prefix: &str) -> bool {
+        self.names_with_prefix(prefix).next().is_some()
+    }
+
+    /// Returns a reference to the archive index.
+    ///
+    /// The index maps entry names to their metadata.
+    pub fn index(&self) -> &BTreeMap<String, Entry> {
+        &self.index
+    }
+
+    /// Removes all entries from the index.
+    ///
+    /// Call [`save()`](Bindle::save) to commit. Data remains in the file until [`vacuum()`](Bindle::vacuum) is called.
+    pub fn clear(&mut self) {
+        if let Some(names) = &mut self.folded_names {
+            names.clear();
+        }
+        self.index.clear();
+        self.meta.clear();
+        self.dirty = true;
+    }
+
+    /// Returns true if an entry with the given name exists.
+    pub fn exists(&self, name: &str) -> bool {
+        self.index.contains_key(self.stored_name(name))
+    }
+
+    /// Returns the index entry for `name`, if it exists.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("hello.txt", b"hello", Compress::None)?;
+    /// let entry = archive.entry("hello.txt").unwrap();
+    /// assert_eq!(entry.crc32(), crc32fast::hash(b"hello"));
+    /// assert!(archive.entry("missing.txt").is_none());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.index.get(self.stored_name(name))
+    }
+
+    /// Sets the zstd dictionary used to compress new zstd entries.
+    ///
+    /// Dictionaries help when there are many small entries with similar contents, such as JSON records
+    /// sharing a schema, see [`train_zstd_dictionary()`](Bindle::train_zstd_dictionary). The dictionary
+    /// is stored in the archive as a reserved index record, which isn't listed as an entry, and is loaded
+    /// again when the archive is opened. Entries written earlier keep decoding without it. Seekable
+    /// entries don't use the dictionary. Since entries compressed with a dictionary can't be read
+    /// without it, returns an [`io::ErrorKind::AlreadyExists`] error if a different dictionary is
+    /// already set and any entry uses it. Call [`save()`](Bindle::save) to commit changes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::open("records.bndl")?;
+    /// let dict = archive.train_zstd_dictionary(16 * 1024)?;
+    /// archive.set_zstd_dictionary(dict)?;
+    /// archive.add("record-1001.json", br#"{"id":1001,"kind":"user"}"#, Compress::Zstd)?;
+    /// archive.save()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn set_zstd_dictionary(&mut self, dict: Vec<u8>) -> io::Result<()> {
+        self.check_writable()?;
+        if self.dict.as_deref() == Some(&dict[..]) {
+            return Ok(());
+        }
+        if self.dict_entry.is_some() && self.index.values().any(Entry::uses_dictionary) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "Entries are already compressed with a different zstd dictionary",
+            ));
+        }
+
+        let mut entry = Entry::default();
+        entry.set_uncompressed_size(dict.len() as u64);
+        entry.set_crc32(crc32fast::hash(&dict));
+        let stored = self.seal(DICT_ENTRY, &dict, &mut entry)?;
+        let entry = self.write_stored(DICT_ENTRY, &stored, entry)?;
+        self.dict_entry = Some(entry);
+        self.dict = Some(Arc::from(dict));
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Returns the zstd dictionary set with [`set_zstd_dictionary()`](Bindle::set_zstd_dictionary).
+    ///
+    /// Returns `None` if there is none, or if it's encrypted and the archive wasn't opened with a key.
+    pub fn zstd_dictionary(&self) -> Option<&[u8]> {
+        self.dict.as_deref()
+    }
+
+    /// Trains a zstd dictionary of up to `max_size` bytes from the entries in the archive.
+    ///
+    /// Every entry except symbolic links is decoded and used as a sample, so train on a representative
+    /// archive and pass the result to [`set_zstd_dictionary()`](Bindle::set_zstd_dictionary). A few
+    /// KiB to around 100 KiB is typical. Returns an error if there are too few samples to train on.
+    pub fn train_zstd_dictionary(&self, max_size: usize) -> io::Result<Vec<u8>> {
+        let samples = self
+            .index
+            .iter()
+            .filter(|(_, entry)| !entry.is_symlink())
+            .map(|(name, entry)| Ok(self.read_entry(name, entry)?.into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        zstd::dict::from_samples(&samples, max_size)
+    }
+
+    /// Returns the decoded metadata of an entry, if it exists.
+    ///
+    /// Unlike [`entry()`](Bindle::entry) the result is owned and doesn't expose the index layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("data.bin", &[0u8; 4096], Compress::Zstd)?;
+    /// let info = archive.stat("data.bin").unwrap();
+    /// assert_eq!(info.name, "data.bin");
+    /// assert_eq!(info.uncompressed_size, 4096);
+    /// assert_eq!(info.compression, Compress::Zstd);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn stat(&self, name: &str) -> Option<EntryInfo> {
+        let name = self.stored_name(name);
+        self.entry(name).map(|entry| EntryInfo::new(name, entry))
+    }
+
+    /// Returns the metadata of every entry, ordered by compressed size.
     ///
-    /// The index maps entry names to their metadata.
-    pub fn index(&self) -> &BTreeMap<String, Entry> {
-        &self.index
+    /// Entries with the same size are ordered by name, so the output is deterministic. Only the
+    /// index is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("small.txt", b"hi", Compress::None)?;
+    /// archive.add("large.bin", &[7u8; 4096], Compress::None)?;
+    /// let largest = archive.entries_by_size(true);
+    /// assert_eq!(largest[0].name, "large.bin");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn entries_by_size(&self, descending: bool) -> Vec<EntryInfo> {
+        let mut entries: Vec<_> = self
+            .index
+            .iter()
+            .map(|(name, entry)| EntryInfo::new(name, entry))
+            .collect();
+        entries.sort_by(|a, b| {
+            let size = a.compressed_size.cmp(&b.compressed_size);
+            let size = if descending { size.reverse() } else { size };
+            size.then_with(|| a.name.cmp(&b.name))
+        });
+        entries
     }
 
-    /// Removes all entries from the index.
+    /// Returns the uncompressed size of an entry in bytes.
     ///
-    /// Call [`save()`](Bindle::save) to commit. Data remains in the file until [`vacuum()`](Bindle::vacuum) is called.
-    pub fn clear(&mut self) {
-        self.index.clear()
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("data.bin", &[0u8; 4096], Compress::Zstd)?;
+    /// assert_eq!(archive.size("data.bin"), Some(4096));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn size(&self, name: &str) -> Option<u64> {
+        self.entry(name).map(|entry| entry.uncompressed_size())
     }
 
-    /// Returns true if an entry with the given name exists.
-    pub fn exists(&self, name: &str) -> bool {
-        self.index.contains_key(name)
+    /// Returns the number of bytes an entry takes up in the data region, excluding padding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("data.bin", &[0u8; 4096], Compress::Zstd)?;
+    /// assert!(archive.compressed_size("data.bin").unwrap() < 4096);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn compressed_size(&self, name: &str) -> Option<u64> {
+        self.entry(name).map(|entry| entry.compressed_size())
+    }
+
+    /// Returns the compression an entry was stored with.
+    ///
+    /// [`Compress::Auto`] is resolved when the entry is written, so it's never returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// archive.add("small.txt", b"tiny", Compress::Auto)?;
+    /// assert_eq!(archive.compression_of("small.txt"), Some(Compress::None));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn compression_of(&self, name: &str) -> Option<Compress> {
+        self.entry(name).map(|entry| entry.compression_type())
+    }
+
+    /// Renames an entry without copying its data.
+    ///
+    /// Returns false if `old` doesn't exist, an [`io::ErrorKind::AlreadyExists`] error if `new` is
+    /// already taken, and an [`io::ErrorKind::PermissionDenied`] error if the archive was opened
+    /// read-only. The data stays where it is and the next [`save()`](Bindle::save) writes the index
+    /// with the new name. Renaming frees no space by itself, [`vacuum()`](Bindle::vacuum) only reclaims
+    /// older versions the entry was shadowing.
+    ///
+    /// Encrypted entries are the exception: their name is authenticated with the data, so they are
+    /// decrypted and written again under the new name, which needs the key.
+    pub fn rename(&mut self, old: &str, new: &str) -> io::Result<bool> {
+        self.rename_entry(old, new, false)
+    }
+
+    /// Renames an entry like [`rename()`](Bindle::rename), replacing any existing entry named `new`.
+    ///
+    /// The replaced entry's data becomes reclaimable by [`vacuum()`](Bindle::vacuum).
+    pub fn rename_overwrite(&mut self, old: &str, new: &str) -> io::Result<bool> {
+        self.rename_entry(old, new, true)
+    }
+
+    fn rename_entry(&mut self, old: &str, new: &str, overwrite: bool) -> io::Result<bool> {
+        self.check_writable()?;
+        check_name(new)?;
+        let old = &self.stored_name(old).to_string();
+        if old == new {
+            return Ok(self.index.contains_key(old));
+        }
+        if !self.index.contains_key(old) {
+            return Ok(false);
+        }
+        // Changing only the case of a name is not a conflict when lookups ignore case
+        let taken = self.stored_name(new);
+        if !overwrite && taken != old && self.index.contains_key(taken) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Entry '{}' already exists", new),
+            ));
+        }
+        let resealed = match self.index.get(old) {
+            Some(entry) if entry.is_encrypted() => Some(self.reseal(old, new, *entry)?),
+            _ => None,
+        };
+        self.meta.remove(new);
+        let Some(mut entry) = self.index.remove(old) else {
+            return Ok(false);
+        };
+        if let Some(resealed) = resealed {
+            entry = resealed;
+        }
+        if let Some(names) = &mut self.folded_names {
+            names.remove(&old.to_lowercase());
+        }
+        entry.set_name_len(new.len() as u16);
+        self.insert_entry(new.to_string(), entry);
+        if let Some(meta) = self.meta.remove(old) {
+            self.meta.insert(new.to_string(), meta);
+        }
+        self.dirty = true;
+        Ok(true)
+    }
+
+    /// Decrypts the data of `entry`, stored as `old`, and writes it again encrypted for `new`,
+    /// returning the entry updated to point at the new copy.
+    fn reseal(&mut self, old: &str, new: &str, mut entry: Entry) -> io::Result<Entry> {
+        let cipher = self.cipher.as_ref().ok_or_else(crypto::missing_key)?;
+        // Read through the file handle, data written since the last save isn't mapped yet
+        let mut stored = vec![0u8; entry.compressed_size() as usize];
+        self.storage.seek(SeekFrom::Start(entry.offset()))?;
+        self.storage.read_exact(&mut stored)?;
+        let computed_crc = crc32fast::hash(&stored);
+        if computed_crc != entry.crc32() {
+            return Err(BindleError::CrcMismatch {
+                name: old.to_string(),
+                expected: entry.crc32(),
+                got: computed_crc,
+            }
+            .into());
+        }
+        let plain = crypto::decrypt(cipher, old, &stored)?;
+        let sealed = crypto::encrypt(cipher, new, &plain)?;
+        entry.set_crc32(crc32fast::hash(&sealed));
+        self.write_stored(new, &sealed, entry)
+    }
+
+    /// Removes an entry from the index.
+    ///
+    /// Returns true if the entry existed. Data remains in the file until [`vacuum()`](Bindle::vacuum) is called.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let name = self.stored_name(name).to_string();
+        let name = name.as_str();
+        if let Some(names) = &mut self.folded_names {
+            names.remove(&name.to_lowercase());
+        }
+        self.meta.remove(name);
+        let removed = self.index.remove(name).is_some();
+        self.dirty |= removed;
+        removed
+    }
+
+    /// Recursively adds all files from a directory to the archive.
+    ///
+    /// File paths are stored relative to the source directory. Returns how many files were added and
+    /// their sizes, see [`PackStats`]. Call [`save()`](Bindle::save) to commit.
+    pub fn pack<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+    ) -> io::Result<PackStats> {
+        self.pack_with_level(src_dir, compress, DEFAULT_ZSTD_LEVEL)
+    }
+
+    /// Recursively adds all files from a directory, compressing with the given zstd level.
+    pub fn pack_with_level<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+        level: i32,
+    ) -> io::Result<PackStats> {
+        self.pack_with_options(src_dir, compress, level, &PackOptions::default())
+    }
+
+    /// Recursively adds the files from a directory that aren't excluded by `options`.
+    ///
+    /// Excluded directories are skipped without reading their contents. Symbolic links are handled
+    /// as set by [`PackOptions::symlinks()`], and identical files are stored once if
+    /// [`PackOptions::dedup()`] is set.
+    pub fn pack_with_options<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+        level: i32,
+        options: &PackOptions,
+    ) -> io::Result<PackStats> {
+        self.pack_with_options_and_progress(src_dir, compress, level, options, |_| {})
+    }
+
+    /// Recursively adds all files from a directory like [`pack()`](Bindle::pack), reporting progress.
+    ///
+    /// `progress` is called right before and right after each file is added, see [`PackEvent`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::open("data.bndl")?;
+    /// archive.pack_with_progress("site", Compress::Zstd, |event| {
+    ///     if event.finished {
+    ///         eprint!("\r{}/{} files, {} bytes", event.done, event.total, event.bytes);
+    ///     }
+    /// })?;
+    /// archive.save()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn pack_with_progress<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+        progress: impl FnMut(PackEvent),
+    ) -> io::Result<PackStats> {
+        self.pack_with_options_and_progress(
+            src_dir,
+            compress,
+            DEFAULT_ZSTD_LEVEL,
+            &PackOptions::default(),
+            progress,
+        )
+    }
+
+    /// Adds files like [`pack_with_options()`](Bindle::pack_with_options), reporting progress like
+    /// [`pack_with_progress()`](Bindle::pack_with_progress).
+    pub fn pack_with_options_and_progress<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+        level: i32,
+        options: &PackOptions,
+        progress: impl FnMut(PackEvent),
+    ) -> io::Result<PackStats> {
+        self.check_writable()?;
+        let (files, skipped) = pack_candidates(src_dir.as_ref(), options)?;
+        let stats = self.pack_files(files, compress, level, options.dedup, progress)?;
+        Ok(PackStats { skipped, ..stats })
+    }
+
+    /// Adds files listed by [`pack_candidates()`], storing each link entry's target.
+    pub(crate) fn pack_files(
+        &mut self,
+        files: Vec<PackFile>,
+        compress: Compress,
+        level: i32,
+        dedup: bool,
+        mut progress: impl FnMut(PackEvent),
+    ) -> io::Result<PackStats> {
+        self.check_writable()?;
+        let enable_dedup = dedup && self.dedup.is_none();
+        if enable_dedup {
+            self.set_dedup(true);
+        }
+        let total = files.len();
+        let mut bytes = 0;
+        let mut stats = PackStats::default();
+        let result = files
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(done, (name, path, is_link))| {
+                let mut event = PackEvent {
+                    name: &name,
+                    bytes,
+                    done,
+                    total,
+                    finished: false,
+                    link_as_file: false,
+                };
+                progress(event);
+                if is_link {
+                    self.add_symlink(&name, &std::fs::read_link(&path)?)?;
+                } else {
+                    self.add_file_with_level(&name, &path, compress, level)?;
+                }
+                let entry = self.index[self.stored_name(&name)];
+                stats.add(&entry);
+                bytes += entry.uncompressed_size();
+                event.bytes = bytes;
+                event.done = done + 1;
+                event.finished = true;
+                progress(event);
+                Ok(())
+            });
+        if enable_dedup {
+            self.set_dedup(false);
+        }
+        result.map(|()| stats)
+    }
+
+    /// Adds an entry holding the target of a symbolic link.
+    ///
+    /// Targets are stored with `/` separators so links unpack on any platform.
+    fn add_symlink(&mut self, name: &str, target: &Path) -> io::Result<()> {
+        let mut target = target.to_string_lossy().into_owned();
+        if cfg!(windows) {
+            target = target.replace('\\', "/");
+        }
+        let mut entry = Entry::default();
+        entry.set_uncompressed_size(target.len() as u64);
+        entry.set_crc32(crc32fast::hash(target.as_bytes()));
+        entry.insert_flags(ENTRY_FLAG_SYMLINK);
+        let stored = self.seal(name, target.as_bytes(), &mut entry)?;
+        self.append_stored(name, &stored, entry)
+    }
+
+    /// Recursively adds all files from a directory, reading and compressing them on `threads` threads.
+    ///
+    /// Entries are named the same way as [`pack()`](Bindle::pack), and zstd entries are compressed
+    /// at `level` like [`pack_with_options()`](Bindle::pack_with_options). Passing `0` uses the available
+    /// parallelism. Compression happens in parallel but appending to the archive is still serial, so
+    /// every compressed payload is held in memory until all files are done. Peak memory use is roughly
+    /// the compressed size of the whole directory, prefer [`pack()`](Bindle::pack) when that's too much.
+    pub fn pack_parallel<P: AsRef<Path>>(
+        &mut self,
+        src_dir: P,
+        compress: Compress,
+        level: i32,
+        threads: usize,
+    ) -> io::Result<PackStats> {
+        check_level(level)?;
+        self.check_writable()?;
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        let (files, skipped) = pack_candidates(src_dir.as_ref(), &PackOptions::default())?;
+
+        let mut stats = PackStats::default();
+        let next = AtomicUsize::new(0);
+        let packed: Vec<_> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.min(files.len()))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some((_, path, _)) = files.get(i) else {
+                                return done;
+                            };
+                            done.push((i, self.encode_file(path, compress, level)));
+                        }
+                    })
+                })
+                .collect();
+
+            let mut packed = Vec::new();
+            for worker in workers {
+                let done = worker
+                    .join()
+                    .map_err(|_| io::Error::other("pack worker panicked"))?;
+                packed.extend(done);
+            }
+            packed.sort_by_key(|(i, _)| *i);
+            Ok::<_, io::Error>(packed)
+        })?;
+
+        for (i, encoded) in packed {
+            let (stored, compress, uncompressed_size, crc32, sha256, modified) = encoded?;
+            let name = &files[i].0;
+            let mut entry = Entry::default();
+            entry.set_uncompressed_size(uncompressed_size);
+            entry.set_crc32(crc32);
+            entry.compression_type = compress as u8;
+            entry.set_flags(self.new_entry_flags(compress));
+            match sha256.and_then(|hash| self.dedup_target(&hash, &entry)) {
+                Some(mut shared) => {
+                    check_name(name)?;
+                    shared.set_name_len(name.len() as u16);
+                    self.meta.remove(name);
+                    self.insert_entry(name.clone(), shared);
+                    self.dirty = true;
+                }
+                None => {
+                    let stored = self.seal(name, &stored, &mut entry)?;
+                    self.append_stored(name, &stored, entry)?
+                }
+            }
+            if let Some(hash) = sha256 {
+                self.record_sha256(name, hash);
+            }
+            if let Some(t) = modified {
+                self.set_mtime(name, t);
+            }
+            stats.add(&self.index[self.stored_name(name)]);
+        }
+        Ok(PackStats { skipped, ..stats })
+    }
+
+    /// Adds the files from a tar archive read from `reader`, available with the `tar` feature.
+    ///
+    /// Regular files are streamed into entries named by their path in the tarball, with any leading
+    /// `./` removed, and keep their modification time. Symbolic links become link entries like
+    /// [`Symlinks::Store`] makes them. Directories are implied by the names and skipped, other entries
+    /// such as hard links and devices are skipped and counted in [`PackStats::skipped`]. If reading
+    /// the tarball fails, or it ends inside a file, that file is dropped and the error returned, the
+    /// files before it stay added. Call [`save()`](Bindle::save) to commit changes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::open("data.bndl")?;
+    /// let stats = archive.import_tar(std::fs::File::open("data.tar")?, Compress::Zstd)?;
+    /// println!("{} files", stats.files);
+    /// archive.save()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "tar")]
+    pub fn import_tar<R: Read>(&mut self, reader: R, compress: Compress) -> io::Result<PackStats> {
+        self.check_writable()?;
+        let mut stats = PackStats::default();
+        let mut tarball = tar::Archive::new(reader);
+        for entry in tarball.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?;
+            let name = path.to_str().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("tar entry {} isn't valid UTF-8", path.display()),
+                )
+            })?;
+            let name = name.trim_start_matches("./").to_string();
+
+            match entry.header().entry_type() {
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    let size = entry.size();
+                    let mut writer = self.writer(&name, compress)?;
+                    let copied = match io::copy(&mut entry, &mut writer) {
+                        Ok(n) if n == size => Ok(()),
+                        Ok(_) => Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!("tar entry {} is truncated", name),
+                        )),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = copied {
+                        // Don't commit the part that was read
+                        writer.abort()?;
+                        return Err(e);
+                    }
+                    writer.close()?;
+                }
+                tar::EntryType::Symlink => match entry.link_name()? {
+                    Some(target) => self.add_symlink(&name, &target)?,
+                    None => {
+                        stats.skipped += 1;
+                        continue;
+                    }
+                },
+                tar::EntryType::Directory | tar::EntryType::XGlobalHeader => continue,
+                _ => {
+                    stats.skipped += 1;
+                    continue;
+                }
+            }
+            if let Ok(mtime) = entry.header().mtime() {
+                self.set_mtime(&name, UNIX_EPOCH + Duration::from_secs(mtime));
+            }
+            stats.add(&self.index[self.stored_name(&name)]);
+        }
+        Ok(stats)
+    }
+
+    /// Writes every entry to `w` as a tar archive, available with the `tar` feature.
+    ///
+    /// Entries are decompressed and written in name order as regular files with mode `0644` and
+    /// their modification time, or the Unix epoch if they have none. Link entries become symbolic
+    /// links. Each entry is decoded twice, once to check its CRC32 before anything is written for it
+    /// and once while writing, so a damaged entry fails the export instead of ending up in the
+    /// tarball and large entries are never held in memory. An entry whose decoded size differs from
+    /// the size in its index record fails the export too.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("data.bndl")?;
+    /// archive.export_tar(std::fs::File::create("data.tar")?)?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "tar")]
+    pub fn export_tar<W: Write>(&self, w: W) -> io::Result<()> {
+        let mut tarball = tar::Builder::new(w);
+        for (name, entry) in &self.index {
+            let mut reader = self.reader(name)?;
+            let size = io::copy(&mut reader, &mut io::sink())?;
+            reader.verify_crc32()?;
+            if size != entry.uncompressed_size() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "'{}': decoded {} bytes, the index records {}",
+                        name,
+                        size,
+                        entry.uncompressed_size()
+                    ),
+                ));
+            }
+
+            let mut header = tar::Header::new_gnu();
+            let mtime = self
+                .mtime(name)
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok());
+            header.set_mtime(mtime.map_or(0, |t| t.as_secs()));
+            if entry.is_symlink() {
+                let target = self.read(name)?;
+                let target = String::from_utf8_lossy(&target);
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_mode(0o777);
+                header.set_size(0);
+                tarball.append_link(&mut header, name, target.as_ref())?;
+            } else {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_mode(0o644);
+                header.set_size(size);
+                tarball.append_data(&mut header, name, self.reader(name)?.take(size))?;
+            }
+        }
+        tarball.into_inner()?.flush()
+    }
+
+    /// Reads and compresses a file in memory for [`pack_parallel()`](Bindle::pack_parallel).
+    fn encode_file(&self, path: &Path, compress: Compress, level: i32) -> io::Result<EncodedFile> {
+        let data = std::fs::read(path)?;
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let uncompressed_size = data.len() as u64;
+        let crc32 = crc32fast::hash(&data);
+        let sha256 = self.hashes_sha256().then(|| Sha256::digest(&data).into());
+        let (stored, compress) = match self.encode(&data, compress, level)? {
+            (Cow::Owned(stored), compress) => (stored, compress),
+            (Cow::Borrowed(_), compress) => (data, compress),
+        };
+        Ok((stored, compress, uncompressed_size, crc32, sha256, modified))
+    }
+
+    /// Compresses `data` in memory, zstd at `level`, resolving [`Compress::Auto`].
+    ///
+    /// Uncompressed data is borrowed as is.
+    fn encode<'a>(
+        &self,
+        data: &'a [u8],
+        compress: Compress,
+        level: i32,
+    ) -> io::Result<(Cow<'a, [u8]>, Compress)> {
+        let compress = compress.resolve(data, data.len(), self.auto_compress_threshold);
+        let stored = match compress {
+            Compress::Zstd => Cow::Owned(match &self.dict {
+                Some(dict) => {
+                    zstd::bulk::Compressor::with_dictionary(level, dict)?.compress(data)?
+                }
+                None => zstd::bulk::compress(data, level)?,
+            }),
+            Compress::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                Cow::Owned(encoder.finish()?)
+            }
+            _ => Cow::Borrowed(data),
+        };
+        Ok((stored, compress))
+    }
+
+    /// Encrypts encoded data for a new entry named `name` if the archive has a key, updating the
+    /// flags and CRC32 of `entry` to match.
+    fn seal<'a>(
+        &self,
+        name: &str,
+        stored: &'a [u8],
+        entry: &mut Entry,
+    ) -> io::Result<Cow<'a, [u8]>> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(Cow::Borrowed(stored));
+        };
+        let encrypted = crypto::encrypt(cipher, name, stored)?;
+        entry.insert_flags(ENTRY_FLAG_ENCRYPTED);
+        entry.set_crc32(crc32fast::hash(&encrypted));
+        Ok(Cow::Owned(encrypted))
+    }
+
+    /// Appends already encoded data as a new entry, bypassing the streaming writer.
+    ///
+    /// Everything but the offset and name length is taken from `entry` as given.
+    fn append_stored(&mut self, name: &str, stored: &[u8], entry: Entry) -> io::Result<()> {
+        check_name(name)?;
+        let entry = self.write_stored(name, stored, entry)?;
+        self.meta.remove(name);
+        self.insert_entry(name.to_string(), entry);
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Writes already encoded data to the end of the data region, returning `entry` updated to
+    /// point at it.
+    fn write_stored(&mut self, name: &str, stored: &[u8], mut entry: Entry) -> io::Result<Entry> {
+        self.check_writable()?;
+        let start_offset = self.append_offset()?;
+        self.storage.write_all(stored)?;
+
+        let pad_len = pad::<BNDL_ALIGN, u64>(stored.len() as u64);
+        if pad_len > 0 {
+            write_padding(&mut self.storage, pad_len as usize)?;
+        }
+
+        entry.set_offset(start_offset);
+        entry.set_compressed_size(stored.len() as u64);
+        entry.set_name_len(name.len() as u16);
+
+        self.data_end += stored.len() as u64 + pad_len;
+        Ok(entry)
+    }
+
+    /// Returns the flags a new entry written with `compress` gets from the archive settings.
+    fn new_entry_flags(&self, compress: Compress) -> u8 {
+        let mut flags = 0;
+        if self.cipher.is_some() {
+            flags |= ENTRY_FLAG_ENCRYPTED;
+        }
+        if compress == Compress::Zstd && self.dict.is_some() {
+            flags |= ENTRY_FLAG_DICT;
+        }
+        flags
+    }
+
+    /// Copies an entry from another archive without decompressing it.
+    ///
+    /// The stored bytes are copied as-is, keeping the compression, CRC32 and metadata of the source.
+    /// An existing entry with the same name is shadowed. Returns [`io::ErrorKind::NotFound`] if `src`
+    /// has no entry named `name`. Entries compressed with a zstd dictionary can only be copied between
+    /// archives with the same dictionary, otherwise [`io::ErrorKind::Unsupported`] is returned.
+    /// Call [`save()`](Bindle::save) to commit changes.
+    pub fn copy_entry_from(&mut self, src: &Bindle, name: &str) -> io::Result<()> {
+        let mut entry = *src
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        let stored = src.stored(&entry)?.read_all()?;
+        if entry.uses_dictionary() && src.dict != self.dict {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Entry was compressed with a zstd dictionary this archive doesn't share",
+            ));
+        }
+        let stored = match entry.is_encrypted() {
+            true => Cow::Borrowed(&*stored),
+            false => self.seal(name, &stored, &mut entry)?,
+        };
+
+        self.append_stored(name, &stored, entry)?;
+        if let Some(meta) = src.meta.get(name) {
+            let mut meta = meta.clone();
+            if entry.is_encrypted() {
+                meta.sha256 = None;
+            }
+            self.meta.insert(name.to_string(), meta);
+        }
+        Ok(())
+    }
+
+    /// Copies every entry from another archive without decompressing it.
+    ///
+    /// Entries are copied like [`copy_entry_from()`](Bindle::copy_entry_from), straight from the
+    /// source's mapped data. `on_conflict` decides what happens to names that already exist; with
+    /// [`Conflict::Error`] an [`io::ErrorKind::AlreadyExists`] error is returned before anything is
+    /// copied. Returns the number of entries copied. Call [`save()`](Bindle::save) to commit changes.
+    pub fn merge(&mut self, other: &Bindle, on_conflict: Conflict) -> io::Result<usize> {
+        if on_conflict == Conflict::Error
+            && let Some(name) = other.names().find(|name| self.exists(name))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Entry '{}' already exists", name),
+            ));
+        }
+
+        let mut copied = 0;
+        for name in other.names() {
+            if on_conflict == Conflict::Skip && self.exists(name) {
+                continue;
+            }
+            self.copy_entry_from(other, name)?;
+            copied += 1;
+        }
+        Ok(copied)
+    }
+
+    /// Splits the archive into volumes of at most `max_bytes` each, returning their paths.
+    ///
+    /// Volumes are complete archives named after `dest_prefix` with a three digit number and the
+    /// `.bndl` extension, such as `backup.001.bndl`, so each one can be read on its own. Entries are
+    /// copied in name order without decompressing them and are never split, so an entry larger than
+    /// the cap gets a volume of its own that ends up larger than `max_bytes`. The zstd dictionary, if
+    /// any, is stored in every volume. Existing files at the volume paths are overwritten. An empty
+    /// archive gives one empty volume. Use [`join()`](Bindle::join) to put the volumes back together.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open("backup.bndl")?;
+    /// let volumes = archive.split("backup", 700 * 1024 * 1024)?;
+    /// let joined = Bindle::join(&volumes, "restored.bndl")?;
+    /// assert_eq!(joined.len(), archive.len());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn split<P: AsRef<Path>>(
+        &self,
+        dest_prefix: P,
+        max_bytes: u64,
+    ) -> io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        let mut volume: Option<(Bindle, u64)> = None;
+        for (name, entry) in &self.index {
+            let size = entry.compressed_size();
+            let needed =
+                size + pad::<BNDL_ALIGN, u64>(size) + record_len(name, self.meta.get(name));
+            if let Some((full, used)) = &mut volume
+                && *used + needed > max_bytes
+                && !full.is_empty()
+            {
+                full.save()?;
+                volume = None;
+            }
+            let (current, used) = match &mut volume {
+                Some(volume) => volume,
+                None => {
+                    let path = volume_path(dest_prefix.as_ref(), paths.len() + 1);
+                    let created = self.create_volume(&path)?;
+                    paths.push(path);
+                    volume.insert(created)
+                }
+            };
+            current.copy_entry_from(self, name)?;
+            *used += needed;
+        }
+
+        match volume {
+            Some((mut last, _)) => last.save()?,
+            None => {
+                let path = volume_path(dest_prefix.as_ref(), 1);
+                self.create_volume(&path)?.0.save()?;
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Creates an empty volume for [`split()`](Bindle::split) holding this archive's dictionary,
+    /// returning it with the number of bytes it will take up once saved.
+    fn create_volume(&self, path: &Path) -> io::Result<(Bindle, u64)> {
+        let mut volume = Bindle::create(path)?;
+        let mut records = 0;
+        if let Some(dict) = &self.dict {
+            volume.set_zstd_dictionary(dict.to_vec())?;
+            records = record_len(DICT_ENTRY, None);
+        }
+        let used = volume.data_end + records + (INDEX_CRC_SIZE + FOOTER_SIZE) as u64;
+        Ok((volume, used))
+    }
+
+    /// Joins volumes made by [`split()`](Bindle::split) into a new archive at `dest`.
+    ///
+    /// Entries are copied from each volume in turn without decompressing them, like
+    /// [`merge()`](Bindle::merge). An existing file at `dest` is overwritten, naming one of the
+    /// volumes as `dest` is rejected with [`io::ErrorKind::InvalidInput`], and an entry found in more
+    /// than one volume with [`io::ErrorKind::AlreadyExists`]. The joined archive is saved before it's
+    /// returned.
+    pub fn join<V: AsRef<Path>, P: AsRef<Path>>(volumes: &[V], dest: P) -> io::Result<Self> {
+        let dest = dest.as_ref();
+        if volumes.iter().any(|volume| volume.as_ref() == dest) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The joined archive can't replace one of its volumes",
+            ));
+        }
+        let mut joined = Bindle::create(dest)?;
+        for path in volumes {
+            let volume = Bindle::open_readonly(path)?;
+            if let Some(dict) = &volume.dict {
+                joined.set_zstd_dictionary(dict.to_vec())?;
+            }
+            joined.merge(&volume, Conflict::Error)?;
+        }
+        joined.save()?;
+        Ok(joined)
+    }
+
+    /// Extracts all entries to a destination directory.
+    ///
+    /// Creates subdirectories as needed to match the stored paths. Names that would end up outside
+    /// `dest`, such as `../file`, `/etc/file` or `..\\file`, that contain `.` or empty components,
+    /// or that lead through a symlink entry, are rejected with an [`io::ErrorKind::InvalidData`]
+    /// error before anything is written. Symlinked directories already in `dest` aren't followed.
+    pub fn unpack<P: AsRef<Path>>(&self, dest: P) -> io::Result<()> {
+        self.unpack_matching(dest, |_| true)
     }
 
-    /// Removes an entry from the index.
+    /// Extracts only the entries whose names match a glob, such as `assets/**`.
     ///
-    /// Returns true if the entry existed. Data remains in the file until [`vacuum()`](Bindle::vacuum) is called.
-    pub fn remove(&mut self, name: &str) -> bool {
-        self.index.remove(name).is_some()
+    /// Non-matching entries are skipped and no directories are created for them.
+    pub fn unpack_filtered<P: AsRef<Path>>(&self, dest: P, pattern: &str) -> io::Result<()> {
+        let glob = globset::Glob::new(pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+            .compile_matcher();
+        self.unpack_matching(dest, |name| glob.is_match(name))
     }
 
-    /// Recursively adds all files from a directory to the archive.
+    /// Extracts only the entries whose names start with `prefix`, such as `assets/`.
     ///
-    /// File paths are stored relative to the source directory. Call [`save()`](Bindle::save) to commit.
-    pub fn pack<P: AsRef<Path>>(&mut self, src_dir: P, compress: Compress) -> io::Result<()> {
-        self.pack_recursive(src_dir.as_ref(), src_dir.as_ref(), compress)
+    /// Non-matching entries are skipped and no directories are created for them.
+    pub fn unpack_prefix<P: AsRef<Path>>(&self, dest: P, prefix: &str) -> io::Result<()> {
+        self.unpack_matching(dest, |name| name.starts_with(prefix))
     }
 
-    fn pack_recursive(
-        &mut self,
-        base: &Path,
-        current: &Path,
-        compress: Compress,
+    /// Extracts the entries for which `filter` returns true.
+    pub fn unpack_matching<P: AsRef<Path>>(
+        &self,
+        dest: P,
+        filter: impl Fn(&str) -> bool,
     ) -> io::Result<()> {
-        if current.is_dir() {
-            for entry in std::fs::read_dir(current)? {
-                self.pack_recursive(base, &entry?.path(), compress)?;
-            }
-        } else {
-            let name = current
-                .strip_prefix(base)
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
-                .to_str()
-                .unwrap_or_default();
-            self.add_file(&name, current, compress)?;
-        }
-        Ok(())
+        self.unpack_matching_with_progress(dest, filter, |_| {})
     }
 
-    /// Extracts all entries to a destination directory.
+    /// Extracts all entries like [`unpack()`](Bindle::unpack), reporting progress.
     ///
-    /// Creates subdirectories as needed to match the stored paths.
-    pub fn unpack<P: AsRef<Path>>(&self, dest: P) -> io::Result<()> {
+    /// `progress` is called right before and right after each entry is written, see [`PackEvent`].
+    /// Entries are extracted in the order they are stored, not by name. Symlink entries that the
+    /// platform can't create as links are written as regular files holding the target, reported
+    /// with [`PackEvent::link_as_file`].
+    pub fn unpack_with_progress<P: AsRef<Path>>(
+        &self,
+        dest: P,
+        progress: impl FnMut(PackEvent),
+    ) -> io::Result<()> {
+        self.unpack_matching_with_progress(dest, |_| true, progress)
+    }
+
+    /// Extracts entries like [`unpack_matching()`](Bindle::unpack_matching), reporting progress like
+    /// [`unpack_with_progress()`](Bindle::unpack_with_progress).
+    pub fn unpack_matching_with_progress<P: AsRef<Path>>(
+        &self,
+        dest: P,
+        filter: impl Fn(&str) -> bool,
+        mut progress: impl FnMut(PackEvent),
+    ) -> io::Result<()> {
         let dest_path = dest.as_ref();
         std::fs::create_dir_all(dest_path)?;
 
+        let mut names: Vec<&String> = self.index.keys().filter(|name| filter(name)).collect();
+        check_unpack_names(&names, |name| {
+            self.index.get(name).is_some_and(Entry::is_symlink)
+        })?;
+        let total_size = names
+            .iter()
+            .try_fold(0u64, |total, name| {
+                total.checked_add(self.index[*name].uncompressed_size())
+            })
+            .unwrap_or(u64::MAX);
+        if total_size > self.limits.max_total_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unpacking {} bytes is over the limit of {} bytes",
+                    total_size, self.limits.max_total_size
+                ),
+            ));
+        }
+
         // Collect all unique parent directories
-        let mut dirs = std::collections::HashSet::new();
-        for (name, _) in &self.index {
+        let mut dirs = HashSet::new();
+        for name in &names {
             if let Some(parent) = Path::new(name).parent() {
                 // Only add non-empty parent paths
                 if parent != Path::new("") {
-                    dirs.insert(dest_path.join(parent));
+                    dirs.insert(parent);
                 }
             }
         }
 
         // Create all directories upfront (sorted for parent-first order)
-        if !dirs.is_empty() {
-            let mut dirs: Vec<_> = dirs.into_iter().collect();
-            dirs.sort();
-            for dir in dirs {
-                std::fs::create_dir_all(&dir)?;
-            }
+        let mut dirs: Vec<_> = dirs.into_iter().collect();
+        dirs.sort();
+        for dir in dirs {
+            create_dirs_below(dest_path, dir)?;
         }
 
         // Sort entries by physical offset for sequential reads (better cache locality)
-        let mut entries: Vec<_> = self.index.iter().collect();
-        entries.sort_by_key(|(_, entry)| entry.offset());
+        names.sort_by_key(|name| self.index[*name].offset());
 
         // Extract files without per-file directory checks
-        for (name, _) in entries {
+        let total = names.len();
+        let mut bytes = 0;
+        for (done, name) in names.into_iter().enumerate() {
+            let mut event = PackEvent {
+                name,
+                bytes,
+                done,
+                total,
+                finished: false,
+                link_as_file: false,
+            };
+            progress(event);
             let file_path = dest_path.join(name);
-            let mut reader = self.reader(name)?;
-            let mut file = File::create(&file_path)?;
-            io::copy(&mut reader, &mut file)?;
-            reader.verify_crc32()?;
+            let linked = self.write_entry_file(name, &file_path)?;
+            bytes += self.index[name].uncompressed_size();
+            event.bytes = bytes;
+            event.done = done + 1;
+            event.finished = true;
+            event.link_as_file = !linked;
+            progress(event);
         }
         Ok(())
     }
 
+    /// Extracts a single entry below `dest`, keeping its path, and returns the path written.
+    ///
+    /// Works like [`unpack()`](Bindle::unpack) for one entry: parent directories are created as
+    /// needed, symlink entries become links, the modification time is restored and names that
+    /// would end up outside `dest` are rejected. Where links can't be created the target is written
+    /// as a regular file, [`Path::is_symlink()`] tells the two apart. Returns [`io::ErrorKind::NotFound`] if there is no
+    /// such entry.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use bindle_file::Bindle;
+    ///
+    /// let archive = Bindle::open_readonly("site.bndl")?;
+    /// let path = archive.extract("assets/css/site.css", "out")?;
+    /// assert_eq!(path, std::path::Path::new("out/assets/css/site.css"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn extract<P: AsRef<Path>>(&self, name: &str, dest: P) -> io::Result<PathBuf> {
+        let (name, _) = self
+            .index
+            .get_key_value(self.stored_name(name))
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        check_unpack_names(&[name], |name| {
+            self.index.get(name).is_some_and(Entry::is_symlink)
+        })?;
+        let dest = dest.as_ref();
+        std::fs::create_dir_all(dest)?;
+        if let Some(parent) = Path::new(name).parent() {
+            create_dirs_below(dest, parent)?;
+        }
+        let file_path = dest.join(name);
+        self.write_entry_file(name, &file_path)?;
+        Ok(file_path)
+    }
+
+    /// Writes the entry `name` to `file_path`, whose directory must exist.
+    ///
+    /// A symlink already at `file_path` is replaced rather than written through. A file left by data
+    /// that fails to decode or verify is removed again, so a corrupt entry doesn't leave a truncated
+    /// file behind. Returns false for a symlink entry written as a regular file, see
+    /// [`create_symlink()`].
+    fn write_entry_file(&self, name: &str, file_path: &Path) -> io::Result<bool> {
+        if self.index[name].is_symlink() {
+            return create_symlink(&self.read(name)?, file_path);
+        }
+        if file_path
+            .symlink_metadata()
+            .is_ok_and(|meta| meta.is_symlink())
+        {
+            std::fs::remove_file(file_path)?;
+        }
+        let mut reader = self.reader(name)?;
+        let mut file = File::create(file_path)?;
+        let written = io::copy(&mut reader, &mut file).and_then(|_| reader.verify_crc32());
+        if let Err(e) = written {
+            drop(file);
+            let _ = std::fs::remove_file(file_path);
+            return Err(e);
+        }
+        if let Some(t) = self.mtime(name) {
+            file.set_modified(t)?;
+        }
+        Ok(true)
+    }
+
     /// Creates a streaming writer for adding an entry.
     ///
     /// The writer must be closed and then [`save()`](Bindle::save) must be called to commit the entry.
+    /// Names longer than [`MAX_NAME_LEN`](crate::MAX_NAME_LEN) bytes and the reserved dictionary name
+    /// are rejected with an [`io::ErrorKind::InvalidInput`] error before anything is written.
     pub fn writer<'a>(&'a mut self, name: &str, compress: Compress) -> io::Result<Writer<'a>> {
-        self.file.lock()?;
-        // Only seek if not already at the correct position
-        let current_pos = self.file.stream_position()?;
-        if current_pos != self.data_end {
-            self.file.seek(SeekFrom::Start(self.data_end))?;
-        }
-        let compress = self.should_auto_compress(compress, 0);
-        let start_offset = self.data_end;
-        let encoder = if compress {
-            let f = self.file.try_clone()?;
-            Some(zstd::Encoder::new(f, 3)?)
-        } else {
-            None
+        self.writer_with_level(name, compress, DEFAULT_ZSTD_LEVEL)
+    }
+
+    /// Creates a streaming writer that compresses with the given zstd level.
+    ///
+    /// `level` must be in the range `1..=22`, higher levels trade speed for a smaller archive.
+    /// [`writer()`](Bindle::writer) uses [`DEFAULT_ZSTD_LEVEL`](crate::DEFAULT_ZSTD_LEVEL). The level
+    /// is ignored unless the entry is zstd compressed.
+    pub fn writer_with_level<'a>(
+        &'a mut self,
+        name: &str,
+        compress: Compress,
+        level: i32,
+    ) -> io::Result<Writer<'a>> {
+        check_level(level)?;
+        self.check_writable()?;
+        let start_offset = self.append_offset()?;
+        self.writer_at(name, compress, level, start_offset)
+    }
+
+    /// Creates a streaming writer for a zstd entry with the given [`ZstdParams`].
+    ///
+    /// Use this for very large entries with long-range redundancy, where a larger window or
+    /// long-distance matching finds repeats the default settings can't see. Returns an
+    /// [`io::ErrorKind::InvalidInput`] error if the level or window log is out of range.
+    pub fn writer_with_params<'a>(
+        &'a mut self,
+        name: &str,
+        params: ZstdParams,
+    ) -> io::Result<Writer<'a>> {
+        if let Some(window_log) = params.window_log
+            && !ZSTD_WINDOW_LOGS.contains(&window_log)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid zstd window log {}, expected {}..={}",
+                    window_log,
+                    ZSTD_WINDOW_LOGS.start(),
+                    ZSTD_WINDOW_LOGS.end()
+                ),
+            ));
+        }
+
+        let mut writer = self.writer_with_level(name, Compress::Zstd, params.level)?;
+        if let Some(Encoder::Zstd(encoder)) = &mut writer.encoder {
+            let configured = encoder
+                .long_distance_matching(params.long_mode)
+                .and_then(|_| match params.window_log {
+                    Some(window_log) => encoder.window_log(window_log),
+                    None => Ok(()),
+                });
+            if let Err(e) = configured {
+                writer.aborted = true;
+                return Err(e);
+            }
+        }
+        writer.long_window = params
+            .window_log
+            .is_some_and(|window_log| window_log > ZSTD_DEFAULT_WINDOW_LOG_MAX);
+        Ok(writer)
+    }
+
+    /// Creates a writer for an entry starting at `start_offset`, which must be the current write
+    /// position past the committed footer.
+    fn writer_at<'a>(
+        &'a mut self,
+        name: &str,
+        compress: Compress,
+        level: i32,
+        start_offset: u64,
+    ) -> io::Result<Writer<'a>> {
+        check_name(name)?;
+        // Auto waits for a sample of the data before picking the encoder
+        let (encoder, pending) = match compress {
+            Compress::Auto => (None, Some(Vec::new())),
+            _ => (
+                Encoder::new(
+                    &self.storage,
+                    self.cipher.is_some(),
+                    compress,
+                    level,
+                    self.dict.as_deref(),
+                )?,
+                None,
+            ),
         };
+        let hash_sha256 = self.hashes_sha256();
+        let sealed = self.cipher.is_some().then(Vec::new);
         Ok(Writer {
             name: name.to_string(),
             bindle: self,
             encoder,
+            pending,
+            level,
             start_offset,
             uncompressed_size: 0,
             crc32_hasher: Hasher::new(),
+            sha256_hasher: hash_sha256.then(Sha256::new),
+            frames: None,
+            sealed,
+            long_window: false,
+            aborted: false,
         })
     }
+
+    /// Creates a streaming writer that extends an existing entry.
+    ///
+    /// The writer starts with the entry's current contents, so data written to it is appended. The
+    /// contents are copied to the end of the data region, compressed entries are decompressed and
+    /// recompressed with the same compression. On close the new entry shadows the old one, whose
+    /// bytes become reclaimable by [`vacuum()`](Bindle::vacuum). Like any other shadowing write, the
+    /// new entry doesn't keep the old [`meta()`](Bindle::meta) or [`mtime()`](Bindle::mtime). Returns
+    /// [`io::ErrorKind::NotFound`] if there is no such entry and [`io::ErrorKind::Unsupported`] for
+    /// seekable and symlink entries.
+    pub fn append_writer<'a>(&'a mut self, name: &str) -> io::Result<Writer<'a>> {
+        let name = &self.stored_name(name).to_string();
+        let entry = *self
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        if entry.is_seekable() || entry.is_symlink() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Can't append to seekable or symlink entries",
+            ));
+        }
+
+        let existing = self.read(name)?.into_owned();
+        let mut writer = self.writer(name, entry.compression_type())?;
+        writer.write_chunk(&existing)?;
+        Ok(writer)
+    }
+
+    /// Creates a streaming writer for a zstd entry that supports seeking.
+    ///
+    /// The data is split into independent zstd frames of `frame_size` uncompressed bytes, followed by a
+    /// seek table, so [`Reader`] can jump to the nearest frame instead of decoding from the start.
+    /// Smaller frames make seeking cheaper at the cost of compression ratio. `frame_size` may be at most 1 GiB,
+    /// [`DEFAULT_FRAME_SIZE`](crate::DEFAULT_FRAME_SIZE) is a good starting point. Regular writers stay a
+    /// single frame, so small entries don't pay for the seek table.
+    pub fn seekable_writer<'a>(
+        &'a mut self,
+        name: &str,
+        frame_size: usize,
+    ) -> io::Result<Writer<'a>> {
+        if frame_size == 0 || frame_size > MAX_FRAME_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid frame size {}", frame_size),
+            ));
+        }
+
+        let mut writer = self.writer_with_level(name, Compress::Zstd, DEFAULT_ZSTD_LEVEL)?;
+        // Each frame starts its own encoder on the first write, without the zstd dictionary
+        writer.encoder = None;
+        writer.frames = Some(Frames {
+            size: frame_size as u64,
+            level: DEFAULT_ZSTD_LEVEL,
+            current_len: 0,
+            current_start: writer.start_offset,
+            table: Vec::new(),
+        });
+        Ok(writer)
+    }
+}
+
+/// Decodes `stored`, the stored bytes of `entry`, and verifies its CRC32.
+///
+/// Uncompressed entries in memory are borrowed. `dict` is the archive's zstd dictionary, if any.
+pub(crate) fn decode_entry<'a>(
+    stored: Region<'a>,
+    name: &str,
+    entry: &Entry,
+    dict: Option<&[u8]>,
+) -> Result<Cow<'a, [u8]>, BindleError> {
+    if entry.is_encrypted() {
+        return Err(crypto::missing_key().into());
+    }
+    let data = match entry.compression_type() {
+        Compress::None | Compress::Auto => stored.read_all()?,
+        _ => {
+            // The recorded size may be forged, don't reserve more than the data could expand to
+            let capacity = entry.uncompressed_size().min(MAX_PREALLOC) as usize;
+            let mut out = Vec::with_capacity(capacity);
+            Reader::from_region(stored, name, entry, dict)?.read_to_end(&mut out)?;
+            Cow::Owned(out)
+        }
+    };
+
+    // Verify CRC32
+    let computed_crc = crc32fast::hash(&data);
+    if computed_crc != entry.crc32() {
+        return Err(BindleError::CrcMismatch {
+            name: name.to_string(),
+            expected: entry.crc32(),
+            got: computed_crc,
+        });
+    }
+
+    Ok(data)
+}
+
+/// Returns a streaming reader for `stored`, the stored bytes of `entry`.
+pub(crate) fn entry_reader<'a>(
+    stored: Region<'a>,
+    name: &str,
+    entry: &Entry,
+    dict: Option<&[u8]>,
+) -> io::Result<Reader<'a>> {
+    if entry.is_encrypted() {
+        return Err(crypto::missing_key());
+    }
+    Reader::from_region(stored, name, entry, dict)
+}
+
+/// Returns the stored bytes of `entry` in `bytes`, the archive bytes.
+///
+/// Empty entries read as an empty slice even if their offset is past the end of `bytes`, which
+/// happens when one is written last and not yet saved.
+pub(crate) fn stored_bytes<'a>(bytes: &'a [u8], entry: &Entry) -> io::Result<&'a [u8]> {
+    if entry.compressed_size() == 0 {
+        return Ok(&[]);
+    }
+    let start = entry.offset();
+    start
+        .checked_add(entry.compressed_size())
+        .and_then(|end| bytes.get(start as usize..end as usize))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Entry data out of bounds"))
+}
+
+/// Returns the zstd dictionary read from `stored`, the stored bytes of its index record `entry`.
+///
+/// Returns `None` if the dictionary is encrypted and no cipher is given, and an error if it's
+/// corrupt or can't be decrypted.
+pub(crate) fn load_dictionary(
+    stored: Region<'_>,
+    entry: &Entry,
+    cipher: Option<&Aes256Gcm>,
+) -> io::Result<Option<Arc<[u8]>>> {
+    let stored = stored.read_all()?;
+    if crc32fast::hash(&stored) != entry.crc32() {
+        return Err(BindleError::CrcMismatch {
+            name: DICT_ENTRY.to_string(),
+            expected: entry.crc32(),
+            got: crc32fast::hash(&stored),
+        }
+        .into());
+    }
+    match (entry.is_encrypted(), cipher) {
+        (false, _) => Ok(Some(Arc::from(stored))),
+        (true, Some(cipher)) => Ok(Some(Arc::from(crypto::decrypt(
+            cipher, DICT_ENTRY, &stored,
+        )?))),
+        (true, None) => Ok(None),
+    }
+}
+
+/// Finds zstd and gzip streams at 8-byte aligned offsets in `bytes[start..]` that decode cleanly.
+///
+/// Used by [`Bindle::recover()`] to rebuild entries that were written but never saved. Consecutive
+/// zstd frames, such as the frames and seek table of a seekable entry, are read as one stream.
+fn scan_streams(bytes: &[u8], start: usize) -> Vec<Entry> {
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+    const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
+    let is_zstd_frame = |b: &[u8]| {
+        // Regular frames, or skippable frames like the seek table
+        b.starts_with(&ZSTD_MAGIC)
+            || (b.len() >= 4 && b[0] & 0xf0 == 0x50 && b[1..4] == [0x2a, 0x4d, 0x18])
+    };
+
+    let mut found = Vec::new();
+    let mut offset = start.next_multiple_of(BNDL_ALIGN);
+    while offset + 4 <= bytes.len() {
+        let rest = &bytes[offset..];
+        let stream = if rest.starts_with(&ZSTD_MAGIC) {
+            let mut len = 0;
+            while is_zstd_frame(&rest[len..]) {
+                match zstd::zstd_safe::find_frame_compressed_size(&rest[len..]) {
+                    Ok(n) => len += n,
+                    Err(_) => break,
+                }
+            }
+            // Try the default window limit first, a larger window means a long window entry
+            let decode = |window_log_max| -> io::Result<Vec<u8>> {
+                let mut decoder = zstd::Decoder::new(&rest[..len])?;
+                decoder.window_log_max(window_log_max)?;
+                let mut data = Vec::new();
+                decoder.read_to_end(&mut data)?;
+                Ok(data)
+            };
+            match decode(ZSTD_DEFAULT_WINDOW_LOG_MAX) {
+                Ok(data) => Some((Compress::Zstd, len, data, 0)),
+                // Only retry frames whose header asks for a window long window entries may use
+                Err(_) => zstd_window_log(rest)
+                    .filter(|log| {
+                        *log > ZSTD_DEFAULT_WINDOW_LOG_MAX && *log <= *ZSTD_WINDOW_LOGS.end()
+                    })
+                    .and_then(|log| decode(log).ok())
+                    .map(|data| (Compress::Zstd, len, data, ENTRY_FLAG_LONG_WINDOW)),
+            }
+            .filter(|_| len > 0)
+        } else if rest.starts_with(&GZIP_MAGIC) {
+            let mut decoder = flate2::bufread::GzDecoder::new(rest);
+            let mut data = Vec::new();
+            decoder.read_to_end(&mut data).ok().map(|_| {
+                (
+                    Compress::Gzip,
+                    rest.len() - decoder.into_inner().len(),
+                    data,
+                    0,
+                )
+            })
+        } else {
+            None
+        };
+
+        match stream {
+            Some((compress, len, data, flags)) => {
+                let mut entry = Entry::default();
+                entry.set_offset(offset as u64);
+                entry.set_compressed_size(len as u64);
+                entry.set_uncompressed_size(data.len() as u64);
+                entry.set_crc32(crc32fast::hash(&data));
+                entry.compression_type = compress as u8;
+                entry.set_flags(flags);
+                found.push(entry);
+                offset = (offset + len).next_multiple_of(BNDL_ALIGN);
+            }
+            None => offset += BNDL_ALIGN,
+        }
+    }
+    found
+}
+
+/// Path of a temporary file next to the archive, deleted when dropped unless
+/// [`keep()`](TempPath::keep) is called once it has been renamed into place.
+struct TempPath {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempPath {
+    /// Creates or truncates the file at `path`, returning the guard and the open file.
+    fn create(path: PathBuf) -> io::Result<(Self, File)> {
+        let file = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        Ok((TempPath { path, keep: false }, file))
+    }
+
+    /// Stops the file from being deleted on drop.
+    fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// The header, last complete commit and index of an archive.
+pub(crate) struct Parsed {
+    pub version: u8,
+    /// End of the last complete commit's footer.
+    pub committed_len: u64,
+    pub data_end: u64,
+    pub index: BTreeMap<String, Entry>,
+    pub meta: BTreeMap<String, EntryMeta>,
+    /// Record of the zstd dictionary, kept out of `index`.
+    pub dictionary: Option<Entry>,
+}
+
+/// Parses the header, footer and index of the archive in `bytes`.
+pub(crate) fn parse_archive(bytes: Region<'_>) -> Result<Parsed, BindleError> {
+    // Must be at least HEADER + FOOTER size (24 bytes) so the footer position can't underflow
+    if bytes.len() < (HEADER_SIZE + FOOTER_SIZE) as u64 {
+        return Err(BindleError::TooSmall);
+    }
+
+    let header = bytes.read(0..HEADER_SIZE as u64)?;
+    let header = header[..].try_into().map_err(|_| BindleError::TooSmall)?;
+    let version = header_version(header)?;
+    let (committed_len, footer, index_bytes) = find_footer(bytes, version)?;
+    let (mut index, meta) = parse_index(&index_bytes, footer.entry_count(), version)?;
+    check_bounds(&index, footer.index_offset())?;
+    let dictionary = index.remove(DICT_ENTRY);
+    Ok(Parsed {
+        version,
+        committed_len,
+        data_end: footer.index_offset(),
+        index,
+        meta,
+        dictionary,
+    })
+}
+
+/// Checks that the data of every entry lies between the header and `data_end`, the index offset.
+///
+/// Rejecting bad records up front means reads never index past the data region, and a forged size
+/// can't make readers allocate more than the file holds.
+pub(crate) fn check_bounds(
+    index: &BTreeMap<String, Entry>,
+    data_end: u64,
+) -> Result<(), BindleError> {
+    for (name, entry) in index {
+        let in_bounds = entry.offset() >= HEADER_SIZE as u64
+            && entry
+                .offset()
+                .checked_add(entry.compressed_size())
+                .is_some_and(|end| end <= data_end);
+        if !in_bounds {
+            return Err(BindleError::EntryOutOfBounds {
+                name: name.clone(),
+                offset: entry.offset(),
+                size: entry.compressed_size(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Entry records and metadata blocks read from an index, keyed by name.
+type IndexRecords = (BTreeMap<String, Entry>, BTreeMap<String, EntryMeta>);
+
+/// Parses `count` index records from the bytes between the index offset and the footer.
+///
+/// Version 2 records carry a metadata block after the name. Parsing stops early at the first
+/// truncated or corrupt record. Names must be valid UTF-8, they are never mangled into a different
+/// name that could collide with another entry.
+pub(crate) fn parse_index(
+    bytes: &[u8],
+    count: u32,
+    version: u8,
+) -> Result<IndexRecords, BindleError> {
+    let mut index = BTreeMap::new();
+    let mut meta = BTreeMap::new();
+
+    let mut cursor = 0;
+    for _ in 0..count {
+        // Ensure there is enough data left for an Entry header
+        if cursor + ENTRY_SIZE > bytes.len() {
+            break;
+        }
+
+        let entry = match Entry::read_from_bytes(&bytes[cursor..cursor + ENTRY_SIZE]) {
+            Ok(e) => e,
+            Err(_) => break, // Corrupted entry, stop reading
+        };
+        let n_start = cursor + ENTRY_SIZE;
+        let n_end = n_start + entry.name_len();
+
+        // Validate that the filename exists within the index bounds
+        if n_end > bytes.len() {
+            break;
+        }
+
+        let name = std::str::from_utf8(&bytes[n_start..n_end])
+            .map_err(|_| {
+                BindleError::InvalidName(String::from_utf8_lossy(&bytes[n_start..n_end]).into())
+            })?
+            .to_string();
+
+        let mut total = ENTRY_SIZE + entry.name_len();
+        if version >= 2 {
+            let Some(len) = bytes.get(n_end..n_end + 2) else {
+                break;
+            };
+            let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+            let Some(block) = bytes.get(n_end + 2..n_end + 2 + len) else {
+                break;
+            };
+            if len > 0 {
+                meta.insert(name.clone(), EntryMeta::decode(block));
+            }
+            total += 2 + len;
+        }
+
+        index.insert(name, entry);
+        cursor += (total + (BNDL_ALIGN - 1)) & !(BNDL_ALIGN - 1);
+    }
+
+    Ok((index, meta))
+}
+
+/// Strips and validates the index checksum from the bytes between the index offset and the footer.
+///
+/// Archives older than version 3 have no checksum and are returned unchanged.
+pub(crate) fn check_index(bytes: &[u8], version: u8) -> Result<&[u8], BindleError> {
+    if version < 3 {
+        return Ok(bytes);
+    }
+
+    let split = bytes
+        .len()
+        .checked_sub(INDEX_CRC_SIZE)
+        .ok_or(BindleError::InvalidFooter)?;
+    let (records, trailer) = bytes.split_at(split);
+    let expected = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    if crc32fast::hash(records) != expected {
+        return Err(BindleError::IndexCrcMismatch);
+    }
+    Ok(records)
+}
+
+/// Finds the last complete commit in `bytes`, returning where its footer ends, the footer and the
+/// index records.
+///
+/// A save interrupted before its footer was written leaves a partial index after the previous
+/// footer. Version 3 and later archives fall back to the last footer whose index passes its checksum, older
+/// versions have no checksum to tell a complete index from a partial one.
+pub(crate) fn find_footer<'a>(
+    bytes: Region<'a>,
+    version: u8,
+) -> Result<(u64, Footer, Cow<'a, [u8]>), BindleError> {
+    const SCAN_CHUNK: u64 = 1 << 20;
+    let err = match read_footer(bytes, bytes.len(), version) {
+        Err(err) if version >= 3 => err,
+        result => return result,
+    };
+
+    // Scan back for the footer of an earlier commit, a footer ends with its magic number so only
+    // those positions are tried. Files are read a chunk at a time.
+    let magic = FOOTER_MAGIC.to_le_bytes();
+    let first = (HEADER_SIZE + INDEX_CRC_SIZE + FOOTER_SIZE) as u64;
+    let mut hi = bytes.len();
+    while hi > first {
+        let lo = hi.saturating_sub(SCAN_CHUNK).max(first);
+        let chunk = bytes.read(lo - magic.len() as u64..hi)?;
+        for end in (lo..hi).rev() {
+            let at = (end - lo) as usize;
+            if chunk[at..at + magic.len()] == magic
+                && let Ok(found) = read_footer(bytes, end, version)
+            {
+                return Ok(found);
+            }
+        }
+        hi = lo;
+    }
+    Err(err)
+}
+
+/// Reads the footer ending at `end` and the index it points to.
+fn read_footer<'a>(
+    bytes: Region<'a>,
+    end: u64,
+    version: u8,
+) -> Result<(u64, Footer, Cow<'a, [u8]>), BindleError> {
+    let footer_pos = end - FOOTER_SIZE as u64;
+    let footer = Footer::read_from_bytes(&bytes.read(footer_pos..end)?)
+        .map_err(|_| BindleError::InvalidFooter)?;
+    if footer.magic() != FOOTER_MAGIC {
+        return Err(BindleError::InvalidFooter);
+    }
+
+    let index_offset = footer.index_offset();
+    if index_offset < HEADER_SIZE as u64 || !index_offset.is_multiple_of(BNDL_ALIGN as u64) {
+        return Err(BindleError::InvalidFooter);
+    }
+    let index_bytes = bytes
+        .slice(index_offset..footer_pos)
+        .ok_or(BindleError::InvalidFooter)?
+        .read_all()?;
+    let records = check_index(&index_bytes, version)?.len();
+    let records = match index_bytes {
+        Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[..records]),
+        Cow::Owned(mut bytes) => {
+            bytes.truncate(records);
+            Cow::Owned(bytes)
+        }
+    };
+    Ok((end, footer, records))
+}
+
+/// Writes index records in the current layout, followed by the index checksum.
+///
+/// The zstd dictionary record, if any, is written first under its reserved name.
+pub(crate) fn write_index<W: Write>(
+    w: &mut W,
+    index: &BTreeMap<String, Entry>,
+    meta: &BTreeMap<String, EntryMeta>,
+    dictionary: Option<&Entry>,
+) -> io::Result<()> {
+    let mut hasher = Hasher::new();
+    let mut write = |bytes: &[u8]| -> io::Result<()> {
+        hasher.update(bytes);
+        w.write_all(bytes)
+    };
+
+    let records = dictionary
+        .map(|entry| (DICT_ENTRY, entry))
+        .into_iter()
+        .chain(index.iter().map(|(name, entry)| (name.as_str(), entry)));
+    for (name, entry) in records {
+        let block = meta.get(name).map(EntryMeta::encode).unwrap_or_default();
+        write(entry.as_bytes())?;
+        write(name.as_bytes())?;
+        write(&(block.len() as u16).to_le_bytes())?;
+        write(&block)?;
+        let pad = pad::<BNDL_ALIGN, usize>(ENTRY_SIZE + name.len() + 2 + block.len());
+        write(&[0u8; BNDL_ALIGN][..pad])?;
+    }
+
+    w.write_all(&hasher.finalize().to_le_bytes())?;
+    w.write_all(&[0u8; INDEX_CRC_SIZE - 4])
+}
+
+/// Returns the size of the index record [`write_index()`] writes for `name`, padding included.
+fn record_len(name: &str, meta: Option<&EntryMeta>) -> u64 {
+    let len = ENTRY_SIZE + name.len() + 2 + meta.map_or(0, |meta| meta.encode().len());
+    (len + pad::<BNDL_ALIGN, usize>(len)) as u64
+}
+
+/// Returns the path of volume `number` for [`Bindle::split()`], such as `backup.001.bndl`.
+fn volume_path(prefix: &Path, number: usize) -> PathBuf {
+    let mut path = prefix.as_os_str().to_owned();
+    path.push(format!(".{:03}.bndl", number));
+    PathBuf::from(path)
+}
+
+/// Writes the footer pointing at an index of `entry_count` records starting at `index_start`.
+pub(crate) fn write_footer<W: Write>(
+    w: &mut W,
+    index_start: u64,
+    entry_count: u32,
+) -> io::Result<()> {
+    let footer = Footer::new(index_start, entry_count, FOOTER_MAGIC);
+    w.write_all(footer.as_bytes())
+}
+
+/// Stored bytes, compression, uncompressed size, CRC32, SHA-256 and modification time of a packed file.
+type EncodedFile = (
+    Vec<u8>,
+    Compress,
+    u64,
+    u32,
+    Option<[u8; 32]>,
+    Option<SystemTime>,
+);
+
+/// Entry name, path and whether it's a symbolic link to store as a link entry, of a file to pack.
+pub(crate) type PackFile = (String, PathBuf, bool);
+
+/// Lists the files under `src_dir` that packing with `options` would add, with their entry names,
+/// and the number of files and directories left out.
+///
+/// Each file is paired with `true` if it is a symbolic link to store as a link entry.
+pub(crate) fn pack_candidates(
+    src_dir: &Path,
+    options: &PackOptions,
+) -> io::Result<(Vec<PackFile>, usize)> {
+    let mut files = Vec::new();
+    let skipped = collect_files(src_dir, src_dir, options, &mut Vec::new(), &mut files)?;
+    Ok((files, skipped))
+}
+
+/// Recursively lists files under `current` with their entry names relative to `base`, returning
+/// the number of files and directories left out.
+///
+/// Each file is paired with `true` if it is a symbolic link to store as a link entry. `ancestors`
+/// holds the canonical paths of the directories being walked, so followed links can't loop.
+fn collect_files(
+    base: &Path,
+    current: &Path,
+    options: &PackOptions,
+    ancestors: &mut Vec<PathBuf>,
+    out: &mut Vec<PackFile>,
+) -> io::Result<usize> {
+    let name = current
+        .strip_prefix(base)
+        .map_err(io::Error::other)?
+        .to_str()
+        .unwrap_or_default();
+    if current != base && options.is_excluded(name) {
+        return Ok(1);
+    }
+
+    if current != base && current.symlink_metadata()?.is_symlink() {
+        match options.symlinks {
+            Symlinks::Follow => {}
+            Symlinks::Store => {
+                out.push((name.to_string(), current.to_path_buf(), true));
+                return Ok(0);
+            }
+            Symlinks::Skip => return Ok(1),
+        }
+    }
+
+    let mut skipped = 0;
+    if current.is_dir() {
+        let real = current.canonicalize()?;
+        if ancestors.contains(&real) {
+            return Ok(1);
+        }
+        ancestors.push(real);
+        for entry in std::fs::read_dir(current)? {
+            skipped += collect_files(base, &entry?.path(), options, ancestors, out)?;
+        }
+        ancestors.pop();
+    } else {
+        out.push((name.to_string(), current.to_path_buf(), false));
+    }
+    Ok(skipped)
+}
+
+/// Checks that every name in `names` stays inside the directory it's unpacked to.
+///
+/// Names are rejected if they are absolute, start with a drive letter, contain an empty, `.` or
+/// `..` component or lead through another entry that `is_symlink`, since writing through the link
+/// could land anywhere. Refusing `.` and empty components keeps every path to a link spelled the
+/// same way, so `./link/file` can't sneak past the check for `link`. Both `/` and `\\` count as
+/// separators so an archive is checked the same way on every platform. Checking everything before
+/// writing means a bad archive leaves nothing behind.
+fn check_unpack_names<'a>(
+    names: &[&'a String],
+    is_symlink: impl Fn(&'a str) -> bool,
+) -> Result<(), BindleError> {
+    for name in names {
+        let bytes = name.as_bytes();
+        let unsafe_name = (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+            || name
+                .split(['/', '\\'])
+                .any(|part| matches!(part, "" | "." | ".."))
+            || name
+                .match_indices(['/', '\\'])
+                .any(|(i, _)| is_symlink(&name[..i]));
+        if unsafe_name {
+            return Err(BindleError::UnsafeName(name.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Creates the directory `dir`, a path relative to `dest`, one component at a time.
+///
+/// Components that already exist must be real directories. A symlink, whether an earlier entry
+/// created it or it was already in `dest`, is refused with [`BindleError::UnsafeName`] instead of
+/// being followed, since the link could point anywhere.
+fn create_dirs_below(dest: &Path, dir: &Path) -> io::Result<()> {
+    let mut path = dest.to_path_buf();
+    for part in dir.components() {
+        path.push(part);
+        match path.symlink_metadata() {
+            Ok(meta) if meta.is_symlink() => {
+                return Err(BindleError::UnsafeName(dir.display().to_string()).into());
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => std::fs::create_dir(&path)?,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Copies `len` bytes at `from` to the lower offset `to` within `storage`, one `buf` at a time.
+///
+/// Copying front to back is safe when the ranges overlap: every chunk is read before the writes
+/// moving left can reach it.
+fn move_data(
+    storage: &mut Storage,
+    from: u64,
+    to: u64,
+    len: u64,
+    buf: &mut [u8],
+) -> io::Result<()> {
+    debug_assert!(to <= from);
+    let mut done = 0;
+    while done < len {
+        let n = (len - done).min(buf.len() as u64) as usize;
+        storage.seek(SeekFrom::Start(from + done))?;
+        storage.read_exact(&mut buf[..n])?;
+        storage.seek(SeekFrom::Start(to + done))?;
+        storage.write_all(&buf[..n])?;
+        done += n as u64;
+    }
+    Ok(())
+}
+
+/// Creates a symbolic link at `path` pointing to `target`, replacing any existing file.
+///
+/// Returns false if the platform couldn't create the link and the target was written to `path` as
+/// a regular file instead.
+#[cfg(unix)]
+fn create_symlink(target: &[u8], path: &Path) -> io::Result<bool> {
+    use std::os::unix::ffi::OsStrExt;
+    let _ = std::fs::remove_file(path);
+    std::os::unix::fs::symlink(std::ffi::OsStr::from_bytes(target), path)?;
+    Ok(true)
+}
+
+/// Creates a symbolic link at `path` pointing to `target`, replacing any existing file.
+///
+/// Windows distinguishes file and directory links, the kind is picked from what the target is when
+/// unpacking. Creating links needs developer mode or admin rights, without them the target is
+/// written as a regular file instead and false is returned. Other errors are returned as is.
+#[cfg(windows)]
+fn create_symlink(target: &[u8], path: &Path) -> io::Result<bool> {
+    let target_path = Path::new(
+        std::str::from_utf8(target).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+    );
+    let _ = std::fs::remove_file(path);
+    let is_dir = path
+        .parent()
+        .is_some_and(|parent| parent.join(target_path).is_dir());
+    let result = if is_dir {
+        std::os::windows::fs::symlink_dir(target_path, path)
+    } else {
+        std::os::windows::fs::symlink_file(target_path, path)
+    };
+    match result {
+        Ok(()) => Ok(true),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::PermissionDenied | io::ErrorKind::Unsupported
+            ) =>
+        {
+            std::fs::write(path, target)?;
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes the target of a link to `path` as a regular file and returns false, symbolic links
+/// aren't supported on this platform.
+#[cfg(not(any(unix, windows)))]
+fn create_symlink(target: &[u8], path: &Path) -> io::Result<bool> {
+    std::fs::write(path, target)?;
+    Ok(false)
 }
 
 impl Drop for Bindle {
     fn drop(&mut self) {
-        let _ = self.file.unlock();
+        if self.autosave && self.dirty {
+            let _ = self.save();
+        }
+        let _ = self.storage.unlock();
     }
 }
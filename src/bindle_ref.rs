@@ -0,0 +1,146 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+
+use crate::bindle::{Parsed, decode_entry, entry_reader, load_dictionary, parse_archive};
+use crate::entry::{Entry, EntryMeta};
+use crate::error::BindleError;
+use crate::limits::Limits;
+use crate::reader::Reader;
+use crate::storage::Region;
+
+/// A read-only archive borrowed from a byte slice, such as an archive received over the network.
+///
+/// Created by [`Bindle::from_bytes`](crate::Bindle::from_bytes) or [`TryFrom<&[u8]>`]. Nothing is
+/// copied, uncompressed entries are read straight from the slice.
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::Bindle;
+///
+/// let bytes = std::fs::read("data.bndl")?;
+/// let archive = Bindle::from_bytes(&bytes)?;
+/// let data = archive.read("file.txt")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct BindleRef<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) index: BTreeMap<String, Entry>,
+    pub(crate) meta: BTreeMap<String, EntryMeta>,
+    pub(crate) version: u8,
+    pub(crate) dict: Option<Arc<[u8]>>,
+    pub(crate) limits: Limits,
+}
+
+impl<'a> TryFrom<&'a [u8]> for BindleRef<'a> {
+    type Error = io::Error;
+
+    fn try_from(bytes: &'a [u8]) -> io::Result<Self> {
+        let Parsed {
+            version,
+            committed_len,
+            index,
+            meta,
+            dictionary,
+            ..
+        } = parse_archive(Region::Memory(bytes))?;
+        let dict = dictionary
+            .map(|entry| load_dictionary(Region::Memory(bytes).entry(&entry)?, &entry, None))
+            .transpose()?
+            .flatten();
+        Ok(Self {
+            bytes: &bytes[..committed_len as usize],
+            index,
+            meta,
+            version,
+            dict,
+            limits: Limits::default(),
+        })
+    }
+}
+
+impl<'a> BindleRef<'a> {
+    /// Reads an entry, decompressing if needed.
+    ///
+    /// Uncompressed entries are borrowed from the slice. Returns [`BindleError::NotFound`] if the
+    /// entry doesn't exist or [`BindleError::CrcMismatch`] if CRC32 verification fails.
+    pub fn read(&self, name: &str) -> Result<Cow<'a, [u8]>, BindleError> {
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        self.limits.check_entry(name, entry)?;
+        decode_entry(
+            Region::Memory(self.bytes).entry(entry)?,
+            name,
+            entry,
+            self.dict.as_deref(),
+        )
+    }
+
+    /// Returns a streaming reader for an entry.
+    ///
+    /// Call [`Reader::verify_crc32()`] after reading to verify integrity.
+    pub fn reader(&self, name: &str) -> io::Result<Reader<'a>> {
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+        self.limits.check_entry(name, entry)?;
+        entry_reader(
+            Region::Memory(self.bytes).entry(entry)?,
+            name,
+            entry,
+            self.dict.as_deref(),
+        )
+    }
+
+    /// Sets the size limits enforced when reading, see [`Limits`].
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the size limits enforced when reading.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Returns the format version of the archive.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the number of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the archive contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns true if an entry with the given name exists.
+    pub fn exists(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Iterates over entry names and their metadata in name order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.index
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Returns the metadata blob stored with an entry, if it has one.
+    pub fn meta(&self, name: &str) -> Option<&[u8]> {
+        self.meta.get(name)?.user.as_deref()
+    }
+
+    /// Returns a reference to the archive index.
+    pub fn index(&self) -> &BTreeMap<String, Entry> {
+        &self.index
+    }
+}
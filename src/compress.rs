@@ -1,3 +1,5 @@
+use crate::{AUTO_SAMPLE_RATIO, AUTO_SAMPLE_SIZE, DEFAULT_ZSTD_LEVEL};
+
 /// Compression mode for entries.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
@@ -6,20 +8,95 @@ pub enum Compress {
     None = 0,
     /// Zstandard compression.
     Zstd = 1,
-    /// Automatically compress if entry is larger than 2KB threshold.
+    /// Automatically compress entries larger than 2KB if a sample of the data compresses well.
+    /// The size cutoff can be changed with [`Bindle::set_auto_compress_threshold()`](crate::Bindle::set_auto_compress_threshold).
     /// Note: This is never stored on disk, only used as a policy hint.
     #[default]
     Auto = 2,
+    /// Gzip (deflate) compression.
+    Gzip = 3,
 }
 
 impl Compress {
+    /// Picks the compression for an entry whose data starts with `sample`.
+    ///
+    /// `Auto` compresses up to the first [`AUTO_SAMPLE_SIZE`] bytes with zstd and picks zstd only if
+    /// that shrinks them enough, so already compressed data such as images is stored as is. Data no
+    /// larger than `threshold` bytes is never compressed. `len` is the full entry size, or
+    /// `usize::MAX` when it is known to be larger than the sample and the threshold.
+    pub(crate) fn resolve(self, sample: &[u8], len: usize, threshold: usize) -> Compress {
+        if self != Compress::Auto {
+            return self;
+        }
+        if len <= threshold {
+            return Compress::None;
+        }
+
+        let sample = &sample[..sample.len().min(AUTO_SAMPLE_SIZE)];
+        match zstd::bulk::compress(sample, 1) {
+            Ok(compressed)
+                if (compressed.len() as f64) < sample.len() as f64 * AUTO_SAMPLE_RATIO =>
+            {
+                Compress::Zstd
+            }
+            _ => Compress::None,
+        }
+    }
+
     pub(crate) fn from_u8(value: u8) -> Self {
         match value {
             0 => Compress::None,
             1 => Compress::Zstd,
+            3 => Compress::Gzip,
             // Invalid/unknown values default to None (safest option)
             // Auto is never stored on disk, only used as input policy
             _ => Compress::None,
         }
     }
 }
+
+/// Zstd settings for [`Bindle::writer_with_params()`](crate::Bindle::writer_with_params).
+///
+/// Long-distance matching and a larger window find repeats that are far apart, such as identical
+/// blocks in a disk image, at the cost of memory while compressing and decompressing. Long mode on
+/// its own uses a 128 MiB window, which every zstd decoder accepts.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Write;
+/// use bindle_file::{Bindle, ZstdParams};
+///
+/// let mut archive = Bindle::in_memory();
+/// let params = ZstdParams {
+///     long_mode: true,
+///     ..ZstdParams::default()
+/// };
+/// let mut writer = archive.writer_with_params("disk.img", params)?;
+/// writer.write_all(&[0u8; 4096])?;
+/// writer.close()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZstdParams {
+    /// Compression level in the range `1..=22`, 3 by default.
+    pub level: i32,
+    /// Base 2 log of the match window in the range `10..=31`, `10..=30` on 32-bit targets, or `None`
+    /// for the level's default.
+    ///
+    /// Windows above 27 (128 MiB) are flagged on the entry so readers of this crate raise the
+    /// decoder's memory limit, other zstd decoders need `--long=N` or an equivalent setting.
+    pub window_log: Option<u32>,
+    /// Enables long-distance matching.
+    pub long_mode: bool,
+}
+
+impl Default for ZstdParams {
+    fn default() -> Self {
+        ZstdParams {
+            level: DEFAULT_ZSTD_LEVEL,
+            window_log: None,
+            long_mode: false,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+/// How [`Bindle::merge`](crate::Bindle::merge) handles entries that already exist in the destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Conflict {
+    /// Keep the existing entry.
+    Skip,
+    /// Replace the existing entry, leaving its data to be reclaimed by vacuum.
+    Overwrite,
+    /// Fail before copying anything.
+    #[default]
+    Error,
+}
@@ -0,0 +1,60 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use std::io;
+
+/// Bytes of the random nonce stored in front of each encrypted entry.
+pub(crate) const NONCE_SIZE: usize = 12;
+
+/// Creates the cipher for a 256-bit archive key.
+pub(crate) fn cipher(key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(key.into())
+}
+
+/// Error for reading an encrypted entry without a key.
+pub(crate) fn missing_key() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Entry is encrypted, open the archive with a key",
+    )
+}
+
+/// Encrypts stored entry data, returning the nonce followed by the ciphertext and tag.
+///
+/// `name` is authenticated along with the data, so the blob only decrypts under the name it was
+/// written for and can't be swapped into another entry unnoticed.
+pub(crate) fn encrypt(cipher: &Aes256Gcm, name: &str, stored: &[u8]) -> io::Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let payload = Payload {
+        msg: stored,
+        aad: name.as_bytes(),
+    };
+    let ciphertext = cipher
+        .encrypt(&nonce, payload)
+        .map_err(|_| io::Error::other("Encryption failed"))?;
+    let mut out = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypts data written by [`encrypt()`] for `name`, failing if the key is wrong, the data was
+/// modified or it belongs to another entry.
+pub(crate) fn decrypt(cipher: &Aes256Gcm, name: &str, data: &[u8]) -> io::Result<Vec<u8>> {
+    let auth_failed = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Decryption failed, the key is wrong or the entry is corrupt",
+        )
+    };
+    if data.len() < NONCE_SIZE {
+        return Err(auth_failed());
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: name.as_bytes(),
+    };
+    cipher
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| auth_failed())
+}
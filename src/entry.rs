@@ -15,9 +15,22 @@ pub struct Entry {
     crc32: u32,
     name_len: u16,
     pub compression_type: u8,
+    /// Entry flags, see [`flags()`](Entry::flags). Kept under its original name so the public
+    /// layout doesn't change.
     pub _reserved: u8,
 }
 
+/// Set on zstd entries made of independent frames followed by a seek table.
+pub(crate) const ENTRY_FLAG_SEEKABLE: u8 = 1;
+/// Set on entries holding the target path of a symbolic link instead of file contents.
+pub(crate) const ENTRY_FLAG_SYMLINK: u8 = 2;
+/// Set on entries whose stored data is encrypted with the archive key.
+pub(crate) const ENTRY_FLAG_ENCRYPTED: u8 = 4;
+/// Set on zstd entries compressed with the archive's dictionary.
+pub(crate) const ENTRY_FLAG_DICT: u8 = 8;
+/// Set on zstd entries whose window is larger than decoders accept by default.
+pub(crate) const ENTRY_FLAG_LONG_WINDOW: u8 = 16;
+
 // The binary format uses little-endian byte order for all multi-byte integers.
 // These methods handle endianness conversion transparently:
 // - On little-endian systems (x86, ARM): zero overhead, direct access
@@ -51,6 +64,18 @@ impl Entry {
         self.uncompressed_size = value.to_le();
     }
 
+    /// Returns the compression ratio, `compressed_size / uncompressed_size`.
+    ///
+    /// Empty entries report a ratio of `1.0`.
+    pub fn ratio(&self) -> f64 {
+        let size = self.uncompressed_size();
+        if size > 0 {
+            self.compressed_size() as f64 / size as f64
+        } else {
+            1.0
+        }
+    }
+
     /// Returns the CRC32 checksum of the uncompressed data.
     pub fn crc32(&self) -> u32 {
         u32::from_le(self.crc32)
@@ -73,6 +98,119 @@ impl Entry {
     pub fn compression_type(&self) -> Compress {
         Compress::from_u8(self.compression_type)
     }
+
+    /// Returns the raw flag bits of this entry, described in the format specification.
+    pub fn flags(&self) -> u8 {
+        self._reserved
+    }
+
+    pub(crate) fn set_flags(&mut self, value: u8) {
+        self._reserved = value;
+    }
+
+    pub(crate) fn insert_flags(&mut self, value: u8) {
+        self._reserved |= value;
+    }
+
+    /// Returns true if this entry supports seeking within its compressed data.
+    pub fn is_seekable(&self) -> bool {
+        self.flags() & ENTRY_FLAG_SEEKABLE != 0
+    }
+
+    /// Returns true if this entry stores a symbolic link, its data is the link target.
+    pub fn is_symlink(&self) -> bool {
+        self.flags() & ENTRY_FLAG_SYMLINK != 0
+    }
+
+    /// Returns true if this entry was compressed with the archive's zstd dictionary.
+    pub fn uses_dictionary(&self) -> bool {
+        self.flags() & ENTRY_FLAG_DICT != 0
+    }
+
+    /// Returns true if this entry was compressed with a zstd window above 128 MiB.
+    ///
+    /// See [`ZstdParams::window_log`](crate::ZstdParams::window_log).
+    pub fn uses_long_window(&self) -> bool {
+        self.flags() & ENTRY_FLAG_LONG_WINDOW != 0
+    }
+
+    /// Returns true if this entry's data is encrypted.
+    ///
+    /// The CRC32 of an encrypted entry covers its stored bytes rather than the uncompressed data.
+    pub fn is_encrypted(&self) -> bool {
+        self.flags() & ENTRY_FLAG_ENCRYPTED != 0
+    }
+}
+
+/// Optional per-entry fields, stored after the name in version 2 index records.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct EntryMeta {
+    /// Modification time in nanoseconds since the Unix epoch.
+    pub mtime: Option<u64>,
+    /// Opaque bytes supplied by the user.
+    pub user: Option<Vec<u8>>,
+    /// SHA-256 of the uncompressed data.
+    pub sha256: Option<[u8; 32]>,
+}
+
+const META_TAG_MTIME: u16 = 1;
+const META_TAG_USER: u16 = 2;
+const META_TAG_SHA256: u16 = 3;
+
+/// Largest user metadata value that fits in the block next to the other records.
+pub(crate) const MAX_USER_META: usize = u16::MAX as usize - 12 - 36 - 4;
+
+impl EntryMeta {
+    /// Serializes the fields that are set as `(tag: u16, len: u16, value)` records.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if let Some(mtime) = self.mtime {
+            out.extend_from_slice(&META_TAG_MTIME.to_le_bytes());
+            out.extend_from_slice(&8u16.to_le_bytes());
+            out.extend_from_slice(&mtime.to_le_bytes());
+        }
+        if let Some(user) = &self.user {
+            out.extend_from_slice(&META_TAG_USER.to_le_bytes());
+            out.extend_from_slice(&(user.len() as u16).to_le_bytes());
+            out.extend_from_slice(user);
+        }
+        if let Some(sha256) = &self.sha256 {
+            out.extend_from_slice(&META_TAG_SHA256.to_le_bytes());
+            out.extend_from_slice(&32u16.to_le_bytes());
+            out.extend_from_slice(sha256);
+        }
+        out
+    }
+
+    /// Parses records written by [`encode`](EntryMeta::encode), skipping unknown tags.
+    ///
+    /// Parsing stops at the first truncated record.
+    pub fn decode(mut bytes: &[u8]) -> Self {
+        let mut meta = Self::default();
+        while bytes.len() >= 4 {
+            let tag = u16::from_le_bytes([bytes[0], bytes[1]]);
+            let len = u16::from_le_bytes([bytes[2], bytes[3]]) as usize;
+            let Some(value) = bytes.get(4..4 + len) else {
+                break;
+            };
+            match tag {
+                META_TAG_MTIME => {
+                    if let Ok(v) = <[u8; 8]>::try_from(value) {
+                        meta.mtime = Some(u64::from_le_bytes(v));
+                    }
+                }
+                META_TAG_USER => meta.user = Some(value.to_vec()),
+                META_TAG_SHA256 => {
+                    if let Ok(v) = <[u8; 32]>::try_from(value) {
+                        meta.sha256 = Some(v);
+                    }
+                }
+                _ => {}
+            }
+            bytes = &bytes[4 + len..];
+        }
+        meta
+    }
 }
 
 #[repr(C, packed)]
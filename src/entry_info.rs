@@ -0,0 +1,45 @@
+use crate::compress::Compress;
+use crate::entry::Entry;
+
+/// Decoded metadata for a single entry, returned by [`Bindle::stat()`](crate::Bindle::stat) and
+/// [`Bindle::entries_by_size()`](crate::Bindle::entries_by_size).
+///
+/// An owned copy of the fields of an index [`Entry`](crate::Entry) that doesn't depend on the
+/// on-disk layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryInfo {
+    /// The entry name.
+    pub name: String,
+    /// Absolute offset of the stored data in the archive.
+    pub offset: u64,
+    /// Number of bytes the entry takes up in the data region, excluding padding.
+    pub compressed_size: u64,
+    /// Size of the entry's data once decompressed.
+    pub uncompressed_size: u64,
+    /// CRC32 of the uncompressed data, or of the stored data for encrypted entries.
+    pub crc32: u32,
+    /// The compression the entry was stored with, never [`Compress::Auto`].
+    pub compression: Compress,
+}
+
+impl EntryInfo {
+    pub(crate) fn new(name: &str, entry: &Entry) -> Self {
+        EntryInfo {
+            name: name.to_string(),
+            offset: entry.offset(),
+            compressed_size: entry.compressed_size(),
+            uncompressed_size: entry.uncompressed_size(),
+            crc32: entry.crc32(),
+            compression: entry.compression_type(),
+        }
+    }
+
+    /// Compression ratio of the entry, see [`Entry::ratio()`].
+    pub fn ratio(&self) -> f64 {
+        if self.uncompressed_size > 0 {
+            self.compressed_size as f64 / self.uncompressed_size as f64
+        } else {
+            1.0
+        }
+    }
+}
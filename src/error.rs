@@ -0,0 +1,112 @@
+use std::fmt;
+use std::io;
+
+/// Errors produced while reading or validating an archive.
+///
+/// Methods returning [`io::Result`] wrap these in an [`io::Error`], so the variant can be
+/// recovered with [`io::Error::get_ref`] and [`downcast_ref`](std::error::Error::downcast_ref).
+#[derive(Debug)]
+pub enum BindleError {
+    /// The file doesn't start with the bindle header.
+    InvalidMagic,
+    /// The file was written in a newer format version than this crate supports.
+    UnsupportedVersion(u16),
+    /// The footer is missing or corrupt.
+    InvalidFooter,
+    /// The index doesn't match the checksum stored before the footer.
+    IndexCrcMismatch,
+    /// The file is too small to hold a header and footer.
+    TooSmall,
+    /// An entry's data doesn't match its stored CRC32.
+    CrcMismatch {
+        name: String,
+        expected: u32,
+        got: u32,
+    },
+    /// No entry exists with the given name.
+    NotFound(String),
+    /// An index record points at data outside the data region, the index is corrupt.
+    EntryOutOfBounds {
+        name: String,
+        offset: u64,
+        size: u64,
+    },
+    /// An entry's uncompressed size is over the archive's [`Limits`](crate::Limits).
+    TooLarge { name: String, size: u64, limit: u64 },
+    /// An entry name in the index isn't valid UTF-8.
+    InvalidName(String),
+    /// An entry name would be written outside the destination directory when unpacking.
+    UnsafeName(String),
+    /// An underlying I/O error.
+    Io(io::Error),
+}
+
+impl fmt::Display for BindleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindleError::InvalidMagic => write!(f, "Invalid header"),
+            BindleError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported format version {}, the newest supported version is {}",
+                version,
+                crate::FORMAT_VERSION
+            ),
+            BindleError::InvalidFooter => write!(f, "Invalid footer, the file may be corrupt"),
+            BindleError::IndexCrcMismatch => {
+                write!(f, "Index checksum mismatch, the index is corrupt")
+            }
+            BindleError::TooSmall => write!(f, "File too small to be a valid bindle"),
+            BindleError::CrcMismatch {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "CRC32 mismatch for '{}': expected {:x}, got {:x}",
+                name, expected, got
+            ),
+            BindleError::NotFound(name) => write!(f, "Entry '{}' not found", name),
+            BindleError::EntryOutOfBounds { name, offset, size } => write!(
+                f,
+                "Entry '{}' claims {} bytes at offset {}, outside the data region",
+                name, size, offset
+            ),
+            BindleError::TooLarge { name, size, limit } => write!(
+                f,
+                "'{}' is {} bytes uncompressed, over the limit of {} bytes",
+                name, size, limit
+            ),
+            BindleError::InvalidName(name) => write!(f, "Entry name '{}' is not valid UTF-8", name),
+            BindleError::UnsafeName(name) => {
+                write!(f, "Entry name '{}' escapes the destination directory", name)
+            }
+            BindleError::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BindleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BindleError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BindleError {
+    fn from(e: io::Error) -> Self {
+        BindleError::Io(e)
+    }
+}
+
+impl From<BindleError> for io::Error {
+    fn from(e: BindleError) -> Self {
+        match e {
+            BindleError::Io(e) => e,
+            BindleError::NotFound(_) => io::Error::new(io::ErrorKind::NotFound, e),
+            BindleError::UnsupportedVersion(_) => io::Error::new(io::ErrorKind::Unsupported, e),
+            _ => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
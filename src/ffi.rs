@@ -1,11 +1,14 @@
 use std::alloc::{Layout, dealloc};
+use std::cell::Cell;
 use std::ffi::{CStr, CString};
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 use std::mem;
 use std::os::raw::c_char;
+use std::path::Path;
 use std::slice;
 
-use crate::{Compress, Reader, Writer};
+use crate::bindle::pack_candidates;
+use crate::{BindleError, Compress, DEFAULT_ZSTD_LEVEL, PackOptions, Reader, Writer};
 
 /// FFI wrapper around Bindle that caches null-terminated entry names for C API.
 pub struct Bindle {
@@ -25,7 +28,7 @@ impl Bindle {
 
     fn rebuild_cache(&mut self) {
         self.entry_names_cache.clear();
-        for (name, _) in &self.bindle.index {
+        for name in self.bindle.index.keys() {
             if let Ok(c_str) = CString::new(name.as_str()) {
                 self.entry_names_cache.push(c_str);
             }
@@ -33,6 +36,142 @@ impl Bindle {
     }
 }
 
+/// Error codes reported by `bindle_last_error()`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The last operation succeeded.
+    Ok = 0,
+    /// A required pointer was NULL or a string wasn't valid UTF-8.
+    InvalidArgument = 1,
+    /// No entry exists with the given name.
+    NotFound = 2,
+    /// An entry's data doesn't match its stored CRC32.
+    CrcMismatch = 3,
+    /// The file isn't a valid archive or its index is corrupt.
+    InvalidFormat = 4,
+    /// An entry with the given name already exists.
+    AlreadyExists = 5,
+    /// The archive was opened read-only.
+    ReadOnly = 6,
+    /// The operation isn't supported for this entry.
+    Unsupported = 7,
+    /// Any other I/O error.
+    Io = 8,
+    /// A buffer for the result couldn't be allocated.
+    OutOfMemory = 9,
+}
+
+impl ErrorCode {
+    fn message(self) -> &'static CStr {
+        match self {
+            ErrorCode::Ok => c"ok",
+            ErrorCode::InvalidArgument => c"invalid argument",
+            ErrorCode::NotFound => c"entry not found",
+            ErrorCode::CrcMismatch => c"CRC32 mismatch",
+            ErrorCode::InvalidFormat => c"invalid or corrupt archive",
+            ErrorCode::AlreadyExists => c"entry already exists",
+            ErrorCode::ReadOnly => c"archive is read-only",
+            ErrorCode::Unsupported => c"operation not supported",
+            ErrorCode::Io => c"I/O error",
+            ErrorCode::OutOfMemory => c"out of memory",
+        }
+    }
+}
+
+impl From<&BindleError> for ErrorCode {
+    fn from(e: &BindleError) -> Self {
+        match e {
+            BindleError::NotFound(_) => ErrorCode::NotFound,
+            BindleError::CrcMismatch { .. } => ErrorCode::CrcMismatch,
+            BindleError::UnsupportedVersion(_) => ErrorCode::Unsupported,
+            BindleError::Io(e) => e.into(),
+            _ => ErrorCode::InvalidFormat,
+        }
+    }
+}
+
+impl From<&io::Error> for ErrorCode {
+    fn from(e: &io::Error) -> Self {
+        if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<BindleError>()) {
+            return e.into();
+        }
+        match e.kind() {
+            io::ErrorKind::NotFound => ErrorCode::NotFound,
+            io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+            io::ErrorKind::PermissionDenied => ErrorCode::ReadOnly,
+            io::ErrorKind::InvalidInput => ErrorCode::InvalidArgument,
+            io::ErrorKind::Unsupported => ErrorCode::Unsupported,
+            io::ErrorKind::InvalidData => ErrorCode::InvalidFormat,
+            _ => ErrorCode::Io,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: Cell<ErrorCode> = const { Cell::new(ErrorCode::Ok) };
+}
+
+fn set_last_error(code: ErrorCode) {
+    LAST_ERROR.with(|e| e.set(code));
+}
+
+/// Records an invalid argument and returns the failure sentinel.
+fn invalid_argument<T>(sentinel: T) -> T {
+    set_last_error(ErrorCode::InvalidArgument);
+    sentinel
+}
+
+/// Records the outcome of an operation for `bindle_last_error()`, returning the value on success.
+fn check<T, E>(result: Result<T, E>) -> Option<T>
+where
+    for<'e> &'e E: Into<ErrorCode>,
+{
+    match result {
+        Ok(value) => {
+            set_last_error(ErrorCode::Ok);
+            Some(value)
+        }
+        Err(e) => {
+            set_last_error((&e).into());
+            None
+        }
+    }
+}
+
+/// Returns the error code of the last failed or succeeded operation on this thread.
+///
+/// Operations that return a failure sentinel (`false`, `NULL`, `0` or `-1`) set this before
+/// returning, so it can tell a missing entry from a CRC32 mismatch or an I/O error. The value is
+/// one of the `BindleErrorCode` constants, 0 means success.
+#[unsafe(no_mangle)]
+pub extern "C" fn bindle_last_error() -> i32 {
+    LAST_ERROR.with(|e| e.get()) as i32
+}
+
+/// Returns a static, NUL-terminated description of an error code from `bindle_last_error()`.
+///
+/// Unknown codes return "unknown error". Do NOT free the returned pointer.
+#[unsafe(no_mangle)]
+pub extern "C" fn bindle_error_message(code: i32) -> *const c_char {
+    const CODES: [ErrorCode; 10] = [
+        ErrorCode::Ok,
+        ErrorCode::InvalidArgument,
+        ErrorCode::NotFound,
+        ErrorCode::CrcMismatch,
+        ErrorCode::InvalidFormat,
+        ErrorCode::AlreadyExists,
+        ErrorCode::ReadOnly,
+        ErrorCode::Unsupported,
+        ErrorCode::Io,
+        ErrorCode::OutOfMemory,
+    ];
+    match usize::try_from(code).ok().and_then(|i| CODES.get(i)) {
+        Some(code) => code.message().as_ptr(),
+        None => c"unknown error".as_ptr(),
+    }
+}
+
 /// Creates a new archive, overwriting any existing file.
 ///
 /// # Parameters
@@ -43,19 +182,19 @@ impl Bindle {
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_create(path: *const c_char) -> *mut Bindle {
     if path.is_null() {
-        return std::ptr::null_mut();
+        return invalid_argument(std::ptr::null_mut());
     }
 
     let path_str = unsafe {
         match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+            Err(_) => return invalid_argument(std::ptr::null_mut()),
         }
     };
 
-    match crate::Bindle::create(path_str) {
-        Ok(b) => Box::into_raw(Box::new(Bindle::new(b))),
-        Err(_) => std::ptr::null_mut(),
+    match check(crate::Bindle::create(path_str)) {
+        Some(b) => Box::into_raw(Box::new(Bindle::new(b))),
+        None => std::ptr::null_mut(),
     }
 }
 
@@ -69,19 +208,19 @@ pub unsafe extern "C" fn bindle_create(path: *const c_char) -> *mut Bindle {
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_open(path: *const c_char) -> *mut Bindle {
     if path.is_null() {
-        return std::ptr::null_mut();
+        return invalid_argument(std::ptr::null_mut());
     }
 
     let path_str = unsafe {
         match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+            Err(_) => return invalid_argument(std::ptr::null_mut()),
         }
     };
 
-    match crate::Bindle::open(path_str) {
-        Ok(b) => Box::into_raw(Box::new(Bindle::new(b))),
-        Err(_) => std::ptr::null_mut(),
+    match check(crate::Bindle::open(path_str)) {
+        Some(b) => Box::into_raw(Box::new(Bindle::new(b))),
+        None => std::ptr::null_mut(),
     }
 }
 
@@ -95,19 +234,19 @@ pub unsafe extern "C" fn bindle_open(path: *const c_char) -> *mut Bindle {
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_load(path: *const c_char) -> *mut Bindle {
     if path.is_null() {
-        return std::ptr::null_mut();
+        return invalid_argument(std::ptr::null_mut());
     }
 
     let path_str = unsafe {
         match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+            Err(_) => return invalid_argument(std::ptr::null_mut()),
         }
     };
 
-    match crate::Bindle::load(path_str) {
-        Ok(b) => Box::into_raw(Box::new(Bindle::new(b))),
-        Err(_) => std::ptr::null_mut(),
+    match check(crate::Bindle::load(path_str)) {
+        Some(b) => Box::into_raw(Box::new(Bindle::new(b))),
+        None => std::ptr::null_mut(),
     }
 }
 
@@ -118,7 +257,7 @@ pub unsafe extern "C" fn bindle_load(path: *const c_char) -> *mut Bindle {
 /// * `name` - NUL-terminated entry name
 /// * `data` - Data bytes (may contain NUL bytes)
 /// * `data_len` - Length of data in bytes
-/// * `compress` - Compression mode (BindleCompressNone, BindleCompressZstd, or BindleCompressAuto)
+/// * `compress` - Compression mode (BindleCompressNone, BindleCompressZstd, BindleCompressGzip, or BindleCompressAuto)
 ///
 /// # Returns
 /// True on success. Call `bindle_save()` to commit changes.
@@ -131,19 +270,19 @@ pub unsafe extern "C" fn bindle_add(
     compress: Compress,
 ) -> bool {
     if ctx.is_null() || name.is_null() || (data.is_null() && data_len > 0) {
-        return false;
+        return invalid_argument(false);
     }
 
     unsafe {
         let name_str = match CStr::from_ptr(name).to_str() {
             Ok(s) => s,
-            Err(_) => return false,
+            Err(_) => return invalid_argument(false),
         };
 
         let data_slice = slice::from_raw_parts(data, data_len);
         let b = &mut (*ctx);
 
-        let result = b.bindle.add(name_str, data_slice, compress).is_ok();
+        let result = check(b.bindle.add(name_str, data_slice, compress)).is_some();
         if result {
             b.rebuild_cache();
         }
@@ -169,23 +308,23 @@ pub unsafe extern "C" fn bindle_add_file(
     compress: Compress,
 ) -> bool {
     if ctx.is_null() || name.is_null() || path.is_null() {
-        return false;
+        return invalid_argument(false);
     }
 
     unsafe {
         let name_str = match CStr::from_ptr(name).to_str() {
             Ok(s) => s,
-            Err(_) => return false,
+            Err(_) => return invalid_argument(false),
         };
 
         let path_str = match CStr::from_ptr(path).to_str() {
             Ok(s) => s,
-            Err(_) => return false,
+            Err(_) => return invalid_argument(false),
         };
 
         let b = &mut (*ctx);
 
-        let result = b.bindle.add_file(name_str, path_str, compress).is_ok();
+        let result = check(b.bindle.add_file(name_str, path_str, compress)).is_some();
         if result {
             b.rebuild_cache();
         }
@@ -199,11 +338,11 @@ pub unsafe extern "C" fn bindle_add_file(
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_save(ctx: *mut Bindle) -> bool {
     if ctx.is_null() {
-        return false;
+        return invalid_argument(false);
     }
     unsafe {
         let b = &mut (*ctx);
-        b.bindle.save().is_ok()
+        check(b.bindle.save()).is_some()
     }
 }
 
@@ -236,23 +375,23 @@ pub unsafe extern "C" fn bindle_read_buffer(
 ) -> *mut u8 {
     unsafe {
         if ctx_ptr.is_null() || name.is_null() {
-            return std::ptr::null_mut();
+            return invalid_argument(std::ptr::null_mut());
         }
 
         // 1. Convert the C string to a Rust &str
         let c_str = std::ffi::CStr::from_ptr(name);
         let name_str = match c_str.to_str() {
             Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+            Err(_) => return invalid_argument(std::ptr::null_mut()),
         };
 
         // 2. Access your Rust Bindle struct
         let ctx = &mut *ctx_ptr;
 
         // 3. The actual data retrieval logic
-        match ctx.bindle.read(name_str) {
+        match check(ctx.bindle.read(name_str)) {
             Some(bytes) => wrap_in_ffi_header(bytes.as_ref(), out_len),
-            None => return std::ptr::null_mut(),
+            None => std::ptr::null_mut(),
         }
     }
 }
@@ -267,11 +406,16 @@ unsafe fn wrap_in_ffi_header(data: &[u8], out_len: *mut usize) -> *mut u8 {
 
         let size_of_header = std::mem::size_of::<usize>();
         let total_size = size_of_header + len;
-        let layout =
-            std::alloc::Layout::from_size_align(total_size, std::mem::align_of::<usize>()).unwrap();
+        let Ok(layout) =
+            std::alloc::Layout::from_size_align(total_size, std::mem::align_of::<usize>())
+        else {
+            set_last_error(ErrorCode::OutOfMemory);
+            return std::ptr::null_mut();
+        };
 
         let raw_ptr = std::alloc::alloc(layout);
         if raw_ptr.is_null() {
+            set_last_error(ErrorCode::OutOfMemory);
             return std::ptr::null_mut();
         }
 
@@ -330,23 +474,34 @@ pub unsafe extern "C" fn bindle_read_uncompressed_direct(
     out_len: *mut usize,
 ) -> *const u8 {
     if ctx.is_null() || name.is_null() || out_len.is_null() {
-        return std::ptr::null_mut();
+        return invalid_argument(std::ptr::null_mut());
     }
 
     unsafe {
         let name_str = match CStr::from_ptr(name).to_str() {
             Ok(s) => s,
-            Err(_) => return std::ptr::null_mut(),
+            Err(_) => return invalid_argument(std::ptr::null_mut()),
         };
 
         let b = &(*ctx);
-        if let Some(data) = b.bindle.read(name_str) {
-            match data {
-                std::borrow::Cow::Borrowed(bytes) => bytes.as_ptr(),
-                _ => std::ptr::null_mut(),
+        // Don't decompress an entry just to reject it
+        if b.bindle
+            .compression_of(name_str)
+            .is_some_and(|c| c != Compress::None)
+        {
+            set_last_error(ErrorCode::Unsupported);
+            return std::ptr::null_mut();
+        }
+        match check(b.bindle.read(name_str)) {
+            Some(std::borrow::Cow::Borrowed(bytes)) => {
+                *out_len = bytes.len();
+                bytes.as_ptr()
+            }
+            Some(_) => {
+                set_last_error(ErrorCode::Unsupported);
+                std::ptr::null_mut()
             }
-        } else {
-            std::ptr::null_mut()
+            None => std::ptr::null_mut(),
         }
     }
 }
@@ -360,6 +515,38 @@ pub unsafe extern "C" fn bindle_length(ctx: *const Bindle) -> usize {
     unsafe { (*ctx).bindle.len() }
 }
 
+/// Returns the format version of the archive, at most `FORMAT_VERSION`, or 0 if `ctx` is NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_version(ctx: *const Bindle) -> u8 {
+    if ctx.is_null() {
+        return 0;
+    }
+    unsafe { (*ctx).bindle.version() }
+}
+
+/// Sets the size limits enforced when reading and unpacking.
+///
+/// Reads of entries larger than `max_entry_size` bytes uncompressed fail before anything is decoded,
+/// and unpacking fails if the entries add up to more than `max_total_size` bytes. The defaults are
+/// 1 GiB per entry and no total limit.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_set_limits(
+    ctx: *mut Bindle,
+    max_entry_size: u64,
+    max_total_size: u64,
+) -> bool {
+    if ctx.is_null() {
+        return invalid_argument(false);
+    }
+    let b = unsafe { &mut (*ctx) };
+    b.bindle.set_limits(crate::Limits {
+        max_entry_size,
+        max_total_size,
+    });
+    set_last_error(ErrorCode::Ok);
+    true
+}
+
 /// Returns the name of the entry at the given index as a null-terminated C string.
 ///
 /// Use with `bindle_length()` to iterate over all entries. The pointer is valid as long as the Bindle handle is open.
@@ -383,10 +570,10 @@ pub unsafe extern "C" fn bindle_entry_name(ctx: *const Bindle, index: usize) ->
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_vacuum(ctx: *mut Bindle) -> bool {
     if ctx.is_null() {
-        return false;
+        return invalid_argument(false);
     }
     let b = unsafe { &mut (*ctx) };
-    let result = b.bindle.vacuum().is_ok();
+    let result = check(b.bindle.vacuum()).is_some();
     if result {
         b.rebuild_cache();
     }
@@ -397,11 +584,11 @@ pub unsafe extern "C" fn bindle_vacuum(ctx: *mut Bindle) -> bool {
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_unpack(ctx: *mut Bindle, dest_path: *const c_char) -> bool {
     if ctx.is_null() || dest_path.is_null() {
-        return false;
+        return invalid_argument(false);
     }
     let b = unsafe { &*ctx };
     let path = unsafe { CStr::from_ptr(dest_path).to_string_lossy() };
-    b.bindle.unpack(path.as_ref()).is_ok()
+    check(b.bindle.unpack(path.as_ref())).is_some()
 }
 
 /// Recursively adds all files from a directory to the archive.
@@ -414,11 +601,54 @@ pub unsafe extern "C" fn bindle_pack(
     compress: Compress,
 ) -> bool {
     if ctx.is_null() || src_path.is_null() {
-        return false;
+        return invalid_argument(false);
     }
     let b = unsafe { &mut *ctx };
     let path = unsafe { CStr::from_ptr(src_path).to_string_lossy() };
-    let result = b.bindle.pack(path.as_ref(), compress).is_ok();
+    let result = check(b.bindle.pack(path.as_ref(), compress)).is_some();
+    if result {
+        b.rebuild_cache();
+    }
+    result
+}
+
+/// Recursively adds the files from a directory for which `filter` returns true.
+///
+/// `filter` is called with the entry name of each file, relative to `src_path`, and the name is only
+/// valid during the call. Every file is filtered before the archive is modified, so the callback may
+/// report progress or call other functions on `ctx`. Call `bindle_save()` to commit changes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_pack_filtered(
+    ctx: *mut Bindle,
+    src_path: *const c_char,
+    compress: Compress,
+    filter: Option<extern "C" fn(*const c_char) -> bool>,
+) -> bool {
+    let Some(filter) = filter else {
+        return invalid_argument(false);
+    };
+    if ctx.is_null() || src_path.is_null() {
+        return invalid_argument(false);
+    }
+    let path = unsafe { CStr::from_ptr(src_path).to_string_lossy() };
+    let Some((files, _)) = check(pack_candidates(
+        Path::new(path.as_ref()),
+        &PackOptions::default(),
+    )) else {
+        return false;
+    };
+
+    // No reference to `ctx` is alive while the callback runs
+    let files = files
+        .into_iter()
+        .filter(|(name, _, _)| CString::new(name.as_str()).is_ok_and(|name| filter(name.as_ptr())))
+        .collect();
+    let b = unsafe { &mut *ctx };
+    let result = check(
+        b.bindle
+            .pack_files(files, compress, DEFAULT_ZSTD_LEVEL, false, |_| {}),
+    )
+    .is_some();
     if result {
         b.rebuild_cache();
     }
@@ -450,24 +680,71 @@ pub unsafe extern "C" fn bindle_exists(ctx: *const Bindle, name: *const c_char)
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_remove(ctx: *mut Bindle, name: *const c_char) -> bool {
     if ctx.is_null() || name.is_null() {
-        return false;
+        return invalid_argument(false);
     }
 
     let b = unsafe { &mut *ctx };
     let name_str = unsafe {
         match CStr::from_ptr(name).to_str() {
             Ok(s) => s,
-            Err(_) => return false,
+            Err(_) => return invalid_argument(false),
         }
     };
 
     let result = b.bindle.remove(name_str);
     if result {
+        set_last_error(ErrorCode::Ok);
         b.rebuild_cache();
+    } else {
+        set_last_error(ErrorCode::NotFound);
     }
     result
 }
 
+/// Renames an entry without copying its data.
+///
+/// Returns false if `old_name` doesn't exist, either name is invalid, or `new_name` is already taken
+/// and `overwrite` is false. Call `bindle_save()` to commit changes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_rename(
+    ctx: *mut Bindle,
+    old_name: *const c_char,
+    new_name: *const c_char,
+    overwrite: bool,
+) -> bool {
+    if ctx.is_null() || old_name.is_null() || new_name.is_null() {
+        return invalid_argument(false);
+    }
+
+    let b = unsafe { &mut *ctx };
+    let (old_str, new_str) = unsafe {
+        match (
+            CStr::from_ptr(old_name).to_str(),
+            CStr::from_ptr(new_name).to_str(),
+        ) {
+            (Ok(o), Ok(n)) => (o, n),
+            _ => return invalid_argument(false),
+        }
+    };
+
+    let result = if overwrite {
+        b.bindle.rename_overwrite(old_str, new_str)
+    } else {
+        b.bindle.rename(old_str, new_str)
+    };
+    match check(result) {
+        Some(true) => {
+            b.rebuild_cache();
+            true
+        }
+        Some(false) => {
+            set_last_error(ErrorCode::NotFound);
+            false
+        }
+        None => false,
+    }
+}
+
 /// Creates a streaming writer for adding an entry.
 ///
 /// The writer must be closed with `bindle_writer_close()`, then call `bindle_save()` to commit.
@@ -482,9 +759,38 @@ pub unsafe extern "C" fn bindle_writer_new<'a>(
         let b = &mut *ctx;
         let name_str = CStr::from_ptr(name).to_string_lossy();
 
-        match b.bindle.writer(&name_str, compress) {
-            Ok(stream) => Box::into_raw(Box::new(std::mem::transmute(stream))),
-            Err(_) => std::ptr::null_mut(),
+        match check(b.bindle.writer(&name_str, compress)) {
+            Some(stream) => Box::into_raw(Box::new(std::mem::transmute::<Writer<'_>, Writer<'a>>(
+                stream,
+            ))),
+            None => std::ptr::null_mut(),
+        }
+    }
+}
+
+/// Creates a streaming writer for a zstd entry that supports seeking.
+///
+/// Data is split into independent frames of `frame_size` uncompressed bytes, pass 0 for the 1 MiB default.
+/// The writer must be closed with `bindle_writer_close()`, then call `bindle_save()` to commit.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_writer_new_seekable<'a>(
+    ctx: *mut Bindle,
+    name: *const c_char,
+    frame_size: usize,
+) -> *mut Writer<'a> {
+    unsafe {
+        let b = &mut *ctx;
+        let name_str = CStr::from_ptr(name).to_string_lossy();
+        let frame_size = match frame_size {
+            0 => crate::DEFAULT_FRAME_SIZE,
+            n => n,
+        };
+
+        match check(b.bindle.seekable_writer(&name_str, frame_size)) {
+            Some(stream) => Box::into_raw(Box::new(std::mem::transmute::<Writer<'_>, Writer<'a>>(
+                stream,
+            ))),
+            None => std::ptr::null_mut(),
         }
     }
 }
@@ -499,7 +805,7 @@ pub unsafe extern "C" fn bindle_writer_write(
     unsafe {
         let s = &mut *stream;
         let chunk = std::slice::from_raw_parts(data, len);
-        s.write_all(chunk).is_ok()
+        check(s.write_all(chunk)).is_some()
     }
 }
 
@@ -507,7 +813,7 @@ pub unsafe extern "C" fn bindle_writer_write(
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_writer_close(stream: *mut Writer) -> bool {
     let s = unsafe { Box::from_raw(stream) };
-    s.close().is_ok()
+    check(s.close()).is_some()
 }
 
 /// Creates a streaming reader for an entry.
@@ -520,15 +826,15 @@ pub unsafe extern "C" fn bindle_reader_new<'a>(
     name: *const c_char,
 ) -> *mut Reader<'a> {
     if ctx.is_null() || name.is_null() {
-        return std::ptr::null_mut();
+        return invalid_argument(std::ptr::null_mut());
     }
 
     let b = unsafe { &*ctx };
     let name_str = unsafe { CStr::from_ptr(name).to_string_lossy() };
 
-    match b.bindle.reader(&name_str) {
-        Ok(reader) => Box::into_raw(Box::new(reader)),
-        Err(_) => std::ptr::null_mut(),
+    match check(b.bindle.reader(&name_str)) {
+        Some(reader) => Box::into_raw(Box::new(reader)),
+        None => std::ptr::null_mut(),
     }
 }
 
@@ -542,15 +848,15 @@ pub unsafe extern "C" fn bindle_reader_read(
     buffer_len: usize,
 ) -> isize {
     if reader.is_null() || buffer.is_null() {
-        return -1;
+        return invalid_argument(-1);
     }
 
     let r = unsafe { &mut *reader };
     let out_slice = unsafe { slice::from_raw_parts_mut(buffer, buffer_len) };
 
-    match r.read(out_slice) {
-        Ok(n) => n as isize,
-        Err(_) => -1,
+    match check(r.read(out_slice)) {
+        Some(n) => n as isize,
+        None => -1,
     }
 }
 
@@ -560,11 +866,11 @@ pub unsafe extern "C" fn bindle_reader_read(
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_reader_verify_crc32(reader: *const Reader) -> bool {
     if reader.is_null() {
-        return false;
+        return invalid_argument(false);
     }
 
     let r = unsafe { &*reader };
-    r.verify_crc32().is_ok()
+    check(r.verify_crc32()).is_some()
 }
 
 /// Closes the reader and frees the handle.
@@ -585,7 +891,8 @@ pub unsafe extern "C" fn bindle_reader_close(reader: *mut Reader) {
 ///
 /// # Returns
 /// The uncompressed size in bytes, or 0 if the entry doesn't exist.
-/// Note: Returns 0 for both non-existent entries and zero-length entries.
+/// Note: Returns 0 for both non-existent entries and zero-length entries, use `bindle_exists()` to
+/// tell the cases apart.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_entry_size(ctx: *const Bindle, name: *const c_char) -> usize {
     if ctx.is_null() || name.is_null() {
@@ -613,9 +920,12 @@ pub unsafe extern "C" fn bindle_entry_size(ctx: *const Bindle, name: *const c_ch
 /// * `name` - NUL-terminated entry name
 ///
 /// # Returns
-/// The Compress value (0 = None, 1 = Zstd), or 0 if the entry doesn't exist.
+/// The Compress value (0 = None, 1 = Zstd, 3 = Gzip), or 0 if the entry doesn't exist.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn bindle_entry_compress(ctx: *const Bindle, name: *const c_char) -> Compress {
+pub unsafe extern "C" fn bindle_entry_compress(
+    ctx: *const Bindle,
+    name: *const c_char,
+) -> Compress {
     if ctx.is_null() || name.is_null() {
         return Compress::None;
     }
@@ -628,18 +938,73 @@ pub unsafe extern "C" fn bindle_entry_compress(ctx: *const Bindle, name: *const
 
         let b = &*ctx;
         match b.bindle.index.get(name_str) {
-            Some(entry) => {
-                if entry.compression_type == 1 {
-                    Compress::Zstd
-                } else {
-                    Compress::None
-                }
-            }
+            Some(entry) => entry.compression_type(),
             None => Compress::None,
         }
     }
 }
 
+/// Gets the compressed size of an entry by name, the number of bytes it occupies in the archive.
+///
+/// # Parameters
+/// * `ctx` - Bindle handle
+/// * `name` - NUL-terminated entry name
+///
+/// # Returns
+/// The compressed size in bytes, or 0 if the entry doesn't exist.
+/// For uncompressed entries this equals the uncompressed size.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_entry_compressed_size(
+    ctx: *const Bindle,
+    name: *const c_char,
+) -> usize {
+    if ctx.is_null() || name.is_null() {
+        return 0;
+    }
+
+    unsafe {
+        let name_str = match CStr::from_ptr(name).to_str() {
+            Ok(s) => s,
+            Err(_) => return 0,
+        };
+
+        let b = &*ctx;
+        match b.bindle.index.get(name_str) {
+            Some(entry) => entry.compressed_size() as usize,
+            None => 0,
+        }
+    }
+}
+
+/// Gets the stored CRC32 of an entry's uncompressed data by name.
+///
+/// # Parameters
+/// * `ctx` - Bindle handle
+/// * `name` - NUL-terminated entry name
+///
+/// # Returns
+/// The CRC32 checksum, or 0 if the entry doesn't exist.
+/// Note: 0 is also a valid checksum, use `bindle_exists()` to tell the cases apart.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bindle_entry_crc32(ctx: *const Bindle, name: *const c_char) -> u32 {
+    if ctx.is_null() || name.is_null() {
+        return 0;
+    }
+
+    unsafe {
+        let name_str = match CStr::from_ptr(name).to_str() {
+            Ok(s) => s,
+            Err(_) => return 0,
+        };
+
+        let b = &*ctx;
+        match b.bindle.index.get(name_str) {
+            Some(entry) => entry.crc32(),
+            None => 0,
+        }
+    }
+}
+
 /// Reads an entry into a pre-existing buffer.
 ///
 /// Decompresses if needed and verifies CRC32. Reads up to `buffer_len` bytes.
@@ -652,7 +1017,9 @@ pub unsafe extern "C" fn bindle_entry_compress(ctx: *const Bindle, name: *const
 ///
 /// # Returns
 /// The number of bytes actually read, or 0 if the entry doesn't exist or CRC32 check fails.
-/// If the entry is larger than `buffer_len`, only `buffer_len` bytes are read.
+/// Empty entries also read 0 bytes, `bindle_last_error()` returns `BindleErrorCodeOk` for them.
+/// If the entry is larger than `buffer_len`, only `buffer_len` bytes are read and the CRC32
+/// isn't checked, since it covers the whole entry.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn bindle_read(
     ctx: *const Bindle,
@@ -661,21 +1028,18 @@ pub unsafe extern "C" fn bindle_read(
     buffer_len: usize,
 ) -> usize {
     if ctx.is_null() || name.is_null() || buffer.is_null() {
-        return 0;
+        return invalid_argument(0);
     }
 
     unsafe {
         let name_str = match CStr::from_ptr(name).to_str() {
             Ok(s) => s,
-            Err(_) => return 0,
+            Err(_) => return invalid_argument(0),
         };
 
         let b = &*ctx;
         let buffer_slice = slice::from_raw_parts_mut(buffer, buffer_len);
 
-        match b.bindle.read_into(name_str, buffer_slice) {
-            Ok(bytes_read) => bytes_read,
-            Err(_) => 0,
-        }
+        check(b.bindle.read_into(name_str, buffer_slice)).unwrap_or_default()
     }
 }
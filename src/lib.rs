@@ -19,32 +19,159 @@
 use std::io::{self, Write};
 
 // Module declarations
+#[cfg(feature = "async")]
+mod async_reader;
 mod bindle;
+mod bindle_ref;
 mod compress;
+mod conflict;
+mod crypto;
 mod entry;
+mod entry_info;
+mod error;
+mod limits;
+mod options;
+mod owned_reader;
+mod pack;
+mod read_only;
 mod reader;
+mod seekable;
+mod storage;
+mod tree;
+mod view;
 mod writer;
 
 pub(crate) mod ffi;
 
 // Public re-exports
+#[cfg(feature = "async")]
+pub use async_reader::AsyncReader;
 pub use bindle::Bindle;
-pub use compress::Compress;
+pub use bindle_ref::BindleRef;
+pub use compress::{Compress, ZstdParams};
+pub use conflict::Conflict;
 pub use entry::Entry;
+pub use entry_info::EntryInfo;
+pub use error::BindleError;
+pub use limits::Limits;
+pub use options::{Backend, Options, SyncPolicy};
+pub use owned_reader::OwnedReader;
+pub use pack::{PackEvent, PackOptions, PackStats, Symlinks};
+pub use read_only::ReadOnlyBindle;
 pub use reader::Reader;
+pub use tree::TreeNode;
+pub use view::ReadOnlyView;
 pub use writer::Writer;
 
+/// Default uncompressed size of each frame written by [`Bindle::seekable_writer`], 1 MiB.
+pub const DEFAULT_FRAME_SIZE: usize = 1 << 20;
+
+/// Zstd compression level used when none is given, such as by [`Bindle::add`] and [`Bindle::writer`].
+///
+/// Pass another level in `1..=22` to [`Bindle::writer_with_level`] and the other `_with_level` methods.
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// Newest archive format version this crate reads, and the version it writes.
+///
+/// Archives with a newer version fail to open with [`BindleError::UnsupportedVersion`], see
+/// [`Bindle::version()`] for the version of an open archive.
+pub const FORMAT_VERSION: u8 = 4;
+
+/// Longest entry name in bytes, names are stored with a 16-bit length.
+///
+/// Adding, packing or renaming to a longer name fails with [`std::io::ErrorKind::InvalidInput`].
+pub const MAX_NAME_LEN: usize = u16::MAX as usize;
+
 // Constants
-pub(crate) const BNDL_MAGIC: &[u8; 8] = b"BINDL001";
+pub(crate) const BNDL_MAGIC: &[u8; 8] = b"BINDL004";
+pub(crate) const BNDL_MAGIC_V1: &[u8; 8] = b"BINDL001";
+pub(crate) const BNDL_MAGIC_V2: &[u8; 8] = b"BINDL002";
+pub(crate) const BNDL_MAGIC_V3: &[u8; 8] = b"BINDL003";
 pub(crate) const BNDL_ALIGN: usize = 8;
 pub(crate) const ENTRY_SIZE: usize = std::mem::size_of::<Entry>();
 pub(crate) const FOOTER_SIZE: usize = std::mem::size_of::<entry::Footer>();
+pub(crate) const INDEX_CRC_SIZE: usize = 8; // index_crc32 + reserved, before the footer since version 3
 pub(crate) const HEADER_SIZE: usize = 8;
-pub(crate) const AUTO_COMPRESS_THRESHOLD: usize = 2048;
+pub(crate) const AUTO_COMPRESS_THRESHOLD: usize = 2048; // Default for Bindle::set_auto_compress_threshold
+pub(crate) const AUTO_SAMPLE_SIZE: usize = 4096; // Prefix compressed to decide Compress::Auto
+pub(crate) const AUTO_SAMPLE_RATIO: f64 = 0.9;
+pub(crate) const MAX_PREALLOC: u64 = 64 << 20; // Largest buffer reserved up front for a recorded size
+pub(crate) const ZSTD_LEVELS: std::ops::RangeInclusive<i32> = 1..=22;
+#[cfg(target_pointer_width = "64")]
+pub(crate) const ZSTD_WINDOW_LOGS: std::ops::RangeInclusive<u32> = 10..=31;
+#[cfg(not(target_pointer_width = "64"))]
+pub(crate) const ZSTD_WINDOW_LOGS: std::ops::RangeInclusive<u32> = 10..=30; // zstd's limit on 32-bit
+pub(crate) const ZSTD_DEFAULT_WINDOW_LOG_MAX: u32 = 27; // Largest window decoders accept by default
 pub(crate) const FOOTER_MAGIC: u32 = 0x62626262;
+pub(crate) const DICT_ENTRY: &str = "\0dict"; // Reserved index name of the zstd dictionary
 const ZEROS: &[u8; 64] = &[0u8; 64]; // Reusable zero buffer for padding
 
 // Helper functions
+
+/// Returns an [`io::ErrorKind::InvalidInput`] error if `name` is longer than [`MAX_NAME_LEN`] or
+/// is the reserved dictionary name.
+pub(crate) fn check_name(name: &str) -> io::Result<()> {
+    if name == DICT_ENTRY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Entry name is reserved for the zstd dictionary",
+        ));
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Entry name is {} bytes, longer than the maximum of {} bytes",
+                name.len(),
+                MAX_NAME_LEN
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns an [`io::ErrorKind::InvalidInput`] error if `level` isn't a zstd level in [`ZSTD_LEVELS`].
+pub(crate) fn check_level(level: i32) -> io::Result<()> {
+    if !ZSTD_LEVELS.contains(&level) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid zstd compression level {}, expected {}..={}",
+                level,
+                ZSTD_LEVELS.start(),
+                ZSTD_LEVELS.end()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the format version for a supported header.
+///
+/// Headers are `BINDL` followed by a three digit version. Versions newer than [`FORMAT_VERSION`]
+/// are rejected with [`BindleError::UnsupportedVersion`] rather than risk misparsing them.
+pub(crate) fn header_version(header: &[u8; HEADER_SIZE]) -> Result<u8, BindleError> {
+    match header {
+        BNDL_MAGIC_V1 => Ok(1),
+        BNDL_MAGIC_V2 => Ok(2),
+        BNDL_MAGIC_V3 => Ok(3),
+        BNDL_MAGIC => Ok(FORMAT_VERSION),
+        _ => {
+            let digits = header
+                .strip_prefix(b"BINDL")
+                .filter(|digits| digits.iter().all(u8::is_ascii_digit))
+                .ok_or(BindleError::InvalidMagic)?;
+            let version = digits
+                .iter()
+                .fold(0u16, |v, d| v * 10 + u16::from(d - b'0'));
+            match version {
+                0 => Err(BindleError::InvalidMagic),
+                _ => Err(BindleError::UnsupportedVersion(version)),
+            }
+        }
+    }
+}
+
 pub(crate) fn pad<
     const SIZE: usize,
     T: Copy + TryFrom<usize> + std::ops::Sub<T, Output = T> + std::ops::Rem<T, Output = T>,
@@ -61,6 +188,21 @@ where
     unreachable!()
 }
 
+/// Returns the base 2 log of the window the zstd frame at the start of `frame` asks for, rounded
+/// up, or `None` if the frame header is incomplete.
+pub(crate) fn zstd_window_log(frame: &[u8]) -> Option<u32> {
+    let descriptor = *frame.get(4)?;
+    let window_size = if descriptor & 0x20 != 0 {
+        // Single segment frames use their content size as the window
+        zstd::zstd_safe::get_frame_content_size(frame).ok()??
+    } else {
+        let window = *frame.get(5)?;
+        let base = 1u64 << (10 + (window >> 3));
+        base + (base >> 3) * u64::from(window & 7)
+    };
+    Some(window_size.max(1).next_power_of_two().trailing_zeros())
+}
+
 // Helper to write padding zeros without allocating
 pub(crate) fn write_padding<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
     let mut remaining = len;
@@ -75,9 +217,10 @@ pub(crate) fn write_padding<W: Write>(writer: &mut W, len: usize) -> io::Result<
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::borrow::Cow;
     use std::fs;
     use std::fs::OpenOptions;
-    use std::io::{Seek, SeekFrom};
+    use std::io::{Read, Seek, SeekFrom};
 
     #[test]
     fn test_create_and_read() {
@@ -341,7 +484,7 @@ mod tests {
 
             // Skip the header and modify the first byte of data
             file.seek(SeekFrom::Start(HEADER_SIZE as u64)).unwrap();
-            file.write(&[b'X']).unwrap(); // Corrupt first byte
+            file.write_all(b"X").unwrap(); // Corrupt first byte
             file.flush().unwrap();
         }
 
@@ -349,7 +492,20 @@ mod tests {
         {
             let b = Bindle::open(path).expect("Failed to reopen after corruption");
             let result = b.read("test.txt");
-            assert!(result.is_none(), "Read should fail due to CRC32 mismatch");
+            assert!(
+                matches!(result, Err(BindleError::CrcMismatch { .. })),
+                "Read should fail due to CRC32 mismatch"
+            );
+
+            // Streaming readers report the same error, which the C API maps to its own code
+            let mut reader = b.reader("test.txt").unwrap();
+            io::copy(&mut reader, &mut io::sink()).unwrap();
+            let err = reader.verify_crc32().unwrap_err();
+            let inner = err.get_ref().and_then(|e| e.downcast_ref::<BindleError>());
+            assert!(
+                matches!(inner, Some(BindleError::CrcMismatch { name, .. }) if name == "test.txt")
+            );
+            assert_eq!(ffi::ErrorCode::from(&err), ffi::ErrorCode::CrcMismatch);
         }
 
         let _ = std::fs::remove_file(path);
@@ -426,4 +582,3139 @@ mod tests {
 
         fs::remove_file(path).ok();
     }
+
+    #[test]
+    fn test_zstd_compression_level() {
+        let path = "test_zstd_level.bindl";
+        let _ = fs::remove_file(path);
+        let data = vec![b'B'; 4096];
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+
+        // Out of range levels are rejected
+        assert!(b.writer_with_level("bad.bin", Compress::Zstd, 0).is_err());
+        assert!(b.writer_with_level("bad.bin", Compress::Zstd, 23).is_err());
+
+        let mut w = b
+            .writer_with_level("high.bin", Compress::Zstd, 19)
+            .expect("Failed to start writer");
+        w.write_all(&data).unwrap();
+        w.close().unwrap();
+        b.save().unwrap();
+
+        let b2 = Bindle::open(path).expect("Failed to reopen");
+        assert!(!b2.exists("bad.bin"));
+        assert_eq!(b2.read("high.bin").unwrap().as_ref(), data.as_slice());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_gzip_compression() {
+        let path = "test_gzip.bindl";
+        let _ = fs::remove_file(path);
+        let data = vec![b'G'; 3000];
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("gzip.bin", &data, Compress::Gzip).unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let entry = b.index().get("gzip.bin").unwrap();
+        assert_eq!(entry.compression_type(), Compress::Gzip);
+        assert!(entry.compressed_size() < data.len() as u64);
+
+        assert_eq!(b.read("gzip.bin").unwrap().as_ref(), data.as_slice());
+
+        let mut output = Vec::new();
+        b.read_to("gzip.bin", &mut output).unwrap();
+        assert_eq!(output, data);
+
+        let mut buffer = vec![0u8; data.len()];
+        assert_eq!(b.read_into("gzip.bin", &mut buffer).unwrap(), data.len());
+        assert_eq!(buffer, data);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_seekable_zstd() {
+        let path = "test_seekable.bindl";
+        let _ = fs::remove_file(path);
+        let data: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            let mut w = b.seekable_writer("seek.bin", 4096).unwrap();
+            w.write_all(&data).unwrap();
+            w.close().unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert!(b.index().get("seek.bin").unwrap().is_seekable());
+
+        // Sequential reads still decode every frame and verify
+        assert_eq!(b.read("seek.bin").unwrap().as_ref(), data.as_slice());
+
+        let mut reader = b.reader("seek.bin").unwrap();
+        for pos in [0u64, 4095, 4096, 12345, 39_990] {
+            reader.seek(SeekFrom::Start(pos)).unwrap();
+            let mut buf = [0u8; 10];
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(&buf, &data[pos as usize..pos as usize + 10]);
+        }
+
+        reader.seek(SeekFrom::End(-4)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &data[data.len() - 4..]);
+
+        // Regular zstd entries still refuse to seek
+        let mut b = b;
+        b.add("plain.bin", &data, Compress::Zstd).unwrap();
+        b.save().unwrap();
+        assert!(
+            b.reader("plain.bin")
+                .unwrap()
+                .seek(SeekFrom::Start(1))
+                .is_err()
+        );
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_contents_iterator() {
+        let path = "test_entries.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("a.txt", b"first", Compress::None).unwrap();
+            b.add("b.txt", &vec![b'b'; 3000], Compress::Zstd).unwrap();
+            b.add("c.txt", b"third", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let items: Vec<_> = b.contents(false).map(|item| item.unwrap()).collect();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].0, "a.txt");
+        assert_eq!(items[0].1.as_ref(), b"first");
+        assert_eq!(items[1].1.as_ref(), vec![b'b'; 3000].as_slice());
+        assert_eq!(items[2].0, "c.txt");
+        drop(b);
+
+        // Corrupt the first entry's data
+        {
+            let mut file = OpenOptions::new().write(true).open(path).unwrap();
+            file.seek(SeekFrom::Start(HEADER_SIZE as u64)).unwrap();
+            file.write_all(b"X").unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let results: Vec<_> = b.contents(false).collect();
+        assert!(results[0].is_err());
+        assert!(results[1].is_ok());
+
+        let names: Vec<_> = b.contents(true).map(|item| item.unwrap().0).collect();
+        assert_eq!(names, ["b.txt", "c.txt"]);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_typed_errors() {
+        let path = "test_typed_errors.bindl";
+        fs::write(path, [b'Z'; 64]).unwrap();
+
+        let err = Bindle::open(path).err().expect("Open should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let inner = err.get_ref().and_then(|e| e.downcast_ref::<BindleError>());
+        assert!(matches!(inner, Some(BindleError::InvalidMagic)));
+
+        // Archives from a newer format version are rejected, not misparsed
+        let mut b = Bindle::create(path).unwrap();
+        b.add("a.txt", b"a", Compress::None).unwrap();
+        b.save().unwrap();
+        drop(b);
+        let mut bytes = fs::read(path).unwrap();
+        bytes[..HEADER_SIZE].copy_from_slice(b"BINDL005");
+        fs::write(path, &bytes).unwrap();
+        let err = Bindle::open(path).err().expect("Open should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        let inner = err.get_ref().and_then(|e| e.downcast_ref::<BindleError>());
+        assert!(matches!(inner, Some(BindleError::UnsupportedVersion(5))));
+        assert!(Bindle::from_bytes(&bytes).is_err());
+
+        fs::remove_file(path).unwrap();
+        let b = Bindle::open(path).expect("Failed to create");
+        match b.read("missing.txt") {
+            Err(BindleError::NotFound(name)) => assert_eq!(name, "missing.txt"),
+            _ => panic!("Expected NotFound"),
+        }
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_try_read_reports_corruption() {
+        let path = "test_try_read.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("data.txt", b"Some stored bytes", Compress::None)
+            .unwrap();
+        b.save().unwrap();
+
+        assert_eq!(
+            b.try_read("data.txt").unwrap().unwrap().as_ref(),
+            b"Some stored bytes"
+        );
+        assert!(b.try_read("missing.txt").unwrap().is_none());
+
+        // Flip a byte of the stored entry, the shared mmap sees the change
+        {
+            let mut file = OpenOptions::new().write(true).open(path).unwrap();
+            file.seek(SeekFrom::Start(HEADER_SIZE as u64 + 2)).unwrap();
+            file.write_all(b"!").unwrap();
+        }
+
+        let err = b.try_read("data.txt").expect_err("Expected CRC error");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_verify() {
+        let path = "test_verify.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("a.txt", b"Entry A", Compress::None).unwrap();
+            b.add("b.txt", b"Entry B", Compress::None).unwrap();
+            b.add("c.bin", &vec![b'c'; 4000], Compress::Zstd).unwrap();
+            b.save().unwrap();
+            assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+        }
+
+        // Corrupt both uncompressed entries, b.txt starts at the next 8-byte boundary
+        {
+            let mut file = OpenOptions::new().write(true).open(path).unwrap();
+            file.seek(SeekFrom::Start(HEADER_SIZE as u64)).unwrap();
+            file.write_all(b"X").unwrap();
+            file.seek(SeekFrom::Start(HEADER_SIZE as u64 + 8)).unwrap();
+            file.write_all(b"X").unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let report = b.verify().unwrap();
+        let failed: Vec<_> = report
+            .iter()
+            .filter(|(_, ok)| !ok)
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(failed, ["a.txt", "b.txt"]);
+        assert_eq!(report.len(), 3);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_entries_and_names() {
+        let path = "test_entries_names.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("b.txt", b"bb", Compress::None).unwrap();
+        b.add("a.txt", b"a", Compress::None).unwrap();
+        b.add("c.bin", &vec![0u8; 4000], Compress::Zstd).unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.names().collect::<Vec<_>>(), ["a.txt", "b.txt", "c.bin"]);
+
+        let sizes: Vec<_> = b
+            .entries()
+            .map(|(name, entry)| (name, entry.uncompressed_size()))
+            .collect();
+        assert_eq!(sizes, [("a.txt", 1), ("b.txt", 2), ("c.bin", 4000)]);
+
+        let (_, compressed) = b.entries().find(|(name, _)| *name == "c.bin").unwrap();
+        assert!(compressed.ratio() < 0.1);
+        let (_, raw) = b.entries().next().unwrap();
+        assert_eq!(raw.ratio(), 1.0);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_add_reader() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("source failed"))
+            }
+        }
+
+        let path = "test_add_reader.bindl";
+        let _ = fs::remove_file(path);
+        let data = vec![b'r'; 10_000];
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            let mut src = std::io::Cursor::new(&data);
+            let n = b
+                .add_reader("streamed.bin", &mut src, Compress::Zstd)
+                .unwrap();
+            assert_eq!(n, data.len() as u64);
+
+            // A source that fails part way leaves no entry behind
+            let failing = (&data[..5000]).chain(FailingReader);
+            assert!(b.add_reader("failed.bin", failing, Compress::Zstd).is_err());
+            assert!(!b.exists("failed.bin"));
+
+            // Same for files that can't be read, such as a directory
+            let dir = "test_add_reader_dir";
+            fs::create_dir_all(dir).unwrap();
+            assert!(b.add_file("dir.bin", dir, Compress::Zstd).is_err());
+            assert!(!b.exists("dir.bin"));
+            fs::remove_dir_all(dir).ok();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.read("streamed.bin").unwrap().as_ref(), data.as_slice());
+        assert_eq!(b.len(), 1);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let path = "test_from_reader.bindl";
+        let _ = fs::remove_file(path);
+        let compressed = vec![b'c'; 10_000];
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("raw.txt", b"raw data", Compress::None).unwrap();
+            b.add("big.bin", &compressed, Compress::Zstd).unwrap();
+            b.save().unwrap();
+        }
+
+        let bytes = fs::read(path).unwrap();
+        let mut b = Bindle::from_reader(std::io::Cursor::new(bytes)).expect("Failed to parse");
+        assert_eq!(b.len(), 2);
+        assert_eq!(b.read("raw.txt").unwrap(), b"raw data");
+        assert_eq!(b.read("big.bin").unwrap(), compressed);
+        assert!(matches!(b.read("missing"), Err(BindleError::NotFound(_))));
+
+        assert!(Bindle::from_reader(std::io::Cursor::new(b"BINDL001")).is_err());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let compressed = vec![b'm'; 10_000];
+
+        let mut b = Bindle::in_memory();
+        b.add("raw.txt", b"old", Compress::None).unwrap();
+        b.add("raw.txt", b"in memory", Compress::None).unwrap();
+        b.add("big.bin", &compressed, Compress::Zstd).unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.read("raw.txt").unwrap().as_ref(), b"in memory");
+        assert_eq!(b.read("big.bin").unwrap().as_ref(), compressed.as_slice());
+
+        b.vacuum().unwrap();
+        assert_eq!(b.read("raw.txt").unwrap().as_ref(), b"in memory");
+
+        let bytes = b.into_bytes().unwrap();
+        let mut b = Bindle::from_reader(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(b.len(), 2);
+        assert_eq!(b.read("big.bin").unwrap(), compressed);
+    }
+
+    #[test]
+    fn test_unpack_filtered() {
+        let path = "test_unpack_filtered.bindl";
+        let dest = "test_unpack_filtered_out";
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_dir_all(dest);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("assets/textures/a.png", b"a", Compress::None)
+            .unwrap();
+        b.add("assets/sounds/b.wav", b"b", Compress::None).unwrap();
+        b.add("readme.txt", b"c", Compress::None).unwrap();
+        b.save().unwrap();
+
+        b.unpack_filtered(dest, "assets/textures/**").unwrap();
+        assert_eq!(
+            fs::read(format!("{}/assets/textures/a.png", dest)).unwrap(),
+            b"a"
+        );
+        assert!(!std::path::Path::new(&format!("{}/assets/sounds", dest)).exists());
+        assert!(!std::path::Path::new(&format!("{}/readme.txt", dest)).exists());
+
+        assert!(b.unpack_filtered(dest, "[").is_err());
+
+        fs::remove_dir_all(dest).ok();
+        b.unpack_prefix(dest, "assets/s").unwrap();
+        assert_eq!(
+            fs::read(format!("{}/assets/sounds/b.wav", dest)).unwrap(),
+            b"b"
+        );
+        assert!(!std::path::Path::new(&format!("{}/assets/textures", dest)).exists());
+        assert!(!std::path::Path::new(&format!("{}/readme.txt", dest)).exists());
+
+        fs::remove_dir_all(dest).ok();
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_rename() {
+        let path = "test_rename.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("old.txt", b"renamed data", Compress::None).unwrap();
+            b.add("other.txt", b"other", Compress::None).unwrap();
+            b.save().unwrap();
+
+            assert!(!b.rename("missing.txt", "new.txt").unwrap());
+            let err = b.rename("old.txt", "other.txt").unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+            assert!(b.rename("old.txt", "a/much/longer/new_name.txt").unwrap());
+            b.add("replace.txt", b"replacement", Compress::None)
+                .unwrap();
+            assert!(b.rename_overwrite("replace.txt", "other.txt").unwrap());
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert!(!b.exists("old.txt"));
+        assert_eq!(
+            b.read("a/much/longer/new_name.txt").unwrap().as_ref(),
+            b"renamed data"
+        );
+        assert_eq!(b.read("other.txt").unwrap().as_ref(), b"replacement");
+        assert!(!b.exists("replace.txt"));
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_mtime_roundtrip() {
+        let path = "test_mtime.bindl";
+        let src = "test_mtime_src";
+        let dest = "test_mtime_out";
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_dir_all(src);
+        let _ = fs::remove_dir_all(dest);
+
+        let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000_000);
+        fs::create_dir_all(src).unwrap();
+        let f = fs::File::create(format!("{}/a.txt", src)).unwrap();
+        f.set_modified(mtime).unwrap();
+        drop(f);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.pack(src, Compress::None).unwrap();
+            b.add("no_mtime.txt", b"x", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.mtime("a.txt"), Some(mtime));
+        assert_eq!(b.mtime("no_mtime.txt"), None);
+
+        b.unpack(dest).unwrap();
+        let restored = fs::metadata(format!("{}/a.txt", dest)).unwrap();
+        assert_eq!(restored.modified().unwrap(), mtime);
+
+        fs::remove_dir_all(src).ok();
+        fs::remove_dir_all(dest).ok();
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_v1_archive() {
+        let path = "test_v1.bindl";
+        let _ = fs::remove_file(path);
+
+        // Header, one 8-byte blob, a single index record without a metadata block, then the footer
+        let mut bytes = BNDL_MAGIC_V1.to_vec();
+        bytes.extend_from_slice(b"v1 data\0");
+        let mut entry = Entry::default();
+        entry.set_offset(HEADER_SIZE as u64);
+        entry.set_compressed_size(7);
+        entry.set_uncompressed_size(7);
+        entry.set_crc32(crc32fast::hash(b"v1 data"));
+        entry.set_name_len(5);
+        bytes.extend_from_slice(zerocopy::IntoBytes::as_bytes(&entry));
+        bytes.extend_from_slice(b"a.txt\0\0\0");
+        let footer = entry::Footer::new(16, 1, FOOTER_MAGIC);
+        bytes.extend_from_slice(zerocopy::IntoBytes::as_bytes(&footer));
+        fs::write(path, &bytes).unwrap();
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open v1 archive");
+            assert_eq!(b.read("a.txt").unwrap().as_ref(), b"v1 data");
+            b.add("b.txt", b"v2 data", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+
+        assert_eq!(&fs::read(path).unwrap()[..HEADER_SIZE], BNDL_MAGIC);
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"v1 data");
+        assert_eq!(b.read("b.txt").unwrap().as_ref(), b"v2 data");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_index_checksum() {
+        let path = "test_index_checksum.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("a.txt", b"checked", Compress::None).unwrap();
+            b.save().unwrap();
+            assert_eq!(b.version(), FORMAT_VERSION);
+        }
+
+        // Flip a bit in the entry name, the first index record starts at the 8-byte aligned data end
+        {
+            let mut file = OpenOptions::new().write(true).open(path).unwrap();
+            file.seek(SeekFrom::Start((HEADER_SIZE + 8 + ENTRY_SIZE) as u64))
+                .unwrap();
+            file.write_all(b"b").unwrap();
+        }
+
+        let err = Bindle::open(path)
+            .err()
+            .expect("Corrupt index should fail to open");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let bytes = fs::read(path).unwrap();
+        assert!(Bindle::from_reader(std::io::Cursor::new(bytes)).is_err());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_open_readonly() {
+        let path = "test_readonly.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add("a.txt", b"shared", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+
+        let mut first = Bindle::open_readonly(path).expect("Failed to open read-only");
+        let second = Bindle::open_readonly(path).expect("Failed to open read-only twice");
+        assert_eq!(first.read("a.txt").unwrap().as_ref(), b"shared");
+        assert_eq!(second.read("a.txt").unwrap().as_ref(), b"shared");
+
+        let denied = std::io::ErrorKind::PermissionDenied;
+        assert_eq!(
+            first.add("b.txt", b"x", Compress::None).unwrap_err().kind(),
+            denied
+        );
+        assert_eq!(first.save().unwrap_err().kind(), denied);
+        assert_eq!(first.vacuum().unwrap_err().kind(), denied);
+        assert_eq!(first.rename("a.txt", "b.txt").unwrap_err().kind(), denied);
+        assert_eq!(
+            first.rename_overwrite("a.txt", "b.txt").unwrap_err().kind(),
+            denied
+        );
+        assert!(first.exists("a.txt"));
+        assert!(!first.exists("b.txt"));
+
+        fs::remove_file(path).ok();
+        assert!(Bindle::open_readonly(path).is_err());
+    }
+
+    #[test]
+    fn test_dead_space() {
+        let path = "test_dead_space.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        assert_eq!(b.dead_space(), 0);
+        assert_eq!(b.fragmentation_ratio(), 0.0);
+
+        b.add("a.txt", b"12345", Compress::None).unwrap(); // 8 bytes with padding
+        b.add("b.txt", &[0u8; 16], Compress::None).unwrap();
+        b.add("c.txt", b"123", Compress::None).unwrap();
+        b.save().unwrap();
+        assert_eq!(b.dead_space(), 0);
+
+        // The next write starts after the committed index and footer, which become dead space
+        let old_index = fs::metadata(path).unwrap().len() - HEADER_SIZE as u64 - 32;
+
+        // Shadowing a.txt leaves its first 8 bytes behind, removing b.txt leaves 16
+        b.add("a.txt", b"updated", Compress::None).unwrap();
+        b.remove("b.txt");
+        b.save().unwrap();
+        let dead = 24 + old_index;
+        assert_eq!(b.dead_space(), dead);
+        assert_eq!(b.live_data_bytes(), 7 + 3);
+        assert_eq!(b.committed_len(), fs::metadata(path).unwrap().len());
+        assert_eq!(
+            b.fragmentation_ratio(),
+            dead as f64 / (b.index_offset() - HEADER_SIZE as u64) as f64
+        );
+
+        b.vacuum().unwrap();
+        assert_eq!(b.dead_space(), 0);
+
+        // Data shared by deduplicated entries is live once, uncommitted entries count right away
+        let committed = b.committed_len();
+        b.set_dedup(true);
+        b.add("d.txt", b"shared", Compress::None).unwrap();
+        b.add("e.txt", b"shared", Compress::None).unwrap();
+        assert_eq!(b.live_data_bytes(), 7 + 3 + 6);
+        assert_eq!(b.committed_len(), committed);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_pack_parallel() {
+        let path = "test_pack_parallel.bindl";
+        let src = "test_pack_parallel_src";
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_dir_all(src);
+
+        fs::create_dir_all(format!("{}/nested", src)).unwrap();
+        for i in 0..20 {
+            let data = format!("file {} ", i).repeat(500);
+            fs::write(format!("{}/nested/{}.txt", src, i), data).unwrap();
+        }
+        fs::write(format!("{}/small.txt", src), b"tiny").unwrap();
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            let err = b.pack_parallel(src, Compress::Zstd, 0, 4).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            b.pack_parallel(src, Compress::Auto, 3, 4).unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.len(), 21);
+        for i in 0..20 {
+            let name = format!("nested/{}.txt", i);
+            let data = b.read(&name).unwrap();
+            assert_eq!(data.as_ref(), format!("file {} ", i).repeat(500).as_bytes());
+            assert_eq!(b.index()[&name].compression_type(), Compress::Zstd);
+            assert!(b.mtime(&name).is_some());
+        }
+        assert_eq!(b.read("small.txt").unwrap().as_ref(), b"tiny");
+        assert_eq!(b.index()["small.txt"].compression_type(), Compress::None);
+        assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+
+        fs::remove_dir_all(src).ok();
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_append_while_reading() {
+        let path = "test_append_while_reading.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut writer = Bindle::open(path).expect("Failed to open");
+        writer
+            .add("first.txt", b"committed", Compress::None)
+            .unwrap();
+        writer.save().unwrap();
+
+        // Open readers don't hold a lock, and appends don't touch the committed index
+        let reader = Bindle::open_readonly(path).expect("Failed to open reader");
+        writer
+            .add("second.txt", b"pending", Compress::Zstd)
+            .unwrap();
+        assert_eq!(reader.read("first.txt").unwrap().as_ref(), b"committed");
+
+        // Pending appends don't lock the file, readers opening now see the last commit
+        let during = Bindle::open_readonly(path).expect("Failed to open during append");
+        assert_eq!(during.names().collect::<Vec<_>>(), ["first.txt"]);
+        assert_eq!(during.read("first.txt").unwrap().as_ref(), b"committed");
+
+        writer.save().unwrap();
+        assert_eq!(reader.read("first.txt").unwrap().as_ref(), b"committed");
+        assert!(!reader.exists("second.txt"));
+
+        let after = Bindle::open_readonly(path).expect("Failed to open after commit");
+        assert_eq!(after.read("second.txt").unwrap().as_ref(), b"pending");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_auto_vacuum() {
+        let path = "test_auto_vacuum.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("big.bin", &[1u8; 4096], Compress::None).unwrap();
+        b.add("keep.txt", b"keep", Compress::None).unwrap();
+        b.save().unwrap();
+
+        // Disabled by default, removed data stays in the file
+        b.remove("big.bin");
+        b.save().unwrap();
+        assert!(b.dead_space() >= 4096);
+
+        for ratio in [-0.1, 1.5, f64::NAN] {
+            let err = b.set_auto_vacuum(Some(ratio)).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        b.save().unwrap();
+        assert!(b.dead_space() >= 4096);
+
+        b.set_auto_vacuum(Some(0.5)).unwrap();
+        b.add("small.txt", b"small", Compress::None).unwrap();
+        b.save().unwrap();
+        assert_eq!(b.dead_space(), 0);
+        assert!(fs::metadata(path).unwrap().len() < 4096);
+        assert_eq!(b.read("keep.txt").unwrap().as_ref(), b"keep");
+        assert_eq!(b.read("small.txt").unwrap().as_ref(), b"small");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_copy_entry_from() {
+        let src_path = "test_copy_src.bindl";
+        let dest_path = "test_copy_dest.bindl";
+        let _ = fs::remove_file(src_path);
+        let _ = fs::remove_file(dest_path);
+        let data = vec![b'z'; 10_000];
+
+        let mut src = Bindle::open(src_path).expect("Failed to open source");
+        src.add("packed.bin", &data, Compress::Zstd).unwrap();
+        src.add("raw.txt", b"raw", Compress::None).unwrap();
+        src.save().unwrap();
+
+        {
+            let mut dest = Bindle::open(dest_path).expect("Failed to open destination");
+            dest.add("raw.txt", b"shadowed", Compress::None).unwrap();
+            dest.copy_entry_from(&src, "packed.bin").unwrap();
+            dest.copy_entry_from(&src, "raw.txt").unwrap();
+            let err = dest.copy_entry_from(&src, "missing").unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+            dest.save().unwrap();
+        }
+
+        let dest = Bindle::open(dest_path).expect("Failed to reopen");
+        let entry = dest.index()["packed.bin"];
+        assert_eq!(entry.compression_type(), Compress::Zstd);
+        assert_eq!(
+            entry.compressed_size(),
+            src.index()["packed.bin"].compressed_size()
+        );
+        assert_eq!(dest.read("packed.bin").unwrap().as_ref(), data.as_slice());
+        assert_eq!(dest.read("raw.txt").unwrap().as_ref(), b"raw");
+
+        fs::remove_file(src_path).ok();
+        fs::remove_file(dest_path).ok();
+    }
+
+    #[test]
+    fn test_read_range() {
+        let data: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+        let mut b = Bindle::in_memory();
+        b.add("raw.bin", &data, Compress::None).unwrap();
+        b.add("packed.bin", &data, Compress::Zstd).unwrap();
+        let mut w = b.seekable_writer("seek.bin", 4096).unwrap();
+        w.write_all(&data).unwrap();
+        w.close().unwrap();
+        b.save().unwrap();
+
+        let raw = b.read_range("raw.bin", 100, 200).unwrap();
+        assert!(matches!(raw, Cow::Borrowed(_)));
+        assert_eq!(raw.as_ref(), &data[100..200]);
+
+        for name in ["packed.bin", "seek.bin"] {
+            let range = b.read_range(name, 12345, 12400).unwrap();
+            assert_eq!(range.as_ref(), &data[12345..12400]);
+        }
+
+        // The end is clamped to the entry size
+        let tail = b.read_range("packed.bin", 39_990, 50_000).unwrap();
+        assert_eq!(tail.as_ref(), &data[39_990..]);
+        assert!(b.read_range("raw.bin", 40_000, 50_000).unwrap().is_empty());
+
+        assert!(b.read_range("raw.bin", 50_000, 60_000).is_none());
+        assert!(b.read_range("raw.bin", 200, 100).is_none());
+        assert!(b.read_range("missing", 0, 10).is_none());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = Bindle::in_memory();
+        a.add("shared.txt", b"from a", Compress::None).unwrap();
+        a.add("a.txt", b"only a", Compress::None).unwrap();
+
+        let mut other = Bindle::in_memory();
+        other
+            .add("shared.txt", b"from other", Compress::None)
+            .unwrap();
+        other
+            .add("big.bin", &[b'x'; 10_000], Compress::Zstd)
+            .unwrap();
+        other.save().unwrap();
+
+        let err = a.merge(&other, Conflict::Error).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(!a.exists("big.bin"));
+
+        assert_eq!(a.merge(&other, Conflict::Skip).unwrap(), 1);
+        assert_eq!(a.read("shared.txt").unwrap().as_ref(), b"from a");
+        assert_eq!(a.merge(&other, Conflict::Overwrite).unwrap(), 2);
+        a.save().unwrap();
+
+        let mut a = Bindle::from_reader(std::io::Cursor::new(a.into_bytes().unwrap())).unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.read("shared.txt").unwrap(), b"from other");
+        assert_eq!(a.read("a.txt").unwrap(), b"only a");
+        assert_eq!(a.read("big.bin").unwrap(), vec![b'x'; 10_000]);
+    }
+
+    #[test]
+    fn test_entry_meta() {
+        let path = "test_entry_meta.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open(path).expect("Failed to open");
+            b.add_with_meta("page.html", b"<html>", Compress::None, b"text/html")
+                .unwrap();
+            b.add_with_meta("empty.bin", b"", Compress::None, b"")
+                .unwrap();
+            b.add("plain.txt", b"plain", Compress::None).unwrap();
+            let err = b
+                .add_with_meta("big.bin", b"", Compress::None, &vec![0; 70_000])
+                .unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            b.save().unwrap();
+        }
+
+        let mut b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.meta("page.html"), Some(&b"text/html"[..]));
+        assert_eq!(b.meta("empty.bin"), Some(&b""[..]));
+        assert_eq!(b.meta("plain.txt"), None);
+        assert_eq!(b.read("page.html").unwrap().as_ref(), b"<html>");
+
+        let bytes = fs::read(path).unwrap();
+        let r = Bindle::from_reader(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(r.meta("page.html"), Some(&b"text/html"[..]));
+
+        // Shadowing an entry drops its metadata
+        b.add("page.html", b"<html></html>", Compress::None)
+            .unwrap();
+        assert_eq!(b.meta("page.html"), None);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_pack_exclude() {
+        let src_dir = "test_pack_exclude_src";
+        let _ = fs::remove_dir_all(src_dir);
+        fs::create_dir_all(format!("{}/.git/objects", src_dir)).unwrap();
+        fs::create_dir_all(format!("{}/docs/node_modules", src_dir)).unwrap();
+        fs::write(format!("{}/.git/objects/a", src_dir), b"git").unwrap();
+        fs::write(format!("{}/docs/node_modules/b.js", src_dir), b"js").unwrap();
+        fs::write(format!("{}/docs/readme.md", src_dir), b"readme").unwrap();
+        fs::write(format!("{}/docs/scratch.tmp", src_dir), b"tmp").unwrap();
+        fs::write(format!("{}/main.rs", src_dir), b"main").unwrap();
+
+        let options = PackOptions::new()
+            .exclude([".git", "node_modules", "*.tmp"])
+            .unwrap();
+        let mut b = Bindle::in_memory();
+        b.pack_with_options(src_dir, Compress::None, 3, &options)
+            .unwrap();
+        assert_eq!(b.names().collect::<Vec<_>>(), ["docs/readme.md", "main.rs"]);
+
+        assert!(PackOptions::new().exclude(["["]).is_err());
+
+        fs::remove_dir_all(src_dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pack_symlinks() {
+        let src_dir = "test_pack_symlinks_src";
+        let out_dir = "test_pack_symlinks_out";
+        let _ = fs::remove_dir_all(src_dir);
+        let _ = fs::remove_dir_all(out_dir);
+        fs::create_dir_all(format!("{}/dir", src_dir)).unwrap();
+        fs::write(format!("{}/dir/file.txt", src_dir), b"data").unwrap();
+        std::os::unix::fs::symlink("file.txt", format!("{}/dir/link.txt", src_dir)).unwrap();
+        // Points back at an ancestor, following it must not recurse forever
+        std::os::unix::fs::symlink("..", format!("{}/dir/up", src_dir)).unwrap();
+
+        let mut b = Bindle::in_memory();
+        b.pack(src_dir, Compress::None).unwrap();
+        assert_eq!(
+            b.names().collect::<Vec<_>>(),
+            ["dir/file.txt", "dir/link.txt"]
+        );
+        assert!(!b.index()["dir/link.txt"].is_symlink());
+
+        let mut b = Bindle::in_memory();
+        let options = PackOptions::new().symlinks(Symlinks::Store);
+        b.pack_with_options(src_dir, Compress::Zstd, 3, &options)
+            .unwrap();
+        assert!(b.index()["dir/link.txt"].is_symlink());
+        assert!(b.index()["dir/up"].is_symlink());
+        assert_eq!(b.read("dir/link.txt").unwrap().as_ref(), b"file.txt");
+
+        let mut fallbacks = 0;
+        b.unpack_with_progress(out_dir, |e| fallbacks += e.link_as_file as usize)
+            .unwrap();
+        assert_eq!(fallbacks, 0);
+        let link = format!("{}/dir/link.txt", out_dir);
+        assert_eq!(
+            fs::read_link(&link).unwrap(),
+            std::path::Path::new("file.txt")
+        );
+        assert_eq!(fs::read(&link).unwrap(), b"data");
+
+        // Entries stored below a link would be written wherever it points
+        fs::remove_dir_all(out_dir).ok();
+        b.add("dir/up/escaped.txt", b"x", Compress::None).unwrap();
+        assert!(b.unpack(out_dir).is_err());
+        assert!(fs::metadata(out_dir).unwrap().is_dir());
+        assert!(fs::read_dir(out_dir).unwrap().next().is_none());
+
+        let mut b = Bindle::in_memory();
+        let options = PackOptions::new().symlinks(Symlinks::Skip);
+        b.pack_with_options(src_dir, Compress::None, 3, &options)
+            .unwrap();
+        assert_eq!(b.names().collect::<Vec<_>>(), ["dir/file.txt"]);
+
+        fs::remove_dir_all(src_dir).ok();
+        fs::remove_dir_all(out_dir).ok();
+    }
+
+    #[test]
+    fn test_sha256() {
+        use sha2::{Digest, Sha256};
+        let data = vec![b's'; 10_000];
+        let hash: [u8; 32] = Sha256::digest(&data).into();
+
+        let mut b = Bindle::in_memory();
+        b.add("unhashed.bin", &data, Compress::None).unwrap();
+        b.set_sha256(true);
+        b.add("hashed.bin", &data, Compress::Zstd).unwrap();
+        b.add_with_meta("tagged.bin", b"other", Compress::None, b"tag")
+            .unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.sha256("unhashed.bin"), None);
+        assert_eq!(b.sha256("hashed.bin"), Some(hash));
+        assert_eq!(b.meta("tagged.bin"), Some(&b"tag"[..]));
+        assert_eq!(b.find_by_hash(&hash), Some("hashed.bin"));
+        assert_eq!(b.find_by_hash(&[0; 32]), None);
+
+        let r = Bindle::from_reader(std::io::Cursor::new(b.into_bytes().unwrap())).unwrap();
+        assert_eq!(r.sha256("hashed.bin"), Some(hash));
+        assert!(r.sha256("tagged.bin").is_some());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let data = vec![b'd'; 10_000];
+
+        let mut b = Bindle::in_memory();
+        b.set_dedup(true);
+        b.add("a.bin", &data, Compress::Zstd).unwrap();
+        let end = b.index_offset();
+        b.add("b.bin", &data, Compress::Zstd).unwrap();
+        assert_eq!(b.index_offset(), end);
+        assert_eq!(b.index()["a.bin"].offset(), b.index()["b.bin"].offset());
+
+        // Different compression isn't shared
+        b.add("c.bin", &data, Compress::None).unwrap();
+        assert!(b.index_offset() > end);
+        b.save().unwrap();
+        assert_eq!(b.dead_space(), 0);
+
+        b.remove("a.bin");
+        b.vacuum().unwrap();
+        assert_eq!(b.read("b.bin").unwrap().as_ref(), data.as_slice());
+        assert_eq!(b.read("c.bin").unwrap().as_ref(), data.as_slice());
+
+        // Shared data is copied once by vacuum and survives reopening
+        b.add("d.bin", &data, Compress::Zstd).unwrap();
+        b.save().unwrap();
+        b.vacuum().unwrap();
+        assert_eq!(b.index()["b.bin"].offset(), b.index()["d.bin"].offset());
+        assert_eq!(b.dead_space(), 0);
+
+        let mut r = Bindle::from_reader(std::io::Cursor::new(b.into_bytes().unwrap())).unwrap();
+        assert_eq!(r.read("d.bin").unwrap(), data);
+    }
+
+    #[test]
+    fn test_add_dedup() {
+        let data = vec![b'i'; 4096];
+
+        let mut b = Bindle::in_memory();
+        b.add("plain.bin", &data, Compress::Zstd).unwrap();
+        b.add_dedup("first.bin", &data, Compress::Zstd).unwrap();
+        let end = b.index_offset();
+        b.add_dedup("second.bin", &data, Compress::Zstd).unwrap();
+        assert_eq!(b.index_offset(), end);
+        assert_eq!(
+            b.index()["first.bin"].offset(),
+            b.index()["second.bin"].offset()
+        );
+        assert_ne!(
+            b.index()["plain.bin"].offset(),
+            b.index()["first.bin"].offset()
+        );
+        assert_eq!(b.read("second.bin").unwrap().as_ref(), data.as_slice());
+
+        // Shadowing replaces the metadata, whether or not the data is shared
+        for (name, content) in [("second.bin", &data[..]), ("unique.bin", b"new")] {
+            b.add_with_meta(name, b"old", Compress::None, b"text/plain")
+                .unwrap();
+            b.set_mtime(name, std::time::UNIX_EPOCH);
+            b.add_dedup(name, content, Compress::Zstd).unwrap();
+            assert_eq!(b.meta(name), None);
+            assert_eq!(b.mtime(name), None);
+            assert!(b.sha256(name).is_some());
+            assert_eq!(b.read(name).unwrap().as_ref(), content);
+        }
+
+        let src_dir = "test_pack_dedup_src";
+        let _ = fs::remove_dir_all(src_dir);
+        fs::create_dir_all(format!("{}/a", src_dir)).unwrap();
+        fs::create_dir_all(format!("{}/b", src_dir)).unwrap();
+        fs::write(format!("{}/a/__init__.py", src_dir), b"").unwrap();
+        fs::write(format!("{}/b/__init__.py", src_dir), b"").unwrap();
+        fs::write(format!("{}/a/icon.png", src_dir), &data).unwrap();
+        fs::write(format!("{}/b/icon.png", src_dir), &data).unwrap();
+
+        let mut b = Bindle::in_memory();
+        let options = PackOptions::new().dedup(true);
+        b.pack_with_options(src_dir, Compress::None, 3, &options)
+            .unwrap();
+        assert_eq!(
+            b.index()["a/icon.png"].offset(),
+            b.index()["b/icon.png"].offset()
+        );
+        assert_eq!(b.index_offset(), (HEADER_SIZE + data.len()) as u64);
+
+        fs::remove_dir_all(src_dir).ok();
+    }
+
+    #[test]
+    fn test_append_writer() {
+        let path = "test_append_writer.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add_with_meta("log.txt", b"one\n", Compress::None, b"text/plain")
+            .unwrap();
+        b.add("log.zst", b"one\n", Compress::Zstd).unwrap();
+        b.save().unwrap();
+
+        for line in [&b"two\n"[..], b"three\n", b"four\n"] {
+            for name in ["log.txt", "log.zst"] {
+                let mut w = b.append_writer(name).unwrap();
+                w.write_all(line).unwrap();
+                w.close().unwrap();
+            }
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let expected = b"one\ntwo\nthree\nfour\n";
+        assert_eq!(b.read("log.txt").unwrap().as_ref(), expected);
+        assert_eq!(b.read("log.zst").unwrap().as_ref(), expected);
+        assert_eq!(b.index()["log.zst"].compression_type(), Compress::Zstd);
+        assert!(b.dead_space() > 0);
+        assert_eq!(b.meta("log.txt"), None);
+
+        let mut b = b;
+        let err = b.append_writer("missing").err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_read_to_with_progress() {
+        let path = "test_read_to_with_progress.bindl";
+        let _ = fs::remove_file(path);
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("raw.bin", &data, Compress::None).unwrap();
+        b.add("zstd.bin", &data, Compress::Zstd).unwrap();
+        b.save().unwrap();
+
+        for name in ["raw.bin", "zstd.bin"] {
+            let mut out = Vec::new();
+            let mut calls = Vec::new();
+            let n = b
+                .read_to_with_progress(name, &mut out, 4096, |done| calls.push(done))
+                .unwrap();
+            assert_eq!(n, data.len() as u64);
+            assert_eq!(out, data);
+            assert!(calls.windows(2).all(|w| w[0] < w[1]));
+            assert!(calls.windows(2).all(|w| w[1] - w[0] <= 4096));
+            assert_eq!(calls.last(), Some(&(data.len() as u64)));
+        }
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_auto_compress_sampling() {
+        let path = "test_auto_compress_sampling.bindl";
+        let _ = fs::remove_file(path);
+
+        // xorshift noise stands in for already compressed data
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        let noise: Vec<u8> = (0..5000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect();
+        let text = b"compressible ".repeat(400);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("small.txt", &text[..1000], Compress::Auto).unwrap();
+        b.add("noise.bin", &noise, Compress::Auto).unwrap();
+        b.add("text.txt", &text, Compress::Auto).unwrap();
+
+        // Streaming in small chunks buffers the sample before picking a codec
+        let mut w = b.writer("streamed.txt", Compress::Auto).unwrap();
+        for chunk in text.chunks(100) {
+            w.write_all(chunk).unwrap();
+        }
+        w.close().unwrap();
+        let mut w = b.writer("short.txt", Compress::Auto).unwrap();
+        w.write_all(&text[..3000]).unwrap();
+        w.close().unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.compression_of("small.txt"), Some(Compress::None));
+        assert_eq!(b.compression_of("noise.bin"), Some(Compress::None));
+        assert_eq!(b.compression_of("text.txt"), Some(Compress::Zstd));
+        assert_eq!(b.compression_of("streamed.txt"), Some(Compress::Zstd));
+        assert_eq!(b.compression_of("short.txt"), Some(Compress::Zstd));
+        assert_eq!(b.read("noise.bin").unwrap().as_ref(), noise.as_slice());
+        assert_eq!(b.read("streamed.txt").unwrap().as_ref(), text.as_slice());
+        assert_eq!(b.read("short.txt").unwrap().as_ref(), &text[..3000]);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_commit() {
+        let path = "test_commit.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("first.txt", b"first", Compress::None).unwrap();
+        b.commit().unwrap();
+        assert_eq!(b.read("first.txt").unwrap().as_ref(), b"first");
+
+        for i in 0..10 {
+            let name = format!("entry-{}.txt", i);
+            b.add(&name, name.as_bytes(), Compress::Zstd).unwrap();
+            b.commit().unwrap();
+            assert_eq!(b.read(&name).unwrap().as_ref(), name.as_bytes());
+        }
+        assert_eq!(b.read("first.txt").unwrap().as_ref(), b"first");
+
+        // A reader opened after the commit sees everything
+        let reopened = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(reopened.len(), 11);
+        assert_eq!(
+            reopened.read("entry-9.txt").unwrap().as_ref(),
+            b"entry-9.txt"
+        );
+
+        b.save().unwrap();
+        assert_eq!(b.read("entry-0.txt").unwrap().as_ref(), b"entry-0.txt");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_add_many() {
+        let single = "test_add_many_single.bindl";
+        let batch = "test_add_many_batch.bindl";
+        let _ = fs::remove_file(single);
+        let _ = fs::remove_file(batch);
+
+        let text = b"batched ".repeat(1000);
+        let items: Vec<(&str, &[u8], Compress)> = vec![
+            ("a.txt", b"a", Compress::None),
+            ("b.txt", &text, Compress::Zstd),
+            ("c.txt", &text, Compress::Auto),
+            ("d.txt", b"odd length", Compress::Gzip),
+            ("a.txt", b"shadowed", Compress::None),
+        ];
+
+        let mut b = Bindle::open(single).expect("Failed to open");
+        for (name, data, compress) in &items {
+            b.add(name, data, *compress).unwrap();
+        }
+        b.save().unwrap();
+
+        let mut b = Bindle::open(batch).expect("Failed to open");
+        b.add_many(items.iter().copied()).unwrap();
+        b.save().unwrap();
+        assert_eq!(b.len(), 4);
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"shadowed");
+        assert_eq!(b.read("b.txt").unwrap().as_ref(), text.as_slice());
+
+        // Byte for byte the same archive as adding one at a time
+        assert_eq!(fs::read(single).unwrap(), fs::read(batch).unwrap());
+
+        // Owned items work the same way
+        let owned: Vec<(String, Vec<u8>, Compress)> = items
+            .iter()
+            .map(|(name, data, compress)| (name.to_string(), data.to_vec(), *compress))
+            .collect();
+        let mut b = Bindle::open(batch).expect("Failed to open");
+        b.clear();
+        b.add_many(owned).unwrap();
+        b.save().unwrap();
+        assert_eq!(b.read("d.txt").unwrap().as_ref(), b"odd length");
+
+        fs::remove_file(single).ok();
+        fs::remove_file(batch).ok();
+    }
+
+    #[test]
+    fn test_autosave() {
+        let path = "test_autosave.bindl";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut b = Bindle::open_with(path, Options::new().autosave(true)).unwrap();
+            b.add("saved.txt", b"saved on drop", Compress::None)
+                .unwrap();
+        }
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.read("saved.txt").unwrap().as_ref(), b"saved on drop");
+        drop(b);
+
+        // A clean archive isn't rewritten
+        let len = fs::metadata(path).unwrap().len();
+        drop(Bindle::open_with(path, Options::new().autosave(true)).unwrap());
+        assert_eq!(fs::metadata(path).unwrap().len(), len);
+
+        {
+            let mut b = Bindle::open_with(path, Options::new().autosave(true)).unwrap();
+            assert!(b.remove("saved.txt"));
+        }
+        let b = Bindle::open(path).expect("Failed to reopen");
+        assert!(b.is_empty());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_names_with_prefix() {
+        let mut b = Bindle::in_memory();
+        for name in ["a", "a/b", "a/c", "ab", "b/a", "a/d/e"] {
+            b.add(name, name.as_bytes(), Compress::None).unwrap();
+        }
+
+        let names: Vec<_> = b.names_with_prefix("a/").collect();
+        assert_eq!(names, ["a/b", "a/c", "a/d/e"]);
+        let names: Vec<_> = b.names_with_prefix("a").collect();
+        assert_eq!(names, ["a", "a/b", "a/c", "a/d/e", "ab"]);
+        assert_eq!(b.names_with_prefix("").count(), 6);
+        assert_eq!(b.names_with_prefix("c").count(), 0);
+
+        let entries: Vec<_> = b.entries_with_prefix("a/").collect();
+        assert_eq!(
+            entries.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            ["a/b", "a/c", "a/d/e"]
+        );
+        for (name, entry) in entries {
+            assert_eq!(entry.offset(), b.index()[name].offset());
+        }
+        assert_eq!(b.entries_with_prefix("b/a/").count(), 0);
+
+        assert!(b.contains_prefix("b/"));
+        assert!(b.contains_prefix("a/d"));
+        assert!(!b.contains_prefix("a/e"));
+        assert!(!b.contains_prefix("z"));
+    }
+
+    #[test]
+    fn test_interrupted_save() {
+        let path = "test_interrupted_save.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("first.txt", b"first", Compress::None).unwrap();
+        b.save().unwrap();
+        b.add("second.txt", b"second", Compress::Zstd).unwrap();
+        b.save().unwrap();
+        drop(b);
+
+        // Cut the last save short, partway through its footer
+        let len = fs::metadata(path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        file.set_len(len - 5).unwrap();
+        drop(file);
+
+        let mut b = Bindle::open(path).expect("Failed to recover");
+        assert_eq!(b.len(), 1);
+        assert_eq!(b.read("first.txt").unwrap().as_ref(), b"first");
+
+        // Writing continues from the recovered commit
+        b.add("third.txt", b"third", Compress::None).unwrap();
+        b.save().unwrap();
+        drop(b);
+
+        let b = Bindle::open(path).expect("Failed to reopen");
+        let names: Vec<_> = b.names().collect();
+        assert_eq!(names, ["first.txt", "third.txt"]);
+        assert_eq!(b.read("third.txt").unwrap().as_ref(), b"third");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_is_dirty() {
+        let mut b = Bindle::in_memory();
+        assert!(!b.is_dirty());
+        b.add("a.txt", b"a", Compress::None).unwrap();
+        assert!(b.is_dirty());
+        b.save().unwrap();
+        assert!(!b.is_dirty());
+
+        assert!(!b.remove("missing.txt"));
+        assert!(!b.is_dirty());
+        assert!(b.rename("a.txt", "b.txt").unwrap());
+        assert!(b.is_dirty());
+        b.commit().unwrap();
+        assert!(!b.is_dirty());
+
+        let mut w = b.writer("c.txt", Compress::Zstd).unwrap();
+        w.write_all(b"c").unwrap();
+        w.close().unwrap();
+        assert!(b.is_dirty());
+        b.vacuum().unwrap();
+        assert!(!b.is_dirty());
+
+        b.clear();
+        assert!(b.is_dirty());
+    }
+
+    #[test]
+    fn test_read_into_partial() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 241) as u8).collect();
+        let mut b = Bindle::in_memory();
+        b.add("raw.bin", &data, Compress::None).unwrap();
+        b.add("zstd.bin", &data, Compress::Zstd).unwrap();
+        b.add("gzip.bin", &data, Compress::Gzip).unwrap();
+
+        for name in ["raw.bin", "zstd.bin", "gzip.bin"] {
+            // Short buffers are filled completely, without verification
+            let mut buf = vec![0u8; 70_000];
+            assert_eq!(b.read_into(name, &mut buf).unwrap(), buf.len());
+            assert_eq!(buf, data[..70_000]);
+
+            let mut buf = vec![0u8; data.len()];
+            assert_eq!(b.read_into(name, &mut buf).unwrap(), data.len());
+            assert_eq!(buf, data);
+
+            let mut buf = vec![0u8; data.len() + 10];
+            assert_eq!(b.read_into(name, &mut buf).unwrap(), data.len());
+            assert_eq!(buf[..data.len()], data);
+        }
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let mut b = Bindle::in_memory();
+        b.add("raw.txt", b"borrowed", Compress::None).unwrap();
+        b.add("packed.txt", &b"packed ".repeat(500), Compress::Zstd)
+            .unwrap();
+        b.save().unwrap();
+        let bytes = b.into_bytes().unwrap();
+
+        let archive = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert!(archive.exists("raw.txt"));
+        assert!(!archive.exists("missing.txt"));
+        assert!(matches!(
+            archive.read("raw.txt").unwrap(),
+            Cow::Borrowed(b"borrowed")
+        ));
+        assert_eq!(
+            archive.read("packed.txt").unwrap().as_ref(),
+            b"packed ".repeat(500).as_slice()
+        );
+        assert!(matches!(
+            archive.read("missing.txt"),
+            Err(BindleError::NotFound(_))
+        ));
+
+        let mut out = Vec::new();
+        let mut reader = archive.reader("packed.txt").unwrap();
+        reader.read_to_end(&mut out).unwrap();
+        reader.verify_crc32().unwrap();
+        assert_eq!(out.len(), 3500);
+
+        let archive = BindleRef::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(archive.index().len(), 2);
+        assert!(BindleRef::try_from(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn test_read_ref_and_decompress_into() {
+        let text = b"hot loop ".repeat(1000);
+        let mut b = Bindle::in_memory();
+        b.add("raw.txt", b"zero copy", Compress::None).unwrap();
+        b.add("packed.txt", &text, Compress::Zstd).unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.read_ref("raw.txt"), Some(&b"zero copy"[..]));
+        assert_eq!(b.read_ref("packed.txt"), None);
+        assert_eq!(b.read_ref("missing.txt"), None);
+
+        // The buffer only grows on the first read
+        let mut buffer = Vec::new();
+        b.decompress_into("packed.txt", &mut buffer).unwrap();
+        let capacity = buffer.capacity();
+        for _ in 0..1000 {
+            let n = b.decompress_into("packed.txt", &mut buffer).unwrap();
+            assert_eq!(n, text.len());
+            assert_eq!(buffer.capacity(), capacity);
+        }
+        assert_eq!(buffer, text);
+
+        assert_eq!(b.decompress_into("raw.txt", &mut buffer).unwrap(), 9);
+        assert_eq!(buffer, b"zero copy");
+    }
+
+    #[test]
+    fn test_view_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ReadOnlyView>();
+
+        let path = "test_view_threads.bindl";
+        let _ = fs::remove_file(path);
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        for i in 0..16 {
+            let data = format!("entry {} ", i).repeat(100);
+            b.add(&format!("{}.txt", i), data.as_bytes(), Compress::Zstd)
+                .unwrap();
+        }
+        b.save().unwrap();
+        let view = b.view().unwrap();
+
+        // Later writes don't affect the view
+        b.add("late.txt", b"late", Compress::None).unwrap();
+        b.remove("0.txt");
+        b.save().unwrap();
+
+        std::thread::scope(|s| {
+            for t in 0..4 {
+                let view = view.clone();
+                s.spawn(move || {
+                    for i in (t..16).step_by(4) {
+                        let expected = format!("entry {} ", i).repeat(100);
+                        let data = view.read(&format!("{}.txt", i)).unwrap();
+                        assert_eq!(data.as_ref(), expected.as_bytes());
+                    }
+                });
+            }
+        });
+        assert_eq!(view.len(), 16);
+        assert!(view.exists("0.txt"));
+        assert!(!view.exists("late.txt"));
+
+        let mut memory = Bindle::in_memory();
+        memory.add("a.txt", b"in memory", Compress::None).unwrap();
+        let view = memory.view().unwrap();
+        std::thread::spawn(move || {
+            assert_eq!(view.read("a.txt").unwrap().as_ref(), b"in memory");
+        })
+        .join()
+        .unwrap();
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_encryption() {
+        let path = "test_encryption.bindl";
+        let _ = fs::remove_file(path);
+        let key = [7u8; 32];
+        let secret = b"attack at dawn, attack at dawn, attack at dawn".repeat(100);
+
+        let mut b = Bindle::open_encrypted(path, &key).expect("Failed to open");
+        b.set_sha256(true);
+        b.set_dedup(true);
+        b.add("plain.txt", &secret, Compress::None).unwrap();
+        b.add("zstd.txt", &secret, Compress::Zstd).unwrap();
+        b.add_dedup("copy.txt", &secret, Compress::None).unwrap();
+        let mut w = b.writer("stream.txt", Compress::Gzip).unwrap();
+        w.write_all(&secret).unwrap();
+        w.close().unwrap();
+        // Streamed data is encrypted before it's written, not overwritten afterwards
+        let mut w = b.writer("unfinished.txt", Compress::None).unwrap();
+        w.write_all(&secret).unwrap();
+        assert!(
+            !fs::read(path)
+                .unwrap()
+                .windows(14)
+                .any(|w| w == b"attack at dawn")
+        );
+        w.abort().unwrap();
+        b.save().unwrap();
+        assert!(b.entry("zstd.txt").unwrap().is_encrypted());
+        // Plaintext hashes would tell which entries match, so none are kept and nothing is shared
+        assert_eq!(b.sha256("plain.txt"), None);
+        assert_eq!(b.sha256("copy.txt"), None);
+        assert_ne!(
+            b.entry("plain.txt").unwrap().offset(),
+            b.entry("copy.txt").unwrap().offset()
+        );
+        drop(b);
+
+        // Nothing is stored in the clear
+        let raw = fs::read(path).unwrap();
+        assert!(!raw.windows(14).any(|w| w == b"attack at dawn"));
+
+        let mut b = Bindle::open_encrypted(path, &key).expect("Failed to reopen");
+        for name in ["plain.txt", "zstd.txt", "stream.txt"] {
+            assert_eq!(b.read(name).unwrap().as_ref(), &secret[..]);
+            let mut out = Vec::new();
+            b.reader(name).unwrap().read_to_end(&mut out).unwrap();
+            assert_eq!(out, secret);
+        }
+        assert_eq!(b.read_range("plain.txt", 7, 11).unwrap().as_ref(), b"at d");
+        assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+
+        // The name is authenticated, data moved to another entry fails to decrypt
+        let mut swapped = Bindle::open_encrypted(path, &key).unwrap();
+        let plain = swapped.index["plain.txt"];
+        swapped.index.insert("copy.txt".to_string(), plain);
+        let err = io::Error::from(swapped.read("copy.txt").unwrap_err());
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        drop(swapped);
+
+        // Renaming encrypts the data again under the new name
+        b.rename("copy.txt", "renamed.txt").unwrap();
+        b.save().unwrap();
+        assert_eq!(b.read("renamed.txt").unwrap().as_ref(), &secret[..]);
+
+        b.remove("zstd.txt");
+        b.vacuum().unwrap();
+        assert_eq!(b.read("stream.txt").unwrap().as_ref(), &secret[..]);
+        drop(b);
+
+        // A wrong key fails authentication, no key can't read at all
+        let wrong = Bindle::open_encrypted(path, &[8u8; 32]).unwrap();
+        let err = io::Error::from(wrong.read("plain.txt").unwrap_err());
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let none = Bindle::open(path).unwrap();
+        assert!(none.read("plain.txt").is_err());
+        assert!(none.reader("stream.txt").is_err());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_read() {
+        use tokio::io::AsyncReadExt;
+
+        let path = "test_async_read.bindl";
+        let _ = fs::remove_file(path);
+        let data: Vec<u8> = (0..300_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.add("large.bin", &data, Compress::Zstd).unwrap();
+        b.add("small.txt", b"small", Compress::None).unwrap();
+        b.save().unwrap();
+
+        assert_eq!(b.read_async("large.bin").await.unwrap(), data);
+        assert_eq!(b.read_async("small.txt").await.unwrap(), b"small");
+        let err = b.read_async("missing").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let mut reader = b.async_reader("large.bin").unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).await.unwrap();
+        assert_eq!(out, data);
+        assert!(b.async_reader("missing").is_err());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_pack_progress() {
+        let path = "test_pack_progress.bindl";
+        let src = "test_pack_progress_src";
+        let dest = "test_pack_progress_dest";
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_dir_all(src);
+        let _ = fs::remove_dir_all(dest);
+        fs::create_dir_all(format!("{}/sub", src)).unwrap();
+        fs::write(format!("{}/a.txt", src), b"aaaa").unwrap();
+        fs::write(format!("{}/sub/b.txt", src), b"bbbbbbbb").unwrap();
+
+        let mut events = Vec::new();
+        let mut b = Bindle::open(path).expect("Failed to open");
+        b.pack_with_progress(src, Compress::Zstd, |e| {
+            events.push((e.name.to_string(), e.bytes, e.done, e.total, e.finished))
+        })
+        .unwrap();
+        b.save().unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].1, 0);
+        assert_eq!((events[3].1, events[3].2, events[3].3), (12, 2, 2));
+        for pair in events.chunks(2) {
+            assert_eq!(pair[0].0, pair[1].0);
+            assert!(!pair[0].4 && pair[1].4);
+            assert_eq!(pair[0].2 + 1, pair[1].2);
+        }
+
+        let mut events = Vec::new();
+        b.unpack_with_progress(dest, |e| events.push((e.done, e.finished)))
+            .unwrap();
+        assert_eq!(events, [(0, false), (1, true), (1, false), (2, true)]);
+        assert_eq!(
+            fs::read(format!("{}/sub/b.txt", dest)).unwrap(),
+            b"bbbbbbbb"
+        );
+
+        fs::remove_file(path).ok();
+        fs::remove_dir_all(src).ok();
+        fs::remove_dir_all(dest).ok();
+    }
+
+    #[test]
+    fn test_zstd_dictionary() {
+        let path = "test_zstd_dictionary.bindl";
+        let _ = fs::remove_file(path);
+        let record = |i: u32| {
+            format!(
+                r#"{{"id":{},"kind":"user","name":"user-{}","active":{},"tags":["a","b"]}}"#,
+                i,
+                i,
+                i.is_multiple_of(2)
+            )
+        };
+
+        let mut b = Bindle::open(path).expect("Failed to open");
+        for i in 0..500 {
+            b.add(&format!("{}.json", i), record(i).as_bytes(), Compress::Zstd)
+                .unwrap();
+        }
+        let dict = b.train_zstd_dictionary(4096).unwrap();
+        b.set_zstd_dictionary(dict.clone()).unwrap();
+        b.add("new.json", record(1000).as_bytes(), Compress::Zstd)
+            .unwrap();
+        b.save().unwrap();
+        assert!(b.entry("new.json").unwrap().uses_dictionary());
+        assert!(!b.entry("0.json").unwrap().uses_dictionary());
+        assert!(b.compressed_size("new.json").unwrap() < b.compressed_size("499.json").unwrap());
+        // The dictionary isn't listed as an entry and can't be swapped out from under its entries
+        assert_eq!(b.len(), 501);
+        assert!(b.set_zstd_dictionary(vec![0; 64]).is_err());
+        drop(b);
+
+        let mut b = Bindle::open(path).expect("Failed to reopen");
+        assert_eq!(b.zstd_dictionary(), Some(&dict[..]));
+        assert_eq!(
+            b.read("new.json").unwrap().as_ref(),
+            record(1000).as_bytes()
+        );
+        assert_eq!(b.read("7.json").unwrap().as_ref(), record(7).as_bytes());
+        b.remove("0.json");
+        b.vacuum().unwrap();
+        // The dictionary's data is live
+        assert_eq!(b.dead_space(), 0);
+        assert_eq!(
+            b.read("new.json").unwrap().as_ref(),
+            record(1000).as_bytes()
+        );
+
+        let bytes = fs::read(path).unwrap();
+        let r = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(r.len(), 500);
+        assert_eq!(
+            r.read("new.json").unwrap().as_ref(),
+            record(1000).as_bytes()
+        );
+
+        // Without the dictionary the entry can't be decoded
+        let mut other = Bindle::in_memory();
+        assert!(other.copy_entry_from(&b, "new.json").is_err());
+        let entry = *b.entry("new.json").unwrap();
+        let start = entry.offset() as usize;
+        let stored = &bytes[start..start + entry.compressed_size() as usize];
+        let err = reader::Reader::new(stored, "new.json", &entry, None)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_auto_compress_threshold() {
+        let config = b"{\"name\": \"service\", \"enabled\": true}\n".repeat(12);
+        assert!(config.len() < AUTO_COMPRESS_THRESHOLD);
+
+        let mut b = Bindle::in_memory();
+        b.add("default.json", &config, Compress::Auto).unwrap();
+        assert_eq!(b.compression_of("default.json"), Some(Compress::None));
+
+        b.set_auto_compress_threshold(256);
+        b.add("lowered.json", &config, Compress::Auto).unwrap();
+        let mut w = b.writer("streamed.json", Compress::Auto).unwrap();
+        for chunk in config.chunks(100) {
+            w.write_all(chunk).unwrap();
+        }
+        w.close().unwrap();
+        assert_eq!(b.compression_of("lowered.json"), Some(Compress::Zstd));
+        assert_eq!(b.compression_of("streamed.json"), Some(Compress::Zstd));
+
+        // Streamed entries are buffered past a threshold larger than the sample
+        let large = b"x".repeat(8000);
+        b.set_auto_compress_threshold(10_000);
+        let mut w = b.writer("large.txt", Compress::Auto).unwrap();
+        w.write_all(&large).unwrap();
+        w.close().unwrap();
+        assert_eq!(b.compression_of("large.txt"), Some(Compress::None));
+        assert_eq!(b.read("large.txt").unwrap().as_ref(), &large[..]);
+
+        // No more than the threshold is buffered, the next byte decides
+        let exact = b"x".repeat(10_000);
+        let mut w = b.writer("exact.txt", Compress::Auto).unwrap();
+        w.write_all(&exact).unwrap();
+        assert_eq!(w.pending.as_ref().map(Vec::len), Some(10_000));
+        w.close().unwrap();
+        assert_eq!(b.compression_of("exact.txt"), Some(Compress::None));
+
+        let mut w = b.writer("over.txt", Compress::Auto).unwrap();
+        w.write_all(&exact).unwrap();
+        w.write_all(b"x").unwrap();
+        assert!(w.pending.is_none());
+        w.close().unwrap();
+        assert_eq!(b.compression_of("over.txt"), Some(Compress::Zstd));
+        assert_eq!(b.read("over.txt").unwrap().len(), 10_001);
+    }
+
+    #[test]
+    fn test_recover() {
+        let path = "test_recover.bndl";
+        fs::remove_file(path).ok();
+        let config = b"{\"name\": \"service\", \"enabled\": true}\n".repeat(50);
+        let log = b"GET /index.html 200\n".repeat(100);
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("saved.txt", b"saved", Compress::None).unwrap();
+            b.save().unwrap();
+            b.add("config.json", &config, Compress::Zstd).unwrap();
+            b.add("access.log", &log, Compress::Gzip).unwrap();
+            b.save().unwrap();
+        }
+
+        // Damage the footer of the second save
+        let len = fs::metadata(path).unwrap().len();
+        let mut file = fs::OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(len - 4)).unwrap();
+        file.write_all(b"XXXX").unwrap();
+        drop(file);
+
+        let b = Bindle::recover(path).unwrap();
+        assert_eq!(b.read("saved.txt").unwrap().as_ref(), b"saved");
+        let mut recovered: Vec<_> = b
+            .names()
+            .filter(|name| name.starts_with("recovered/"))
+            .map(|name| b.read(name).unwrap().into_owned())
+            .collect();
+        recovered.sort();
+        assert_eq!(recovered, [log.clone(), config.clone()]);
+        drop(b);
+
+        // A partial trailing write is truncated back to the last save
+        let b = Bindle::open(path).unwrap();
+        assert_eq!(b.len(), 3);
+        let len = fs::metadata(path).unwrap().len();
+        drop(b);
+        let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&[0xab; 100]).unwrap();
+        drop(file);
+        let b = Bindle::recover(path).unwrap();
+        assert_eq!(b.len(), 3);
+        drop(b);
+        assert_eq!(fs::metadata(path).unwrap().len(), len);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_recover_keeps_data() {
+        let path = "test_recover_keeps_data.bndl";
+        fs::remove_file(path).ok();
+        let corrupt_footer = || {
+            let len = fs::metadata(path).unwrap().len();
+            let mut file = fs::OpenOptions::new().write(true).open(path).unwrap();
+            file.seek(SeekFrom::Start(len - 4)).unwrap();
+            file.write_all(b"XXXX").unwrap();
+            drop(file);
+            fs::read(path).unwrap()
+        };
+
+        // Nothing to recover from uncompressed entries, the file is left alone
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("a.txt", b"first", Compress::None).unwrap();
+            b.add("b.txt", &[b'b'; 1000], Compress::None).unwrap();
+            b.save().unwrap();
+        }
+        let damaged = corrupt_footer();
+        assert!(Bindle::recover(path).is_err());
+        assert_eq!(fs::read(path).unwrap(), damaged);
+        fs::remove_file(path).ok();
+
+        // Uncompressed data after a recovered stream isn't truncated
+        let config = b"{\"name\": \"service\", \"enabled\": true}\n".repeat(50);
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("config.json", &config, Compress::Zstd).unwrap();
+            b.add("raw.bin", &[b'r'; 1000], Compress::None).unwrap();
+            b.save().unwrap();
+        }
+        let damaged = corrupt_footer();
+        let b = Bindle::recover(path).unwrap();
+        assert_eq!(b.len(), 1);
+        let name = b.names().next().unwrap().to_string();
+        assert_eq!(b.read(&name).unwrap().as_ref(), config.as_slice());
+        drop(b);
+        let bytes = fs::read(path).unwrap();
+        assert!(bytes.len() > damaged.len());
+        assert_eq!(&bytes[..damaged.len()], damaged.as_slice());
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_entries_by_size() {
+        let mut b = Bindle::in_memory();
+        b.add("b.txt", b"four", Compress::None).unwrap();
+        b.add("a.txt", b"four", Compress::None).unwrap();
+        b.add("large.bin", &[1u8; 100], Compress::None).unwrap();
+        b.add("empty", b"", Compress::None).unwrap();
+
+        let names = |entries: Vec<EntryInfo>| -> Vec<String> {
+            entries.into_iter().map(|info| info.name).collect()
+        };
+        assert_eq!(
+            names(b.entries_by_size(true)),
+            ["large.bin", "a.txt", "b.txt", "empty"]
+        );
+        assert_eq!(
+            names(b.entries_by_size(false)),
+            ["empty", "a.txt", "b.txt", "large.bin"]
+        );
+    }
+
+    #[test]
+    fn test_save_atomic() {
+        let path = "test_save_atomic.bndl";
+        fs::remove_file(path).ok();
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("a.txt", b"first", Compress::None).unwrap();
+            b.add("b.txt", &[b'b'; 1000], Compress::Zstd).unwrap();
+            b.save_atomic().unwrap();
+            b.add("a.txt", b"second", Compress::None).unwrap();
+            b.save_atomic().unwrap();
+            assert!(!b.is_dirty());
+            assert_eq!(b.read("a.txt").unwrap().as_ref(), b"second");
+
+            // Regular saves keep appending to the replaced file
+            b.add("c.txt", b"third", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+        assert!(fs::metadata("test_save_atomic.tmp").is_err());
+
+        let b = Bindle::open(path).unwrap();
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"second");
+        assert_eq!(b.read("b.txt").unwrap().as_ref(), &[b'b'; 1000][..]);
+        assert_eq!(b.read("c.txt").unwrap().as_ref(), b"third");
+        drop(b);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_vacuum_ordered() {
+        let mut b = Bindle::in_memory();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+            b.add(name, name.as_bytes(), Compress::None).unwrap();
+        }
+        b.add("a.txt", b"updated", Compress::None).unwrap();
+        b.save().unwrap();
+
+        let order = ["d.txt", "missing", "b.txt", "d.txt"].map(String::from);
+        b.vacuum_ordered(&order).unwrap();
+        let offset = |b: &Bindle, name| b.stat(name).unwrap().offset;
+        assert!(offset(&b, "d.txt") < offset(&b, "b.txt"));
+        assert!(offset(&b, "b.txt") < offset(&b, "a.txt"));
+        assert!(offset(&b, "a.txt") < offset(&b, "c.txt"));
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"updated");
+        assert_eq!(b.read("d.txt").unwrap().as_ref(), b"d.txt");
+
+        // Plain vacuum goes back to name order
+        b.vacuum().unwrap();
+        assert!(offset(&b, "a.txt") < offset(&b, "b.txt"));
+    }
+
+    #[test]
+    fn test_sync_policy() {
+        let path = "test_sync_policy.bndl";
+        fs::remove_file(path).ok();
+        {
+            let options = Options::new().sync(SyncPolicy::None);
+            let mut b = Bindle::open_with(path, options).unwrap();
+            b.add("none.txt", b"none", Compress::None).unwrap();
+            b.save().unwrap();
+            b.add("durable.txt", b"durable", Compress::Zstd).unwrap();
+            b.save_durable().unwrap();
+            b.set_sync_policy(SyncPolicy::All);
+            b.add("all.txt", b"all", Compress::None).unwrap();
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).unwrap();
+        assert_eq!(b.read("none.txt").unwrap().as_ref(), b"none");
+        assert_eq!(b.read("durable.txt").unwrap().as_ref(), b"durable");
+        assert_eq!(b.read("all.txt").unwrap().as_ref(), b"all");
+        drop(b);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_writer_abort() {
+        let mut b = Bindle::in_memory();
+        b.add("kept.txt", b"original", Compress::None).unwrap();
+        b.save().unwrap();
+        let end = b.committed_len;
+
+        let mut w = b.writer("kept.txt", Compress::Zstd).unwrap();
+        w.write_all(&[b'x'; 10_000]).unwrap();
+        w.abort().unwrap();
+        assert_eq!(b.read("kept.txt").unwrap().as_ref(), b"original");
+        assert!(!b.is_dirty());
+
+        // The next entry reuses the space of the discarded one
+        b.add("next.txt", b"next", Compress::None).unwrap();
+        assert_eq!(b.stat("next.txt").unwrap().offset, end);
+        b.save().unwrap();
+        assert_eq!(b.len(), 2);
+    }
+
+    #[test]
+    fn test_limits() {
+        let dest = "test_limits_out";
+        let _ = fs::remove_dir_all(dest);
+        let mut b = Bindle::in_memory();
+        b.add("forged.bin", b"small", Compress::None).unwrap();
+        b.add("bomb.bin", &vec![0u8; 1 << 20], Compress::Zstd)
+            .unwrap();
+        b.save().unwrap();
+
+        // A forged size is rejected before anything is allocated
+        b.index
+            .get_mut("forged.bin")
+            .unwrap()
+            .set_uncompressed_size(u64::MAX / 2);
+        match b.read("forged.bin") {
+            Err(BindleError::TooLarge { size, limit, .. }) => {
+                assert_eq!(size, u64::MAX / 2);
+                assert_eq!(limit, Limits::default().max_entry_size);
+            }
+            other => panic!("expected TooLarge, got {:?}", other.map(|d| d.len())),
+        }
+        assert!(b.reader("forged.bin").is_err());
+
+        // Data that expands past its recorded size stops decoding
+        b.index
+            .get_mut("bomb.bin")
+            .unwrap()
+            .set_uncompressed_size(16);
+        let err = b.read("bomb.bin").unwrap_err();
+        assert!(err.to_string().contains("larger than its recorded size"));
+        b.index.remove("forged.bin");
+        b.index
+            .get_mut("bomb.bin")
+            .unwrap()
+            .set_uncompressed_size(1 << 20);
+
+        b.set_limits(Limits {
+            max_entry_size: 1000,
+            ..Limits::default()
+        });
+        assert!(matches!(
+            b.read("bomb.bin"),
+            Err(BindleError::TooLarge { .. })
+        ));
+        assert_eq!(b.read_range("bomb.bin", 0, 10).unwrap().len(), 10);
+
+        // Views start with the archive's limits
+        let view = b.view().unwrap();
+        assert_eq!(view.limits(), b.limits());
+        assert!(matches!(
+            view.read("bomb.bin"),
+            Err(BindleError::TooLarge { .. })
+        ));
+        assert!(view.reader("bomb.bin").is_err());
+        assert!(view.owned_reader("bomb.bin").is_err());
+
+        b.set_limits(Limits {
+            max_entry_size: 1 << 20,
+            max_total_size: 1000,
+        });
+        assert!(b.unpack(dest).is_err());
+        assert!(fs::metadata(format!("{}/bomb.bin", dest)).is_err());
+        let _ = fs::remove_dir_all(dest);
+
+        // So do archives read from bytes or a reader
+        let limits = Limits {
+            max_entry_size: 1000,
+            ..Limits::default()
+        };
+        let bytes = b.into_bytes().unwrap();
+        let mut borrowed = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(borrowed.read("bomb.bin").unwrap().len(), 1 << 20);
+        borrowed.set_limits(limits);
+        assert!(matches!(
+            borrowed.read("bomb.bin"),
+            Err(BindleError::TooLarge { .. })
+        ));
+        assert!(borrowed.reader("bomb.bin").is_err());
+        let mut from_reader = Bindle::from_reader(io::Cursor::new(&bytes)).unwrap();
+        from_reader.set_limits(limits);
+        assert!(matches!(
+            from_reader.read("bomb.bin"),
+            Err(BindleError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_add_cas() {
+        let mut b = Bindle::in_memory();
+        let name = b.add_cas(b"hello world", Compress::None).unwrap();
+        assert_eq!(
+            name,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        let end = b.data_end;
+        assert_eq!(b.add_cas(b"hello world", Compress::Zstd).unwrap(), name);
+        assert_eq!(b.data_end, end);
+        assert_eq!(b.read(&name).unwrap().as_ref(), b"hello world");
+
+        let other = b.add_cas(b"other", Compress::Zstd).unwrap();
+        assert_ne!(other, name);
+        assert_eq!(b.len(), 2);
+    }
+
+    #[test]
+    fn test_corrupt_archives() {
+        let path = "test_corrupt_archives.bndl";
+        fs::remove_file(path).ok();
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("raw.txt", b"raw data", Compress::None).unwrap();
+            b.add("zstd.bin", &[7u8; 300], Compress::Zstd).unwrap();
+            b.add("gzip.bin", &[9u8; 300], Compress::Gzip).unwrap();
+            let mut w = b.seekable_writer("seekable.bin", 100).unwrap();
+            w.write_all(&[3u8; 350]).unwrap();
+            w.close().unwrap();
+            b.save().unwrap();
+        }
+        let bytes = fs::read(path).unwrap();
+        fs::remove_file(path).ok();
+
+        let read_all = |bytes: &[u8]| {
+            if let Ok(b) = Bindle::from_bytes(bytes) {
+                for name in b.index().keys() {
+                    let _ = b.read(name);
+                    if let Ok(mut reader) = b.reader(name) {
+                        let _ = reader.seek(SeekFrom::Start(120));
+                        let _ = io::copy(&mut reader, &mut io::sink());
+                    }
+                }
+            }
+            if let Ok(mut b) = Bindle::from_reader(io::Cursor::new(bytes)) {
+                let names: Vec<String> = b.names().map(String::from).collect();
+                for name in names {
+                    let _ = b.read(&name);
+                }
+            }
+        };
+
+        // Truncated and bit-flipped files fail cleanly instead of panicking
+        for len in 0..bytes.len() {
+            read_all(&bytes[..len]);
+        }
+        for i in 0..bytes.len() {
+            let mut mangled = bytes.clone();
+            mangled[i] ^= 0xff;
+            read_all(&mangled);
+        }
+
+        // Records pointing outside the data region are rejected even with a valid index checksum
+        let len = bytes.len();
+        let index_offset =
+            u64::from_le_bytes(bytes[len - 16..len - 8].try_into().unwrap()) as usize;
+        let records = index_offset..len - FOOTER_SIZE - INDEX_CRC_SIZE;
+        for (offset, size) in [
+            (u64::MAX, 8),
+            (index_offset as u64, 8),
+            (8, u64::MAX),
+            (0, 8),
+        ] {
+            let mut mangled = bytes.clone();
+            mangled[index_offset..index_offset + 8].copy_from_slice(&offset.to_le_bytes());
+            mangled[index_offset + 8..index_offset + 16].copy_from_slice(&size.to_le_bytes());
+            let crc = crc32fast::hash(&mangled[records.clone()]);
+            mangled[records.end..records.end + 4].copy_from_slice(&crc.to_le_bytes());
+
+            let err = Bindle::from_bytes(&mangled).err().unwrap();
+            let err = err.get_ref().unwrap().downcast_ref::<BindleError>();
+            assert!(matches!(err, Some(BindleError::EntryOutOfBounds { .. })));
+            assert!(Bindle::from_reader(io::Cursor::new(&mangled)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_owned_reader() {
+        let path = "test_owned_reader.bndl";
+        fs::remove_file(path).ok();
+        let data = b"owned data ".repeat(1000);
+        let mut b = Bindle::open(path).unwrap();
+        b.add("data.bin", &data, Compress::Zstd).unwrap();
+        b.add("raw.txt", b"raw", Compress::None).unwrap();
+        b.save().unwrap();
+
+        let mut reader = b.owned_reader("data.bin").unwrap();
+        let mut view_reader = b.view().unwrap().owned_reader("raw.txt").unwrap();
+        drop(b);
+
+        // The readers outlive the archive and move to another thread
+        let handle = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            reader.read_to_end(&mut out).unwrap();
+            reader.verify_crc32().unwrap();
+            out
+        });
+        assert_eq!(handle.join().unwrap(), data);
+        let handle = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            view_reader.read_to_end(&mut out).unwrap();
+            out
+        });
+        assert_eq!(handle.join().unwrap(), b"raw");
+
+        let mut m = Bindle::in_memory();
+        m.add("first", b"first", Compress::None).unwrap();
+        m.add("second", &data, Compress::Gzip).unwrap();
+        let mut reader = m.owned_reader("second").unwrap();
+        drop(m);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_reader_out_of_bounds() {
+        let mut b = Bindle::in_memory();
+        b.add("raw.txt", b"raw data", Compress::None).unwrap();
+        b.add("zstd.bin", &[1u8; 1000], Compress::Zstd).unwrap();
+        b.save().unwrap();
+        let end = b.committed_len;
+
+        for offset in [end, end - 4, u64::MAX - 2] {
+            for name in ["raw.txt", "zstd.bin"] {
+                b.index.get_mut(name).unwrap().set_offset(offset);
+                assert!(b.reader(name).is_err());
+                assert!(b.owned_reader(name).is_err());
+                assert!(b.read(name).is_err());
+                assert!(b.read_to(name, io::sink()).is_err());
+                assert!(b.read_range(name, 0, 4).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_entries() {
+        let path = "test_empty_entries.bndl";
+        fs::remove_file(path).ok();
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.add("empty.txt", b"", Compress::None).unwrap();
+            b.add("empty.zst", b"", Compress::Zstd).unwrap();
+            b.add("last.txt", b"", Compress::None).unwrap();
+
+            // Readable before the save, even though nothing was written for them
+            assert_eq!(b.read("last.txt").unwrap().as_ref(), b"");
+            b.save().unwrap();
+        }
+
+        let b = Bindle::open(path).unwrap();
+        for name in ["empty.txt", "empty.zst", "last.txt"] {
+            assert!(b.exists(name));
+            assert_eq!(b.size(name), Some(0));
+            assert_eq!(b.stat(name).unwrap().crc32, crc32fast::hash(b""));
+            assert!(b.read(name).unwrap().is_empty());
+            let mut out = Vec::new();
+            assert_eq!(b.read_to(name, &mut out).unwrap(), 0);
+        }
+        assert!(matches!(b.read("empty.txt"), Ok(Cow::Borrowed(&[]))));
+        assert!(
+            Bindle::from_bytes(&fs::read(path).unwrap())
+                .unwrap()
+                .read("last.txt")
+                .unwrap()
+                .is_empty()
+        );
+        drop(b);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_unpack_unsafe_names() {
+        let dest = "test_unpack_unsafe_names_out";
+        fs::remove_dir_all(dest).ok();
+
+        for name in [
+            "../evil.txt",
+            "dir/../../evil.txt",
+            "/abs.txt",
+            "..\\evil.txt",
+            "dir\\..\\..\\evil.txt",
+            "\\abs.txt",
+            "C:evil.txt",
+        ] {
+            let mut b = Bindle::in_memory();
+            b.add("ok.txt", b"ok", Compress::None).unwrap();
+            b.add(name, b"evil", Compress::None).unwrap();
+            let err = b.unpack(dest).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", name);
+            assert!(matches!(
+                err.get_ref().and_then(|e| e.downcast_ref()),
+                Some(BindleError::UnsafeName(_))
+            ));
+            // Nothing is written, not even the safe entries
+            assert!(fs::read_dir(dest).unwrap().next().is_none(), "{:?}", name);
+            assert!(fs::metadata("evil.txt").is_err());
+
+            // Filtering the bad name out unpacks the rest
+            b.unpack_matching(dest, |n| n == "ok.txt").unwrap();
+            assert_eq!(fs::read(format!("{}/ok.txt", dest)).unwrap(), b"ok");
+            fs::remove_dir_all(dest).ok();
+        }
+
+        // Names that only look like traversal are fine
+        let mut b = Bindle::in_memory();
+        b.add("..hidden", b"a", Compress::None).unwrap();
+        b.add("dir/file..txt", b"b", Compress::None).unwrap();
+        b.unpack(dest).unwrap();
+        assert_eq!(fs::read(format!("{}/..hidden", dest)).unwrap(), b"a");
+        assert_eq!(fs::read(format!("{}/dir/file..txt", dest)).unwrap(), b"b");
+        fs::remove_dir_all(dest).ok();
+
+        // Names that aren't UTF-8 are rejected when opening instead of being mangled
+        let mut b = Bindle::in_memory();
+        b.add("name\u{e9}.txt", b"data", Compress::None).unwrap();
+        b.save().unwrap();
+        let mut bytes = b.into_bytes().unwrap();
+        let len = bytes.len();
+        let index_offset =
+            u64::from_le_bytes(bytes[len - 16..len - 8].try_into().unwrap()) as usize;
+        let records = index_offset..len - FOOTER_SIZE - INDEX_CRC_SIZE;
+        let at = records.start + ENTRY_SIZE + 4;
+        assert_eq!(&bytes[at..at + 2], "\u{e9}".as_bytes());
+        bytes[at + 1] = b'?';
+        let crc = crc32fast::hash(&bytes[records.clone()]);
+        bytes[records.end..records.end + 4].copy_from_slice(&crc.to_le_bytes());
+        let err = Bindle::from_bytes(&bytes).err().unwrap();
+        assert!(err.to_string().contains("not valid UTF-8"));
+        assert!(Bindle::from_reader(io::Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_name_too_long() {
+        let mut b = Bindle::in_memory();
+        let long = "n".repeat(70000);
+        let max = "m".repeat(MAX_NAME_LEN);
+
+        assert_eq!(
+            b.add(&long, b"data", Compress::None).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            b.writer(&long, Compress::Zstd).err().unwrap().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        b.add(&max, b"data", Compress::None).unwrap();
+        assert_eq!(
+            b.rename(&max, &long).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        b.add_dedup("short.txt", b"data", Compress::None).unwrap();
+        assert!(b.add_dedup(&long, b"data", Compress::None).is_err());
+        b.save().unwrap();
+
+        // Nothing was written for the rejected names and the index still parses
+        let bytes = b.into_bytes().unwrap();
+        let b = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(b.index().len(), 2);
+        assert_eq!(b.read(&max).unwrap().as_ref(), b"data");
+    }
+
+    #[test]
+    fn test_compact_in_place() {
+        let path = "test_compact_in_place.bndl";
+        fs::remove_file(path).ok();
+
+        // Large enough to span several copy buffers while overlapping its new position
+        let big: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        {
+            let mut b = Bindle::open(path).unwrap();
+            b.set_dedup(true);
+            b.add("dead.txt", b"shadowed", Compress::None).unwrap();
+            b.add("big.bin", &big, Compress::None).unwrap();
+            b.add("removed.txt", b"removed", Compress::None).unwrap();
+            b.add("a.txt", b"shared", Compress::None).unwrap();
+            b.add("b.txt", b"shared", Compress::None).unwrap();
+            b.add("zstd.txt", &[5u8; 5000], Compress::Zstd).unwrap();
+            b.save().unwrap();
+            b.add("dead.txt", b"live", Compress::None).unwrap();
+            b.remove("removed.txt");
+            b.add("pending.txt", b"not saved yet", Compress::None)
+                .unwrap();
+
+            let view = b.view().unwrap();
+            assert_eq!(
+                b.compact_in_place().unwrap_err().kind(),
+                io::ErrorKind::ResourceBusy
+            );
+            drop(view);
+
+            let before = fs::metadata(path).unwrap().len();
+            b.compact_in_place().unwrap();
+            assert_eq!(b.dead_space(), 0);
+            assert!(fs::metadata(path).unwrap().len() < before);
+            assert_eq!(b.read("big.bin").unwrap().as_ref(), &big[..]);
+            assert_eq!(b.index()["a.txt"].offset(), b.index()["b.txt"].offset());
+        }
+
+        let b = Bindle::open(path).unwrap();
+        assert_eq!(b.len(), 6);
+        assert_eq!(b.read("big.bin").unwrap().as_ref(), &big[..]);
+        assert_eq!(b.read("dead.txt").unwrap().as_ref(), b"live");
+        assert_eq!(b.read("b.txt").unwrap().as_ref(), b"shared");
+        assert_eq!(b.read("zstd.txt").unwrap().as_ref(), &[5u8; 5000][..]);
+        assert_eq!(b.read("pending.txt").unwrap().as_ref(), b"not saved yet");
+        assert!(!b.exists("removed.txt"));
+        drop(b);
+
+        // In-memory archives compact the same way
+        let mut b = Bindle::in_memory();
+        b.add("x", b"old", Compress::None).unwrap();
+        b.add("y", b"kept", Compress::None).unwrap();
+        b.add("x", b"new", Compress::None).unwrap();
+        b.compact_in_place().unwrap();
+        assert_eq!(b.dead_space(), 0);
+        let bytes = b.into_bytes().unwrap();
+        let b = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(b.read("x").unwrap().as_ref(), b"new");
+        assert_eq!(b.read("y").unwrap().as_ref(), b"kept");
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let mut b = Bindle::in_memory();
+        b.add("README.md", b"docs", Compress::None).unwrap();
+        b.add("Docs/Guide.txt", b"guide", Compress::Zstd).unwrap();
+        assert!(!b.exists("readme.md"));
+
+        b.set_case_insensitive(true);
+        assert!(b.exists("readme.md"));
+        assert_eq!(b.read("readme.MD").unwrap().as_ref(), b"docs");
+        let mut out = Vec::new();
+        b.reader("docs/guide.txt")
+            .unwrap()
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, b"guide");
+        assert_eq!(b.stat("readme.md").unwrap().name, "README.md");
+        assert_eq!(
+            b.names().collect::<Vec<_>>(),
+            ["Docs/Guide.txt", "README.md"]
+        );
+
+        // The last write wins and keeps its spelling
+        b.add("ReadMe.md", b"new docs", Compress::None).unwrap();
+        assert_eq!(
+            b.names().collect::<Vec<_>>(),
+            ["Docs/Guide.txt", "ReadMe.md"]
+        );
+        assert_eq!(b.read("README.md").unwrap().as_ref(), b"new docs");
+
+        // Renaming only the case isn't a conflict
+        assert!(b.rename("readme.md", "README.md").unwrap());
+        assert_eq!(
+            b.names().collect::<Vec<_>>(),
+            ["Docs/Guide.txt", "README.md"]
+        );
+        b.add("other.txt", b"other", Compress::None).unwrap();
+        assert_eq!(
+            b.rename("OTHER.txt", "readme.md").unwrap_err().kind(),
+            io::ErrorKind::AlreadyExists
+        );
+
+        assert!(b.remove("DOCS/GUIDE.TXT"));
+        assert!(!b.exists("docs/guide.txt"));
+        assert_eq!(b.names().collect::<Vec<_>>(), ["README.md", "other.txt"]);
+
+        b.set_case_insensitive(false);
+        assert!(!b.exists("readme.md"));
+        assert!(b.exists("README.md"));
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut b = Bindle::in_memory();
+        for name in [
+            "docs",
+            "docs/index.html",
+            "docs/img/logo.png",
+            "docs/img/icon.png",
+            "docs/img.txt",
+            "docs-old/a.txt",
+            "readme.txt",
+            "/abs/file",
+            "trailing/",
+        ] {
+            b.add(name, name.as_bytes(), Compress::None).unwrap();
+        }
+
+        let tree = b.tree();
+        // "docs" is a file and a directory at the same time
+        assert_eq!(
+            tree.files.keys().collect::<Vec<_>>(),
+            ["docs", "readme.txt"]
+        );
+        assert_eq!(
+            tree.dirs.keys().collect::<Vec<_>>(),
+            ["", "docs", "docs-old", "trailing"]
+        );
+        assert_eq!(
+            tree.files["docs"].uncompressed_size(),
+            b.size("docs").unwrap()
+        );
+        let img = tree.dir("docs/img/").unwrap();
+        assert_eq!(
+            img.files.keys().collect::<Vec<_>>(),
+            ["icon.png", "logo.png"]
+        );
+        assert_eq!(
+            img.files["logo.png"].offset(),
+            b.entry("docs/img/logo.png").unwrap().offset()
+        );
+        assert!(tree.dir("/abs").unwrap().files.contains_key("file"));
+        assert!(tree.dir("abs").is_none());
+        assert!(tree.dir("trailing").unwrap().files.contains_key(""));
+        assert!(tree.dir("missing").is_none());
+        assert_eq!(tree.dir("").unwrap().files.len(), 2);
+
+        assert_eq!(
+            b.children_of(""),
+            ["/", "docs", "docs-old/", "docs/", "readme.txt", "trailing/"]
+        );
+        assert_eq!(b.children_of("docs"), ["img.txt", "img/", "index.html"]);
+        assert_eq!(b.children_of("docs/"), b.children_of("docs"));
+        assert_eq!(b.children_of("docs/img"), ["icon.png", "logo.png"]);
+        assert_eq!(b.children_of("trailing"), [""]);
+        assert!(b.children_of("missing").is_empty());
+
+        // Names that only differ in repeated slashes are different entries
+        b.add("a/b", b"one", Compress::None).unwrap();
+        b.add("a//b", b"two", Compress::None).unwrap();
+        let tree = b.tree();
+        assert!(tree.dir("a").unwrap().files.contains_key("b"));
+        assert!(tree.dir("a//").unwrap().files.contains_key("b"));
+        assert_eq!(b.children_of("a"), ["/", "b"]);
+        assert_eq!(b.children_of("a//"), ["b"]);
+
+        // Both list the same children for every directory
+        fn check(b: &Bindle, node: &TreeNode, path: &str) {
+            let mut expected: Vec<String> = node.files.keys().cloned().collect();
+            expected.extend(node.dirs.keys().map(|dir| format!("{}/", dir)));
+            expected.sort();
+            assert_eq!(b.children_of(path), expected, "{:?}", path);
+            for (dir, child) in &node.dirs {
+                check(b, child, &format!("{}{}/", path, dir));
+            }
+        }
+        check(&b, &tree, "");
+
+        // No entry is lost from the tree
+        fn count(node: &TreeNode) -> usize {
+            node.files.len() + node.dirs.values().map(count).sum::<usize>()
+        }
+        assert_eq!(count(&tree), b.len());
+    }
+
+    #[test]
+    fn test_extract() {
+        let dest = "test_extract_out";
+        fs::remove_dir_all(dest).ok();
+
+        let mut b = Bindle::in_memory();
+        b.add("docs/img/logo.png", b"png", Compress::Zstd).unwrap();
+        b.add("docs/index.html", b"html", Compress::None).unwrap();
+        b.add("../evil.txt", b"evil", Compress::None).unwrap();
+
+        let path = b.extract("docs/img/logo.png", dest).unwrap();
+        assert_eq!(path, std::path::Path::new(dest).join("docs/img/logo.png"));
+        assert_eq!(fs::read(&path).unwrap(), b"png");
+        // Only the requested entry is written
+        assert!(fs::metadata(format!("{}/docs/index.html", dest)).is_err());
+
+        assert_eq!(
+            b.extract("missing.txt", dest).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            b.extract("../evil.txt", dest).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(fs::metadata("evil.txt").is_err());
+
+        fs::remove_dir_all(dest).ok();
+    }
+
+    #[test]
+    fn test_zstd_long_mode() {
+        // A 4 MiB block of noise repeated to 16 MiB, repeats are further apart than the level 3 window
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        let block: Vec<u8> = (0..4 << 20)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect();
+        let data = block.repeat(4);
+
+        let mut b = Bindle::in_memory();
+        b.add("default.img", &data, Compress::Zstd).unwrap();
+        let params = ZstdParams {
+            long_mode: true,
+            ..ZstdParams::default()
+        };
+        let mut writer = b.writer_with_params("long.img", params).unwrap();
+        writer.write_all(&data).unwrap();
+        writer.close().unwrap();
+
+        let default = b.entry("default.img").unwrap();
+        let long = b.entry("long.img").unwrap();
+        assert!(long.compressed_size() * 3 < default.compressed_size());
+        // A 128 MiB window decodes with the default limits
+        assert!(!long.uses_long_window());
+        assert!(b.read("long.img").unwrap() == data);
+
+        // Larger windows are flagged so the decoder accepts them
+        let params = ZstdParams {
+            window_log: Some(28),
+            ..params
+        };
+        let mut writer = b.writer_with_params("wide.img", params).unwrap();
+        writer.write_all(&block).unwrap();
+        writer.close().unwrap();
+        assert!(b.entry("wide.img").unwrap().uses_long_window());
+        assert!(b.read("wide.img").unwrap() == block);
+
+        // Recovery reads the window a frame needs from its header
+        let mut encoder = zstd::Encoder::new(Vec::new(), 3).unwrap();
+        encoder.window_log(28).unwrap();
+        encoder.write_all(b"wide").unwrap();
+        assert_eq!(zstd_window_log(&encoder.finish().unwrap()), Some(28));
+        let frame = zstd::bulk::compress(&[7u8; 3000], 3).unwrap();
+        assert_eq!(zstd_window_log(&frame), Some(12));
+        assert_eq!(zstd_window_log(&frame[..4]), None);
+
+        for window_log in [9, 32] {
+            let params = ZstdParams {
+                window_log: Some(window_log),
+                ..ZstdParams::default()
+            };
+            assert_eq!(
+                b.writer_with_params("bad.img", params)
+                    .err()
+                    .unwrap()
+                    .kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+        assert!(!b.exists("bad.img"));
+    }
+
+    #[test]
+    fn test_pread_backend() {
+        let path = "test_pread_backend.bndl";
+        fs::remove_file(path).ok();
+        let pread = Options::new().backend(Backend::Pread);
+        let big: Vec<u8> = (0..300_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        {
+            let mut b = Bindle::open_with(path, pread).unwrap();
+            b.add("raw.txt", b"raw data", Compress::None).unwrap();
+            b.add("zstd.bin", &big, Compress::Zstd).unwrap();
+            b.add("gzip.bin", &big, Compress::Gzip).unwrap();
+            let mut w = b.seekable_writer("seekable.bin", 4096).unwrap();
+            w.write_all(&big).unwrap();
+            w.close().unwrap();
+            b.save().unwrap();
+            assert!(b.read("zstd.bin").unwrap() == big);
+        }
+        // A save interrupted after the last commit, its footer is found with positioned reads
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(&[0xab; 100])
+            .unwrap();
+
+        let mut b = Bindle::open_with(path, pread).unwrap();
+        assert_eq!(b.len(), 4);
+        for name in ["raw.txt", "zstd.bin", "gzip.bin", "seekable.bin"] {
+            assert!(matches!(b.read(name).unwrap(), Cow::Owned(_)));
+        }
+        assert!(b.read("seekable.bin").unwrap() == big);
+        assert!(b.read_ref("raw.txt").is_none());
+        assert_eq!(b.read_range("raw.txt", 4, 8).unwrap(), &b"data"[..]);
+        assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+
+        let mut reader = b.reader("seekable.bin").unwrap();
+        reader.seek(SeekFrom::Start(1_000_000)).unwrap();
+        let mut buf = [0u8; 8];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, big[1_000_000..1_000_008]);
+
+        // Views and owned readers share a file handle instead of a memory map
+        let view = b.view().unwrap();
+        assert!(view.read("gzip.bin").unwrap() == big);
+        let mut owned = b.owned_reader("zstd.bin").unwrap();
+
+        // Reading on another thread doesn't move where a streaming writer writes
+        std::thread::scope(|scope| {
+            let reading = scope.spawn(|| (0..20).all(|_| view.read("gzip.bin").unwrap() == big));
+            let mut w = b.writer("streamed.bin", Compress::None).unwrap();
+            for chunk in big.chunks(4096) {
+                w.write_all(chunk).unwrap();
+            }
+            w.close().unwrap();
+            assert!(reading.join().unwrap());
+        });
+        b.save().unwrap();
+        assert!(b.read("streamed.bin").unwrap() == big);
+        assert_eq!(
+            b.compact_in_place().unwrap_err().kind(),
+            io::ErrorKind::ResourceBusy
+        );
+        drop(b);
+        let mut out = Vec::new();
+        owned.read_to_end(&mut out).unwrap();
+        owned.verify_crc32().unwrap();
+        assert_eq!(out, big);
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_replace_in_place() {
+        let path = "test_replace_in_place.bndl";
+        fs::remove_file(path).ok();
+        let mut b = Bindle::open(path).unwrap();
+        b.add("a.txt", b"first version", Compress::None).unwrap();
+        b.add("b.txt", b"neighbour", Compress::None).unwrap();
+        b.save().unwrap();
+        let offset = b.entry("a.txt").unwrap().offset();
+
+        // replace() leaves committed data alone, readers of the file never see it change
+        let committed = fs::read(path).unwrap();
+        b.replace("a.txt", b"second version!", Compress::None)
+            .unwrap();
+        let pending = b.entry("a.txt").unwrap().offset();
+        assert_ne!(pending, offset);
+        assert_eq!(&fs::read(path).unwrap()[..committed.len()], &committed[..]);
+        // Data written since the last save is reused though
+        b.replace("a.txt", b"second version!!", Compress::None)
+            .unwrap();
+        assert_eq!(b.entry("a.txt").unwrap().offset(), pending);
+        b.save().unwrap();
+        drop(b);
+
+        let mut b = Bindle::open(path).unwrap();
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"second version!!");
+        b.replace("a.txt", b"first version", Compress::None)
+            .unwrap();
+        b.save().unwrap();
+        let offset = b.entry("a.txt").unwrap().offset();
+        let dead = b.dead_space();
+
+        // 16 bytes fit the slot of the 13 byte entry and its padding
+        b.replace_in_place("a.txt", b"second version!!", Compress::None)
+            .unwrap();
+        assert_eq!(b.entry("a.txt").unwrap().offset(), offset);
+        assert_eq!(b.dead_space(), dead);
+        b.save().unwrap();
+        drop(b);
+
+        let mut b = Bindle::open(path).unwrap();
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"second version!!");
+        assert_eq!(b.read("b.txt").unwrap().as_ref(), b"neighbour");
+        assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+
+        // Too large for the slot, shadowed like add()
+        let dead = b.dead_space();
+        b.replace_in_place("a.txt", b"a third version, too long to fit", Compress::None)
+            .unwrap();
+        assert_ne!(b.entry("a.txt").unwrap().offset(), offset);
+        assert!(b.dead_space() >= dead + 16);
+        b.save().unwrap();
+        assert_eq!(
+            b.read("a.txt").unwrap().as_ref(),
+            b"a third version, too long to fit"
+        );
+
+        // Data shared by deduplicated entries is left alone
+        b.set_dedup(true);
+        b.add("c.txt", b"shared", Compress::None).unwrap();
+        b.add("d.txt", b"shared", Compress::None).unwrap();
+        b.replace_in_place("c.txt", b"SHARED", Compress::None)
+            .unwrap();
+        b.save().unwrap();
+        assert_ne!(
+            b.entry("c.txt").unwrap().offset(),
+            b.entry("d.txt").unwrap().offset()
+        );
+        assert_eq!(b.read("c.txt").unwrap().as_ref(), b"SHARED");
+        assert_eq!(b.read("d.txt").unwrap().as_ref(), b"shared");
+
+        // So is data an open view can still read
+        let view = b.view().unwrap();
+        let offset = b.entry("b.txt").unwrap().offset();
+        b.replace_in_place("b.txt", b"changed", Compress::None)
+            .unwrap();
+        assert_ne!(b.entry("b.txt").unwrap().offset(), offset);
+        assert_eq!(view.read("b.txt").unwrap().as_ref(), b"neighbour");
+        drop(view);
+
+        // Compressed data that fits is written in place too
+        let text = b"abcdefgh".repeat(1000);
+        b.add("z.bin", &text, Compress::Zstd).unwrap();
+        let offset = b.entry("z.bin").unwrap().offset();
+        b.replace_in_place("z.bin", &b"hgfedcba".repeat(1000), Compress::Zstd)
+            .unwrap();
+        b.save().unwrap();
+        assert_eq!(b.entry("z.bin").unwrap().offset(), offset);
+        assert_eq!(b.read("z.bin").unwrap().as_ref(), b"hgfedcba".repeat(1000));
+
+        fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_pack_stats() {
+        let src_dir = "test_pack_stats_src";
+        let _ = fs::remove_dir_all(src_dir);
+        fs::create_dir_all(format!("{}/.git/objects", src_dir)).unwrap();
+        fs::create_dir_all(format!("{}/docs", src_dir)).unwrap();
+        fs::write(format!("{}/.git/objects/a", src_dir), b"git").unwrap();
+        fs::write(
+            format!("{}/docs/readme.md", src_dir),
+            "readme ".repeat(1000),
+        )
+        .unwrap();
+        fs::write(format!("{}/docs/scratch.tmp", src_dir), b"tmp").unwrap();
+        fs::write(format!("{}/main.rs", src_dir), b"main").unwrap();
+
+        let options = PackOptions::new().exclude([".git", "*.tmp"]).unwrap();
+        let mut b = Bindle::in_memory();
+        let stats = b
+            .pack_with_options(src_dir, Compress::Zstd, 3, &options)
+            .unwrap();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(stats.total_uncompressed, 7004);
+        assert_eq!(
+            stats.total_compressed,
+            b.entries().map(|(_, e)| e.compressed_size()).sum::<u64>()
+        );
+        assert!(stats.ratio() < 0.1);
+
+        // Nothing is skipped without exclude patterns
+        let stats = Bindle::in_memory()
+            .pack_parallel(src_dir, Compress::None, 3, 2)
+            .unwrap();
+        assert_eq!(stats.files, 4);
+        assert_eq!(stats.skipped, 0);
+        assert_eq!(stats.total_uncompressed, stats.total_compressed);
+
+        fs::remove_dir_all(src_dir).ok();
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn test_import_tar() {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "./docs/", io::empty())
+            .unwrap();
+        let text = "readme ".repeat(1000);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_mtime(1_700_000_000);
+        builder
+            .append_data(&mut header, "./docs/readme.md", text.as_bytes())
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "docs/latest", "readme.md")
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Fifo);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "pipe", io::empty())
+            .unwrap();
+        let tarball = builder.into_inner().unwrap();
+
+        let mut b = Bindle::in_memory();
+        let stats = b.import_tar(&tarball[..], Compress::Zstd).unwrap();
+        b.save().unwrap();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(
+            b.names().collect::<Vec<_>>(),
+            ["docs/latest", "docs/readme.md"]
+        );
+        assert_eq!(b.read("docs/readme.md").unwrap().as_ref(), text.as_bytes());
+        assert_eq!(
+            b.index()["docs/readme.md"].compression_type(),
+            Compress::Zstd
+        );
+        assert_eq!(
+            b.mtime("docs/readme.md"),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000))
+        );
+        assert!(b.index()["docs/latest"].is_symlink());
+        assert_eq!(b.read("docs/latest").unwrap().as_ref(), b"readme.md");
+
+        // A tarball cut off inside a file leaves no partial entry behind
+        let mut b = Bindle::in_memory();
+        assert!(b.import_tar(&tarball[..2048], Compress::Zstd).is_err());
+        assert!(!b.exists("docs/readme.md"));
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut b = Bindle::in_memory();
+        b.add("a.txt", b"first", Compress::None).unwrap();
+        b.add("b.bin", &[7u8; 5000], Compress::Zstd).unwrap();
+        b.save().unwrap();
+        let mut bytes = b.into_bytes().unwrap();
+        // Left over from an interrupted save
+        bytes.extend_from_slice(&[0xab; 100]);
+
+        let mut b = Bindle::from_vec(bytes).unwrap();
+        assert_eq!(b.len(), 2);
+        assert_eq!(b.read("a.txt").unwrap().as_ref(), b"first");
+        assert_eq!(b.read("b.bin").unwrap().as_ref(), &[7u8; 5000]);
+        assert!(b.verify().unwrap().iter().all(|(_, ok)| *ok));
+
+        b.add("c.txt", b"third", Compress::Gzip).unwrap();
+        b.save().unwrap();
+        let bytes = b.into_bytes().unwrap();
+        let b = Bindle::from_bytes(&bytes).unwrap();
+        assert_eq!(b.len(), 3);
+        assert_eq!(b.read("c.txt").unwrap().as_ref(), b"third");
+
+        assert!(Bindle::from_vec(b"not an archive".to_vec()).is_err());
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn test_export_tar() {
+        let mtime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+        let big: Vec<u8> = (0..100_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let mut b = Bindle::in_memory();
+        b.add("docs/readme.md", b"readme", Compress::None).unwrap();
+        b.add("data/big.bin", &big, Compress::Zstd).unwrap();
+        b.add("data/small.gz", b"gzipped", Compress::Gzip).unwrap();
+        b.set_mtime("docs/readme.md", mtime);
+        b.save().unwrap();
+        let src_dir = "test_export_tar_src";
+        let _ = fs::remove_dir_all(src_dir);
+        fs::create_dir_all(src_dir).unwrap();
+        fs::write(format!("{}/target.txt", src_dir), b"target").unwrap();
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("target.txt", format!("{}/link", src_dir)).unwrap();
+            let options = PackOptions::new().symlinks(Symlinks::Store);
+            b.pack_with_options(src_dir, Compress::None, 3, &options)
+                .unwrap();
+            b.save().unwrap();
+        }
+        fs::remove_dir_all(src_dir).ok();
+
+        let mut tarball = Vec::new();
+        b.export_tar(&mut tarball).unwrap();
+        let mut copy = Bindle::in_memory();
+        copy.import_tar(&tarball[..], Compress::None).unwrap();
+        copy.save().unwrap();
+        assert_eq!(
+            copy.names().collect::<Vec<_>>(),
+            b.names().collect::<Vec<_>>()
+        );
+        for name in b.names() {
+            assert_eq!(copy.read(name).unwrap(), b.read(name).unwrap(), "{}", name);
+            assert_eq!(
+                copy.index()[name].is_symlink(),
+                b.index()[name].is_symlink()
+            );
+        }
+        assert_eq!(copy.mtime("docs/readme.md"), Some(mtime));
+
+        // A damaged entry fails the export before its header is written
+        let mut bytes = b.into_bytes().unwrap();
+        let offset = Bindle::from_bytes(&bytes).unwrap().index()["docs/readme.md"].offset();
+        bytes[offset as usize] ^= 0xff;
+        let b = Bindle::from_vec(bytes).unwrap();
+        let mut tarball = Vec::new();
+        let err = b.export_tar(&mut tarball).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("docs/readme.md"));
+        assert!(!tarball.windows(14).any(|w| w == b"docs/readme.md"));
+
+        // So does an entry whose index records the wrong size
+        let mut b = Bindle::in_memory();
+        b.add("short.txt", b"short", Compress::None).unwrap();
+        b.index
+            .get_mut("short.txt")
+            .unwrap()
+            .set_uncompressed_size(100);
+        let mut tarball = Vec::new();
+        let err = b.export_tar(&mut tarball).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("decoded 5 bytes"), "{}", err);
+    }
+
+    #[test]
+    fn test_split_join() {
+        let path = "test_split.bindl";
+        let prefix = "test_split_part";
+        let joined_path = "test_split_joined.bindl";
+        let _ = fs::remove_file(path);
+        let record = |i: u32| format!(r#"{{"id":{},"kind":"user","name":"user-{}"}}"#, i, i);
+        let big: Vec<u8> = (0..5_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let max_bytes = 8192;
+
+        let mut b = Bindle::open(path).unwrap();
+        for i in 0..200 {
+            b.add(
+                &format!("{:03}.json", i),
+                record(i).as_bytes(),
+                Compress::Zstd,
+            )
+            .unwrap();
+        }
+        let dict = b.train_zstd_dictionary(1024).unwrap();
+        b.set_zstd_dictionary(dict).unwrap();
+        b.add("dict.json", record(1000).as_bytes(), Compress::Zstd)
+            .unwrap();
+        b.add("big.bin", &big, Compress::None).unwrap();
+        b.set_mtime(
+            "big.bin",
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000),
+        );
+        b.save().unwrap();
+
+        let volumes = b.split(prefix, max_bytes).unwrap();
+        assert!(volumes.len() > 2);
+        assert_eq!(volumes[0], std::path::Path::new("test_split_part.001.bndl"));
+        let mut names = Vec::new();
+        for volume in &volumes {
+            // Every volume is a complete archive within the cap, except the one holding the
+            // entry that is larger than the cap on its own
+            let v = Bindle::open_readonly(volume).unwrap();
+            assert!(v.verify().unwrap().iter().all(|(_, ok)| *ok));
+            let len = fs::metadata(volume).unwrap().len();
+            if v.entry("big.bin").is_some() {
+                assert_eq!(v.len(), 1);
+            } else {
+                assert!(len <= max_bytes, "{} is {} bytes", volume.display(), len);
+            }
+            names.extend(v.names().map(str::to_string));
+        }
+        assert_eq!(names, b.names().collect::<Vec<_>>());
+
+        let joined = Bindle::join(&volumes, joined_path).unwrap();
+        drop(joined);
+        let joined = Bindle::open_readonly(joined_path).unwrap();
+        assert_eq!(
+            joined.names().collect::<Vec<_>>(),
+            b.names().collect::<Vec<_>>()
+        );
+        for name in b.names() {
+            assert_eq!(
+                joined.read(name).unwrap(),
+                b.read(name).unwrap(),
+                "{}",
+                name
+            );
+        }
+        assert!(joined.entry("dict.json").unwrap().uses_dictionary());
+        assert_eq!(joined.mtime("big.bin"), b.mtime("big.bin"));
+        let err = Bindle::join(&volumes, &volumes[0]).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // An empty archive still gives one volume
+        let empty = Bindle::in_memory();
+        let empty_volumes = empty.split(prefix, max_bytes).unwrap();
+        assert_eq!(empty_volumes.len(), 1);
+        assert!(Bindle::open_readonly(&empty_volumes[0]).unwrap().is_empty());
+
+        for volume in &volumes {
+            fs::remove_file(volume).ok();
+        }
+        fs::remove_file(path).ok();
+        fs::remove_file(joined_path).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_through_symlink() {
+        let src_dir = "test_extract_through_symlink_src";
+        let dest = "test_extract_through_symlink_out";
+        let outside = std::env::current_dir()
+            .unwrap()
+            .join("test_extract_through_symlink_outside");
+        for dir in [src_dir, dest] {
+            let _ = fs::remove_dir_all(dir);
+        }
+        let _ = fs::remove_dir_all(&outside);
+        fs::create_dir_all(src_dir).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, format!("{}/!", src_dir)).unwrap();
+
+        let mut b = Bindle::in_memory();
+        let options = PackOptions::new().symlinks(Symlinks::Store);
+        b.pack_with_options(src_dir, Compress::None, 3, &options)
+            .unwrap();
+        b.add("./!/pwned.txt", b"pwned", Compress::None).unwrap();
+        b.add("!//pwned.txt", b"pwned", Compress::None).unwrap();
+        b.add("dir/./pwned.txt", b"pwned", Compress::None).unwrap();
+
+        // Extracting one entry at a time, like bindle read --output-dir does, must not write
+        // through the link an earlier call created
+        b.extract("!", dest).unwrap();
+        for name in ["./!/pwned.txt", "!//pwned.txt", "dir/./pwned.txt"] {
+            let err = b.extract(name, dest).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", name);
+        }
+        assert!(b.unpack(dest).is_err());
+        assert!(fs::read_dir(&outside).unwrap().next().is_none());
+
+        // Links that were already in the destination aren't followed either
+        let mut b = Bindle::in_memory();
+        b.add("linked/pwned.txt", b"pwned", Compress::None).unwrap();
+        b.add("file.txt", b"data", Compress::None).unwrap();
+        std::os::unix::fs::symlink(&outside, format!("{}/linked", dest)).unwrap();
+        std::os::unix::fs::symlink(outside.join("file.txt"), format!("{}/file.txt", dest)).unwrap();
+        let err = b.extract("linked/pwned.txt", dest).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(b.unpack(dest).is_err());
+        b.extract("file.txt", dest).unwrap();
+        assert!(fs::read_dir(&outside).unwrap().next().is_none());
+        assert_eq!(fs::read(format!("{}/file.txt", dest)).unwrap(), b"data");
+
+        for dir in [src_dir, dest] {
+            fs::remove_dir_all(dir).ok();
+        }
+        fs::remove_dir_all(&outside).ok();
+    }
+
+    #[test]
+    fn test_reserved_dict_name() {
+        let path = "test_reserved_dict_name.bindl";
+        let mut b = Bindle::create(path).unwrap();
+        b.add("a.txt", b"a", Compress::None).unwrap();
+
+        let err = b.add(DICT_ENTRY, b"x", Compress::None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(b.replace(DICT_ENTRY, b"x", Compress::None).is_err());
+        assert!(b.add_dedup(DICT_ENTRY, b"a", Compress::None).is_err());
+        assert!(b.writer(DICT_ENTRY, Compress::None).is_err());
+        assert!(b.rename("a.txt", DICT_ENTRY).is_err());
+        assert!(b.rename_overwrite("a.txt", DICT_ENTRY).is_err());
+        assert!(!b.exists(DICT_ENTRY));
+        assert!(b.exists("a.txt"));
+
+        // Archives are written as version 4, older readers refuse them
+        b.save().unwrap();
+        assert_eq!(b.version(), 4);
+        assert_eq!(&fs::read(path).unwrap()[..HEADER_SIZE], b"BINDL004");
+        fs::remove_file(path).ok();
+    }
 }
@@ -0,0 +1,56 @@
+use crate::entry::Entry;
+use crate::error::BindleError;
+
+/// Size limits enforced when reading untrusted archives, set with
+/// [`Bindle::set_limits()`](crate::Bindle::set_limits) or the `set_limits()` method of
+/// [`BindleRef`](crate::BindleRef), [`ReadOnlyBindle`](crate::ReadOnlyBindle) and
+/// [`ReadOnlyView`](crate::ReadOnlyView). Views start with the limits of the archive they came from.
+///
+/// Sizes are checked against the uncompressed sizes recorded in the index before anything is
+/// allocated, and decoding stops with an error if an entry expands past its recorded size.
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::{Bindle, Limits};
+///
+/// let mut archive = Bindle::open_readonly("upload.bndl")?;
+/// archive.set_limits(Limits {
+///     max_entry_size: 16 << 20,
+///     max_total_size: 256 << 20,
+/// });
+/// archive.unpack("upload")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Largest uncompressed entry that [`read()`](crate::Bindle::read) and
+    /// [`reader()`](crate::Bindle::reader) accept, 1 GiB by default.
+    pub max_entry_size: u64,
+    /// Largest total uncompressed size that [`unpack()`](crate::Bindle::unpack) extracts, unlimited
+    /// by default.
+    pub max_total_size: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_entry_size: 1 << 30,
+            max_total_size: u64::MAX,
+        }
+    }
+}
+
+impl Limits {
+    /// Returns [`BindleError::TooLarge`] if `entry` is larger than the entry size limit.
+    pub(crate) fn check_entry(&self, name: &str, entry: &Entry) -> Result<(), BindleError> {
+        if entry.uncompressed_size() > self.max_entry_size {
+            return Err(BindleError::TooLarge {
+                name: name.to_string(),
+                size: entry.uncompressed_size(),
+                limit: self.max_entry_size,
+            });
+        }
+        Ok(())
+    }
+}
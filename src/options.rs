@@ -0,0 +1,93 @@
+/// Options for [`Bindle::open_with()`](crate::Bindle::open_with).
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::{Bindle, Compress, Options};
+///
+/// let options = Options::new().autosave(true);
+/// let mut archive = Bindle::open_with("data.bndl", options)?;
+/// archive.add("file.txt", b"data", Compress::None)?;
+/// // Saved when `archive` is dropped
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Options {
+    /// Save uncommitted changes when the archive is dropped. Errors can't be returned from `drop`,
+    /// so they are discarded, call [`Bindle::save()`](crate::Bindle::save) to handle them.
+    pub autosave: bool,
+    /// How [`Bindle::save()`](crate::Bindle::save) syncs the file to disk.
+    pub sync: SyncPolicy,
+    /// How entry data is read from the file.
+    pub backend: Backend,
+}
+
+impl Options {
+    /// Creates the default options: no autosave, [`SyncPolicy::Data`] and [`Backend::Mmap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Saves uncommitted changes when the archive is dropped, off by default. Errors from the save
+    /// are discarded, see [`autosave`](Options::autosave).
+    pub fn autosave(mut self, enabled: bool) -> Self {
+        self.autosave = enabled;
+        self
+    }
+
+    /// Sets how [`Bindle::save()`](crate::Bindle::save) syncs the file, [`SyncPolicy::Data`] by
+    /// default.
+    pub fn sync(mut self, policy: SyncPolicy) -> Self {
+        self.sync = policy;
+        self
+    }
+
+    /// Sets how entry data is read from the file, [`Backend::Mmap`] by default.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+}
+
+/// When [`Bindle::save()`](crate::Bindle::save) calls `fsync`, set with [`Options::sync()`] or
+/// [`Bindle::set_sync_policy()`](crate::Bindle::set_sync_policy).
+///
+/// Stronger policies cost a round trip to the disk per save.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Never sync, leave flushing to the OS. Fastest, but after a power failure the last saves may be
+    /// lost, and the archive falls back to the last save whose index reached the disk intact.
+    None,
+    /// Sync the data and index before writing the footer, so a crash can't leave a footer pointing at
+    /// missing data. The footer itself isn't synced, so the save may still be lost on power failure.
+    #[default]
+    Data,
+    /// Like [`Data`](SyncPolicy::Data), and also sync the footer and file length once written. The
+    /// save is on disk when it returns.
+    All,
+}
+
+/// How a file-backed archive reads entry data, set with [`Options::backend()`].
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::{Backend, Bindle, Options};
+///
+/// let options = Options::new().backend(Backend::Pread);
+/// let archive = Bindle::open_with("huge.bndl", options)?;
+/// let data = archive.read("disk.img")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Map the file into memory, uncompressed entries are borrowed from the map without copying.
+    #[default]
+    Mmap,
+    /// Read with positioned reads (`pread` on Unix) instead of mapping the file, for archives larger
+    /// than the address space, such as on 32-bit targets. Only the index is read when opening.
+    /// [`read()`](crate::Bindle::read) allocates and reads the entry's stored bytes, readers stream
+    /// from the file, and [`read_ref()`](crate::Bindle::read_ref) always returns `None`.
+    Pread,
+}
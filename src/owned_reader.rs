@@ -0,0 +1,77 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::entry::Entry;
+use crate::reader::Reader;
+use crate::storage::Region;
+use crate::view::ViewData;
+
+/// A streaming reader for an entry that owns its data and can be moved to another thread.
+///
+/// Created by [`Bindle::owned_reader()`](crate::Bindle::owned_reader) or
+/// [`ReadOnlyView::owned_reader()`](crate::ReadOnlyView::owned_reader). Works like a [`Reader`] but
+/// keeps the memory map alive through an [`Arc`](std::sync::Arc) instead of borrowing the archive,
+/// so it is `'static` and `Send`.
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::Bindle;
+///
+/// let archive = Bindle::open("data.bndl")?;
+/// let mut reader = archive.owned_reader("large.bin")?;
+/// let worker = std::thread::spawn(move || {
+///     let n = std::io::copy(&mut reader, &mut std::io::sink())?;
+///     reader.verify_crc32()?;
+///     Ok::<u64, std::io::Error>(n)
+/// });
+/// worker.join().unwrap()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct OwnedReader {
+    // Declared first so it's dropped before the data it points into
+    reader: Reader<'static>,
+    _data: Option<ViewData>,
+}
+
+impl OwnedReader {
+    /// Creates a reader over the stored bytes of `entry` in `data`.
+    pub(crate) fn new(
+        data: ViewData,
+        name: &str,
+        entry: &Entry,
+        dict: Option<&[u8]>,
+    ) -> io::Result<Self> {
+        // SAFETY: the bytes or file live in an `Arc` held by `_data` until the reader is dropped, and
+        // moving the `Arc` doesn't move them
+        let stored: Region<'static> = unsafe { std::mem::transmute(data.stored(entry)?) };
+        Ok(OwnedReader {
+            reader: crate::bindle::entry_reader(stored, name, entry, dict)?,
+            _data: Some(data),
+        })
+    }
+
+    /// Creates a reader over data that was already decoded, such as a decrypted entry.
+    pub(crate) fn decoded(name: &str, data: Vec<u8>) -> Self {
+        OwnedReader {
+            reader: Reader::decoded(name, data),
+            _data: None,
+        }
+    }
+
+    /// Verifies the CRC32 checksum of the data read so far, see [`Reader::verify_crc32()`].
+    pub fn verify_crc32(&self) -> io::Result<()> {
+        self.reader.verify_crc32()
+    }
+}
+
+impl Read for OwnedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Seek for OwnedReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
@@ -0,0 +1,143 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::io;
+
+use crate::entry::Entry;
+
+/// Options for [`Bindle::pack_with_options()`](crate::Bindle::pack_with_options).
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::{Bindle, Compress, PackOptions};
+///
+/// let mut archive = Bindle::open("data.bndl")?;
+/// let options = PackOptions::new().exclude([".git", "node_modules", "*.tmp"])?;
+/// archive.pack_with_options("site", Compress::Zstd, 3, &options)?;
+/// archive.save()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PackOptions {
+    exclude: GlobSet,
+    pub(crate) symlinks: Symlinks,
+    pub(crate) dedup: bool,
+}
+
+/// Progress reported by [`Bindle::pack_with_progress()`](crate::Bindle::pack_with_progress) and
+/// [`Bindle::unpack_with_progress()`](crate::Bindle::unpack_with_progress).
+///
+/// Each file is reported twice, once before it's processed with `finished` unset and once after.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackEvent<'a> {
+    /// Stored name of the current file.
+    pub name: &'a str,
+    /// Uncompressed bytes of the files finished so far.
+    pub bytes: u64,
+    /// Number of files finished so far, including the current one once it's finished.
+    pub done: usize,
+    /// Number of files that will be processed.
+    pub total: usize,
+    /// False right before the current file is processed, true right after.
+    pub finished: bool,
+    /// Set once an unpacked symlink entry is finished if the platform couldn't create the link, so
+    /// its target was written as a regular file instead. Always false when packing.
+    pub link_as_file: bool,
+}
+
+/// Summary of the files added by [`Bindle::pack()`](crate::Bindle::pack) and the other `pack`
+/// methods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PackStats {
+    /// Number of files and symbolic links added.
+    pub files: usize,
+    /// Uncompressed size of the added files.
+    pub total_uncompressed: u64,
+    /// Stored size of the added files. Deduplicated files count in full even though their data is
+    /// stored once.
+    pub total_compressed: u64,
+    /// Number of files and directories left out, because they're excluded, are links skipped with
+    /// [`Symlinks::Skip`] or lead back to a directory already being walked.
+    pub skipped: usize,
+}
+
+impl PackStats {
+    /// Returns `total_compressed / total_uncompressed`, `1.0` if nothing was added.
+    pub fn ratio(&self) -> f64 {
+        if self.total_uncompressed > 0 {
+            self.total_compressed as f64 / self.total_uncompressed as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// Counts the entry `entry` as added.
+    pub(crate) fn add(&mut self, entry: &Entry) {
+        self.files += 1;
+        self.total_uncompressed += entry.uncompressed_size();
+        self.total_compressed += entry.compressed_size();
+    }
+}
+
+/// How [`Bindle::pack_with_options()`](crate::Bindle::pack_with_options) treats symbolic links.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symlinks {
+    /// Pack the file or directory the link points to. Links to a directory that is already being
+    /// walked are skipped, so cycles can't recurse forever.
+    #[default]
+    Follow,
+    /// Store the link target as a symlink entry, which unpacking recreates as a link. Where links
+    /// can't be created, unpacking writes the target path as a regular file with a warning.
+    Store,
+    /// Leave links out of the archive.
+    Skip,
+}
+
+impl PackOptions {
+    /// Creates options that pack every file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips files and directories matching any of the glob `patterns`.
+    ///
+    /// Patterns are matched against the stored name, relative to the packed directory. Patterns
+    /// without a `/` also match the last path component, so `.git` skips `.git` directories at any
+    /// depth. Excluded directories are not descended into. Replaces any previous exclude list and
+    /// returns an [`io::ErrorKind::InvalidInput`] error if a pattern is invalid.
+    pub fn exclude<S: AsRef<str>>(
+        mut self,
+        patterns: impl IntoIterator<Item = S>,
+    ) -> io::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern.as_ref())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            builder.add(glob);
+        }
+        self.exclude = builder
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(self)
+    }
+
+    /// Sets how symbolic links are handled, [`Symlinks::Follow`] by default.
+    pub fn symlinks(mut self, mode: Symlinks) -> Self {
+        self.symlinks = mode;
+        self
+    }
+
+    /// Stores files with identical contents once, see [`Bindle::set_dedup()`](crate::Bindle::set_dedup).
+    pub fn dedup(mut self, enabled: bool) -> Self {
+        self.dedup = enabled;
+        self
+    }
+
+    /// Returns true if the file or directory stored as `name` should be skipped.
+    pub(crate) fn is_excluded(&self, name: &str) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let file_name = name.rsplit('/').next().unwrap_or(name);
+        self.exclude.is_match(name) || self.exclude.is_match(file_name)
+    }
+}
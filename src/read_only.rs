@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zerocopy::FromBytes;
+
+use crate::bindle::{check_bounds, check_index, parse_index};
+use crate::crypto;
+use crate::entry::{Entry, EntryMeta, Footer};
+use crate::error::BindleError;
+use crate::limits::Limits;
+use crate::reader::Reader;
+use crate::{DICT_ENTRY, FOOTER_MAGIC, FOOTER_SIZE, HEADER_SIZE, MAX_PREALLOC, header_version};
+
+/// A read-only archive backed by any [`Read`] + [`Seek`] source.
+///
+/// Created by [`Bindle::from_reader`](crate::Bindle::from_reader). Only the index is loaded up front,
+/// entry data is read from the source on demand. Since there is no memory map, every read returns owned data.
+///
+/// # Example
+///
+/// ```no_run
+/// use bindle_file::Bindle;
+///
+/// let bytes = std::fs::read("data.bndl")?;
+/// let mut archive = Bindle::from_reader(std::io::Cursor::new(bytes))?;
+/// let data = archive.read("file.txt")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct ReadOnlyBindle<R> {
+    pub(crate) inner: R,
+    pub(crate) index: BTreeMap<String, Entry>,
+    pub(crate) meta: BTreeMap<String, EntryMeta>,
+    /// The zstd dictionary, unless there is none or it's encrypted.
+    pub(crate) dict: Option<Vec<u8>>,
+    pub(crate) limits: Limits,
+}
+
+impl<R: Read + Seek> ReadOnlyBindle<R> {
+    pub(crate) fn new(mut inner: R) -> io::Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        if len < (HEADER_SIZE + FOOTER_SIZE) as u64 {
+            return Err(BindleError::TooSmall.into());
+        }
+
+        let mut header = [0u8; HEADER_SIZE];
+        inner.seek(SeekFrom::Start(0))?;
+        inner.read_exact(&mut header)?;
+        let version = header_version(&header)?;
+
+        let footer_pos = len - FOOTER_SIZE as u64;
+        let mut footer_bytes = [0u8; FOOTER_SIZE];
+        inner.seek(SeekFrom::Start(footer_pos))?;
+        inner.read_exact(&mut footer_bytes)?;
+        let footer =
+            Footer::read_from_bytes(&footer_bytes).map_err(|_| BindleError::InvalidFooter)?;
+        if footer.magic() != FOOTER_MAGIC || footer.index_offset() > footer_pos {
+            return Err(BindleError::InvalidFooter.into());
+        }
+
+        let mut index_bytes = vec![0u8; (footer_pos - footer.index_offset()) as usize];
+        inner.seek(SeekFrom::Start(footer.index_offset()))?;
+        inner.read_exact(&mut index_bytes)?;
+        let index_bytes = check_index(&index_bytes, version)?;
+        let (mut index, meta) = parse_index(index_bytes, footer.entry_count(), version)?;
+        check_bounds(&index, footer.index_offset())?;
+        let dict = match index.remove(DICT_ENTRY) {
+            Some(entry) if !entry.is_encrypted() => {
+                let stored = read_stored(&mut inner, &entry)?;
+                let computed_crc = crc32fast::hash(&stored);
+                if computed_crc != entry.crc32() {
+                    return Err(BindleError::CrcMismatch {
+                        name: DICT_ENTRY.to_string(),
+                        expected: entry.crc32(),
+                        got: computed_crc,
+                    }
+                    .into());
+                }
+                Some(stored)
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            inner,
+            index,
+            meta,
+            dict,
+            limits: Limits::default(),
+        })
+    }
+
+    /// Reads an entry from the source, decompressing if needed.
+    ///
+    /// Returns [`BindleError::NotFound`] if the entry doesn't exist or [`BindleError::CrcMismatch`]
+    /// if CRC32 verification fails.
+    pub fn read(&mut self, name: &str) -> Result<Vec<u8>, BindleError> {
+        let entry = *self
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        self.limits.check_entry(name, &entry)?;
+        if entry.is_encrypted() {
+            return Err(crypto::missing_key().into());
+        }
+
+        let stored = read_stored(&mut self.inner, &entry)?;
+        let mut out = Vec::with_capacity(entry.uncompressed_size().min(MAX_PREALLOC) as usize);
+        Reader::new(&stored, name, &entry, self.dict.as_deref())?.read_to_end(&mut out)?;
+
+        let computed_crc = crc32fast::hash(&out);
+        if computed_crc != entry.crc32() {
+            return Err(BindleError::CrcMismatch {
+                name: name.to_string(),
+                expected: entry.crc32(),
+                got: computed_crc,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Reads an entry and writes it to the given writer.
+    ///
+    /// Returns the number of bytes written. Verifies CRC32 before writing.
+    pub fn read_to<W: Write>(&mut self, name: &str, mut w: W) -> io::Result<u64> {
+        let data = self.read(name)?;
+        w.write_all(&data)?;
+        Ok(data.len() as u64)
+    }
+
+    /// Returns the number of entries in the archive.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the archive contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns true if an entry with the given name exists.
+    pub fn exists(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Iterates over entry names and their metadata in name order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.index
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Iterates over entry names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the stored modification time of an entry, if it has one.
+    pub fn mtime(&self, name: &str) -> Option<SystemTime> {
+        let nanos = self.meta.get(name)?.mtime?;
+        Some(UNIX_EPOCH + Duration::from_nanos(nanos))
+    }
+
+    /// Returns the metadata blob stored with an entry, if it has one.
+    pub fn meta(&self, name: &str) -> Option<&[u8]> {
+        self.meta.get(name)?.user.as_deref()
+    }
+
+    /// Returns the SHA-256 of an entry's data, if it stores one.
+    pub fn sha256(&self, name: &str) -> Option<[u8; 32]> {
+        self.meta.get(name)?.sha256
+    }
+
+    /// Sets the size limits enforced when reading, see [`Limits`].
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the size limits enforced when reading.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Returns a reference to the archive index.
+    pub fn index(&self) -> &BTreeMap<String, Entry> {
+        &self.index
+    }
+
+    /// Consumes the archive, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Reads the stored bytes of `entry` from `inner`.
+fn read_stored<R: Read + Seek>(inner: &mut R, entry: &Entry) -> io::Result<Vec<u8>> {
+    let mut stored = vec![0u8; entry.compressed_size() as usize];
+    inner.seek(SeekFrom::Start(entry.offset()))?;
+    inner.read_exact(&mut stored)?;
+    Ok(stored)
+}
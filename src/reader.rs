@@ -1,9 +1,20 @@
 use crc32fast::Hasher;
+use flate2::read::GzDecoder;
 use std::io::{self, BufReader, Read, Seek, SeekFrom};
 
-pub(crate) enum Either<A, B> {
-    Left(A),
-    Right(B),
+use crate::ZSTD_WINDOW_LOGS;
+use crate::compress::Compress;
+use crate::entry::Entry;
+use crate::error::BindleError;
+use crate::seekable::SeekTable;
+use crate::storage::{Region, RegionReader};
+
+pub(crate) enum Decoder<'a> {
+    Zstd(zstd::Decoder<'static, BufReader<RegionReader<'a>>>),
+    Gzip(GzDecoder<RegionReader<'a>>),
+    Raw(RegionReader<'a>),
+    /// Data decoded up front, such as a decrypted entry.
+    Decoded(io::Cursor<Vec<u8>>),
 }
 
 /// A streaming reader for archive entries.
@@ -21,56 +32,188 @@ pub(crate) enum Either<A, B> {
 /// # Ok::<(), std::io::Error>(())
 /// ```
 pub struct Reader<'a> {
-    pub(crate) decoder:
-        Either<zstd::Decoder<'static, BufReader<io::Cursor<&'a [u8]>>>, io::Cursor<&'a [u8]>>,
+    pub(crate) decoder: Decoder<'a>,
     pub(crate) crc32_hasher: Hasher,
     pub(crate) expected_crc32: u32,
+    pub(crate) region: Region<'a>,
+    pub(crate) seek_table: Option<SeekTable>,
+    pub(crate) position: u64,
+    /// Name of the entry, for errors.
+    pub(crate) name: String,
+    /// Uncompressed size recorded in the index, decoding past it is an error.
+    pub(crate) size: u64,
 }
 
 impl<'a> Read for Reader<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let n = match &mut self.decoder {
-            Either::Left(x) => x.read(buf)?,
-            Either::Right(x) => x.read(buf)?,
+            Decoder::Zstd(x) => x.read(buf)?,
+            Decoder::Gzip(x) => x.read(buf)?,
+            Decoder::Raw(x) => x.read(buf)?,
+            Decoder::Decoded(x) => x.read(buf)?,
         };
 
         if n > 0 {
+            // Stop a crafted stream from expanding without bound
+            if self.position + n as u64 > self.size {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Entry data is larger than its recorded size",
+                ));
+            }
             self.crc32_hasher.update(&buf[..n]);
+            self.position += n as u64;
         }
 
         Ok(n)
     }
 }
 
-// Note: Seeking is only supported for uncompressed entries in this simple implementation.
-// Seeking in compressed streams requires a frame-aware decoder.
+// Note: Seeking is supported for uncompressed entries and for zstd entries written by
+// `Bindle::seekable_writer`, which decodes from the nearest frame boundary.
+// Other compressed streams would need a frame-aware decoder.
 impl<'a> Seek for Reader<'a> {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        match &mut self.decoder {
-            Either::Left(_) => Err(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "Seeking not supported on compressed streams",
-            )),
-            Either::Right(x) => x.seek(pos),
+        let table = match (&mut self.decoder, &self.seek_table) {
+            (Decoder::Raw(x), _) => {
+                self.position = x.seek(pos)?;
+                return Ok(self.position);
+            }
+            (Decoder::Decoded(x), _) => {
+                self.position = x.seek(pos)?;
+                return Ok(self.position);
+            }
+            (Decoder::Zstd(_), Some(table)) => table,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Seeking not supported on compressed streams",
+                ));
+            }
+        };
+
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+            SeekFrom::End(n) => table.decompressed_size().checked_add_signed(n),
         }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        // Seeking past the end is allowed, the last frame is drained and reads return EOF
+        let frame = table
+            .find(target)
+            .or_else(|| table.last())
+            .copied()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Empty seek table"))?;
+
+        let frames = self
+            .region
+            .slice(frame.compressed_offset..table.frames_len())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Corrupt seek table"))?;
+        let mut decoder = zstd::Decoder::new(frames.reader())?;
+        let skip = target - frame.decompressed_offset;
+        io::copy(&mut (&mut decoder).take(skip), &mut io::sink())?;
+
+        self.decoder = Decoder::Zstd(decoder);
+        self.position = target;
+        Ok(target)
     }
 }
 
 impl<'a> Reader<'a> {
+    /// Creates a reader over the stored bytes of `entry`, picking a decoder from its compression type.
+    ///
+    /// `dict` is the archive's zstd dictionary, needed for entries compressed with it.
+    pub(crate) fn new(
+        data: &'a [u8],
+        name: &str,
+        entry: &Entry,
+        dict: Option<&[u8]>,
+    ) -> io::Result<Self> {
+        Self::from_region(Region::Memory(data), name, entry, dict)
+    }
+
+    /// Creates a reader over `region`, the stored bytes of `entry`, see [`new()`](Reader::new).
+    pub(crate) fn from_region(
+        region: Region<'a>,
+        name: &str,
+        entry: &Entry,
+        dict: Option<&[u8]>,
+    ) -> io::Result<Self> {
+        let cursor = region.reader();
+        let mut decoder = match entry.compression_type() {
+            Compress::Zstd if entry.uses_dictionary() => {
+                let dict = dict.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Entry was compressed with a zstd dictionary the archive doesn't have",
+                    )
+                })?;
+                Decoder::Zstd(zstd::Decoder::with_dictionary(
+                    BufReader::new(cursor),
+                    dict,
+                )?)
+            }
+            Compress::Zstd => Decoder::Zstd(zstd::Decoder::new(cursor)?),
+            Compress::Gzip => Decoder::Gzip(GzDecoder::new(cursor)),
+            Compress::None | Compress::Auto => Decoder::Raw(cursor),
+        };
+        if let Decoder::Zstd(decoder) = &mut decoder
+            && entry.uses_long_window()
+        {
+            decoder.window_log_max(*ZSTD_WINDOW_LOGS.end())?;
+        }
+        let seek_table = if entry.is_seekable() && matches!(decoder, Decoder::Zstd(_)) {
+            Some(SeekTable::parse(region)?)
+        } else {
+            None
+        };
+        Ok(Reader {
+            decoder,
+            crc32_hasher: Hasher::new(),
+            expected_crc32: entry.crc32(),
+            region,
+            seek_table,
+            position: 0,
+            size: entry.uncompressed_size(),
+            name: name.to_string(),
+        })
+    }
+
+    /// Creates a reader over data that was already decoded and verified, such as a decrypted entry.
+    pub(crate) fn decoded(name: &str, data: Vec<u8>) -> Self {
+        Reader {
+            expected_crc32: crc32fast::hash(&data),
+            size: data.len() as u64,
+            decoder: Decoder::Decoded(io::Cursor::new(data)),
+            crc32_hasher: Hasher::new(),
+            region: Region::Memory(&[]),
+            seek_table: None,
+            position: 0,
+            name: name.to_string(),
+        }
+    }
+
     /// Verifies the CRC32 checksum of the data read so far.
     ///
     /// Should be called after reading all data to ensure integrity.
-    /// Returns an error if the computed CRC32 doesn't match the expected value.
+    /// Returns an error wrapping [`BindleError::CrcMismatch`] if the computed CRC32 doesn't match
+    /// the expected value.
+    /// The checksum covers everything passed through [`Read`], so it won't match after seeking.
     pub fn verify_crc32(&self) -> io::Result<()> {
         let computed_crc = self.crc32_hasher.clone().finalize();
         if computed_crc != self.expected_crc32 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "CRC32 mismatch: expected {:x}, got {:x}",
-                    self.expected_crc32, computed_crc
-                ),
-            ));
+            return Err(BindleError::CrcMismatch {
+                name: self.name.clone(),
+                expected: self.expected_crc32,
+                got: computed_crc,
+            }
+            .into());
         }
         Ok(())
     }
@@ -0,0 +1,127 @@
+use std::io::{self, Write};
+
+use crate::storage::Region;
+
+// Seekable entries follow the zstd seekable format: the blob is a series of independent zstd
+// frames followed by a skippable frame holding the seek table. Decoders that don't know about
+// the table skip it, so the entry stays a valid zstd stream.
+const SKIPPABLE_MAGIC: u32 = 0x184D2A5E;
+const SEEKABLE_MAGIC: u32 = 0x8F92EAB1;
+const SKIPPABLE_HEADER_SIZE: usize = 8;
+const SEEK_TABLE_FOOTER_SIZE: usize = 9;
+const SEEK_TABLE_ENTRY_SIZE: usize = 8;
+
+/// Largest number of uncompressed bytes allowed in a single frame.
+pub(crate) const MAX_FRAME_SIZE: usize = 1 << 30;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Frame {
+    pub compressed_offset: u64,
+    pub decompressed_offset: u64,
+    pub decompressed_size: u64,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SeekTable {
+    frames: Vec<Frame>,
+    decompressed_size: u64,
+    frames_len: u64,
+}
+
+impl SeekTable {
+    /// Writes the seek table for frames given as `(compressed_size, decompressed_size)` pairs.
+    pub fn write<W: Write>(w: &mut W, frames: &[(u32, u32)]) -> io::Result<()> {
+        let table_size = frames.len() * SEEK_TABLE_ENTRY_SIZE + SEEK_TABLE_FOOTER_SIZE;
+        w.write_all(&SKIPPABLE_MAGIC.to_le_bytes())?;
+        w.write_all(&(table_size as u32).to_le_bytes())?;
+        for (compressed, decompressed) in frames {
+            w.write_all(&compressed.to_le_bytes())?;
+            w.write_all(&decompressed.to_le_bytes())?;
+        }
+        w.write_all(&(frames.len() as u32).to_le_bytes())?;
+        w.write_all(&[0u8])?; // Descriptor: no per-frame checksums
+        w.write_all(&SEEKABLE_MAGIC.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Parses the seek table stored at the end of a seekable entry's data.
+    ///
+    /// Only the table is read, so entries in a file aren't read in full.
+    pub fn parse(data: Region<'_>) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid seek table");
+
+        let len = data.len();
+        let footer_pos = len
+            .checked_sub(SEEK_TABLE_FOOTER_SIZE as u64)
+            .ok_or_else(invalid)?;
+        let footer = data.read(footer_pos..len)?;
+        if read_u32(&footer[5..9]) != SEEKABLE_MAGIC {
+            return Err(invalid());
+        }
+        let count = read_u32(&footer[0..4]) as usize;
+
+        let table_size = count
+            .checked_mul(SEEK_TABLE_ENTRY_SIZE)
+            .and_then(|n| n.checked_add(SEEK_TABLE_FOOTER_SIZE + SKIPPABLE_HEADER_SIZE))
+            .ok_or_else(invalid)?;
+        let table_start = len.checked_sub(table_size as u64).ok_or_else(invalid)?;
+        let table = data.read(table_start..len)?;
+        if read_u32(&table[..4]) != SKIPPABLE_MAGIC {
+            return Err(invalid());
+        }
+
+        let mut frames = Vec::with_capacity(count);
+        let mut compressed_offset = 0u64;
+        let mut decompressed_offset = 0u64;
+        let mut cursor = SKIPPABLE_HEADER_SIZE;
+        for _ in 0..count {
+            let compressed = read_u32(&table[cursor..cursor + 4]) as u64;
+            let decompressed = read_u32(&table[cursor + 4..cursor + 8]) as u64;
+            frames.push(Frame {
+                compressed_offset,
+                decompressed_offset,
+                decompressed_size: decompressed,
+            });
+            compressed_offset += compressed;
+            decompressed_offset += decompressed;
+            cursor += SEEK_TABLE_ENTRY_SIZE;
+        }
+
+        if compressed_offset != table_start {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            frames,
+            decompressed_size: decompressed_offset,
+            frames_len: table_start,
+        })
+    }
+
+    /// Returns the frame containing the given uncompressed position.
+    pub fn find(&self, pos: u64) -> Option<&Frame> {
+        let i = self
+            .frames
+            .partition_point(|f| f.decompressed_offset + f.decompressed_size <= pos);
+        self.frames.get(i)
+    }
+
+    /// Returns the final frame.
+    pub fn last(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
+    /// Total uncompressed size of all frames.
+    pub fn decompressed_size(&self) -> u64 {
+        self.decompressed_size
+    }
+
+    /// Number of bytes occupied by the frames, excluding the seek table itself.
+    pub fn frames_len(&self) -> u64 {
+        self.frames_len
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
@@ -0,0 +1,449 @@
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use crate::entry::Entry;
+use crate::options::Backend;
+
+/// The backing store for an archive, either a file on disk or a growable buffer.
+pub(crate) enum Storage {
+    File(File),
+    Memory(io::Cursor<Vec<u8>>),
+}
+
+/// Destination for compressed data while an encoder is active.
+///
+/// File encoders write through a cloned handle that shares the file offset, in-memory encoders
+/// buffer their output until [`Storage::finish_sink`] appends it.
+pub(crate) enum Sink {
+    File(File),
+    Memory(Vec<u8>),
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::File(f) => f.write(buf),
+            Sink::Memory(v) => v.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::File(f) => f.flush(),
+            Sink::Memory(_) => Ok(()),
+        }
+    }
+}
+
+impl Storage {
+    pub fn lock(&self) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.lock(),
+            Storage::Memory(_) => Ok(()),
+        }
+    }
+
+    pub fn unlock(&self) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.unlock(),
+            Storage::Memory(_) => Ok(()),
+        }
+    }
+
+    /// Flushes written data to disk.
+    pub fn sync(&self) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.sync_data(),
+            Storage::Memory(_) => Ok(()),
+        }
+    }
+
+    /// Flushes written data and file metadata, such as the length, to disk.
+    pub fn sync_all(&self) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.sync_all(),
+            Storage::Memory(_) => Ok(()),
+        }
+    }
+
+    /// Truncates or extends the store to `len` bytes.
+    pub fn set_len(&mut self, len: u64) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.set_len(len),
+            Storage::Memory(c) => {
+                c.get_mut().resize(len as usize, 0);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns a sink positioned at the current offset for an encoder to write into.
+    pub fn sink(&self) -> io::Result<Sink> {
+        match self {
+            Storage::File(f) => Ok(Sink::File(f.try_clone()?)),
+            Storage::Memory(_) => Ok(Sink::Memory(Vec::new())),
+        }
+    }
+
+    /// Takes back a sink from a finished encoder, returning the new position.
+    pub fn finish_sink(&mut self, sink: Sink) -> io::Result<u64> {
+        match (self, sink) {
+            (Storage::File(f), Sink::File(mut clone)) => {
+                let pos = clone.stream_position()?;
+                f.seek(SeekFrom::Start(pos))
+            }
+            (storage, Sink::Memory(buf)) => {
+                storage.write_all(&buf)?;
+                storage.stream_position()
+            }
+            (_, Sink::File(_)) => Err(io::Error::other("Sink doesn't match storage")),
+        }
+    }
+}
+
+impl Read for Storage {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Storage::File(f) => f.read(buf),
+            Storage::Memory(c) => c.read(buf),
+        }
+    }
+}
+
+impl Write for Storage {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Storage::File(f) => f.write(buf),
+            Storage::Memory(c) => c.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Storage::File(f) => f.flush(),
+            Storage::Memory(_) => Ok(()),
+        }
+    }
+}
+
+impl Seek for Storage {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Storage::File(f) => f.seek(pos),
+            Storage::Memory(c) => c.seek(pos),
+        }
+    }
+}
+
+/// How a file-backed archive reads committed data, picked with [`Backend`].
+pub(crate) enum Mapping {
+    Mmap {
+        /// Memory map made on the last save.
+        mmap: Option<Arc<Mmap>>,
+        /// Mapping made on the first read after [`Bindle::commit()`](crate::Bindle::commit), which
+        /// doesn't remap.
+        remap: OnceLock<Arc<Mmap>>,
+    },
+    Pread {
+        /// Handle shared with views and owned readers, cloned on the first one.
+        shared: OnceLock<Arc<File>>,
+    },
+}
+
+impl Mapping {
+    /// Creates an empty mapping for `backend`, nothing is mapped until [`map()`](Mapping::map).
+    pub fn new(backend: Backend) -> Self {
+        match backend {
+            Backend::Mmap => Mapping::Mmap {
+                mmap: None,
+                remap: OnceLock::new(),
+            },
+            Backend::Pread => Mapping::Pread {
+                shared: OnceLock::new(),
+            },
+        }
+    }
+
+    /// Creates a mapping holding the memory map `mmap`.
+    pub fn mapped(mmap: Mmap) -> Self {
+        Mapping::Mmap {
+            mmap: Some(Arc::new(mmap)),
+            remap: OnceLock::new(),
+        }
+    }
+
+    /// Maps `file` again after it changed, does nothing for positioned reads.
+    pub fn map(&mut self, file: &File) -> io::Result<()> {
+        if let Mapping::Mmap { mmap, remap } = self {
+            *mmap = Some(Arc::new(unsafe { Mmap::map(file)? }));
+            remap.take();
+        }
+        Ok(())
+    }
+
+    /// Drops everything that refers to the current file, before it's replaced or truncated.
+    pub fn unmap(&mut self) {
+        match self {
+            Mapping::Mmap { mmap, remap } => {
+                drop(mmap.take());
+                remap.take();
+            }
+            Mapping::Pread { shared } => {
+                shared.take();
+            }
+        }
+    }
+
+    /// Forgets the mapping made after the last commit, so the next read maps the file again.
+    pub fn clear_remap(&mut self) {
+        if let Mapping::Mmap { remap, .. } = self {
+            remap.take();
+        }
+    }
+
+    /// Returns true if views or owned readers still share the mapping or file handle.
+    pub fn is_shared(&self) -> bool {
+        match self {
+            Mapping::Mmap { mmap, remap } => mmap
+                .iter()
+                .chain(remap.get())
+                .any(|mmap| Arc::strong_count(mmap) > 1),
+            Mapping::Pread { shared } => {
+                shared.get().is_some_and(|file| Arc::strong_count(file) > 1)
+            }
+        }
+    }
+
+    /// Returns the memory map of `file` covering `committed_len` bytes, mapping it again if it was
+    /// committed without remapping. Returns `None` for positioned reads.
+    pub fn get(&self, file: &File, committed_len: u64) -> io::Result<Option<&Arc<Mmap>>> {
+        let (mmap, remap) = match self {
+            Mapping::Mmap { mmap, remap } => (mmap, remap),
+            Mapping::Pread { .. } => return Ok(None),
+        };
+        match mmap {
+            Some(mmap) if mmap.len() as u64 >= committed_len => Ok(Some(mmap)),
+            _ => {
+                // Committed without remapping, map the file again on the first read
+                if remap.get().is_none() {
+                    let mmap = unsafe { Mmap::map(file)? };
+                    let _ = remap.set(Arc::new(mmap));
+                }
+                remap
+                    .get()
+                    .map(Some)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing mmap"))
+            }
+        }
+    }
+
+    /// Returns a handle to `file`, the archive at `path`, that views and owned readers share,
+    /// opened on first use.
+    pub fn shared_file(&self, file: &File, path: &Path) -> io::Result<Arc<File>> {
+        let Mapping::Pread { shared } = self else {
+            return Ok(Arc::new(read_handle(file, path)?));
+        };
+        if shared.get().is_none() {
+            let _ = shared.set(Arc::new(read_handle(file, path)?));
+        }
+        shared
+            .get()
+            .cloned()
+            .ok_or_else(|| io::Error::other("Missing shared file"))
+    }
+}
+
+/// Returns a second handle to `file`, the archive at `path`, for positioned reads on other threads.
+///
+/// Positioned reads on Windows move the file position, which a cloned handle shares with `file` and
+/// the encoders writing through it without seeking, so Windows opens the path again instead.
+#[cfg(windows)]
+fn read_handle(_file: &File, path: &Path) -> io::Result<File> {
+    File::open(path)
+}
+
+/// Returns a second handle to `file`, the archive at `path`, for positioned reads on other threads.
+#[cfg(not(windows))]
+fn read_handle(file: &File, _path: &Path) -> io::Result<File> {
+    file.try_clone()
+}
+
+/// A range of archive bytes, borrowed from memory or read from a file with positioned reads.
+#[derive(Clone, Copy)]
+pub(crate) enum Region<'a> {
+    Memory(&'a [u8]),
+    File {
+        file: &'a File,
+        offset: u64,
+        len: u64,
+    },
+}
+
+impl<'a> Region<'a> {
+    /// Returns the stored bytes of `entry` in the file, which aren't bounds checked until read.
+    pub fn file(file: &'a File, entry: &Entry) -> Self {
+        Region::File {
+            file,
+            offset: entry.offset(),
+            len: entry.compressed_size(),
+        }
+    }
+
+    /// Returns the length of the region in bytes.
+    pub fn len(&self) -> u64 {
+        match self {
+            Region::Memory(bytes) => bytes.len() as u64,
+            Region::File { len, .. } => *len,
+        }
+    }
+
+    /// Returns the stored bytes of `entry`, for a region holding the whole archive.
+    ///
+    /// Empty entries are an empty region even if their offset is past the end, like
+    /// [`stored_bytes()`](crate::bindle::stored_bytes).
+    pub fn entry(&self, entry: &Entry) -> io::Result<Region<'a>> {
+        if entry.compressed_size() == 0 {
+            return Ok(Region::Memory(&[]));
+        }
+        let start = entry.offset();
+        start
+            .checked_add(entry.compressed_size())
+            .and_then(|end| self.slice(start..end))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Entry data out of bounds"))
+    }
+
+    /// Returns the part of the region in `range`, or `None` if it's out of bounds.
+    pub fn slice(&self, range: Range<u64>) -> Option<Region<'a>> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+        Some(match *self {
+            Region::Memory(bytes) => {
+                Region::Memory(&bytes[range.start as usize..range.end as usize])
+            }
+            Region::File { file, offset, .. } => Region::File {
+                file,
+                offset: offset + range.start,
+                len: range.end - range.start,
+            },
+        })
+    }
+
+    /// Reads the bytes in `range`, borrowing them from memory or allocating for a file.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the range is out of bounds.
+    pub fn read(&self, range: Range<u64>) -> io::Result<Cow<'a, [u8]>> {
+        let region = self.slice(range).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Entry data out of bounds")
+        })?;
+        match region {
+            Region::Memory(bytes) => Ok(Cow::Borrowed(bytes)),
+            Region::File { file, offset, len } => {
+                let mut buf = vec![0u8; len as usize];
+                read_exact_at(file, &mut buf, offset)?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+
+    /// Reads the whole region, see [`read()`](Region::read).
+    pub fn read_all(&self) -> io::Result<Cow<'a, [u8]>> {
+        self.read(0..self.len())
+    }
+
+    /// Returns a reader over the region, starting at its beginning.
+    pub fn reader(self) -> RegionReader<'a> {
+        RegionReader {
+            region: self,
+            pos: 0,
+        }
+    }
+}
+
+/// A [`Read`] + [`Seek`] cursor over a [`Region`].
+pub(crate) struct RegionReader<'a> {
+    region: Region<'a>,
+    pos: u64,
+}
+
+impl Read for RegionReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.region.len().saturating_sub(self.pos);
+        let n = (buf.len() as u64).min(remaining) as usize;
+        if n == 0 {
+            return Ok(0);
+        }
+        let n = match self.region {
+            Region::Memory(bytes) => {
+                let start = self.pos as usize;
+                buf[..n].copy_from_slice(&bytes[start..start + n]);
+                n
+            }
+            Region::File { file, offset, .. } => read_at(file, &mut buf[..n], offset + self.pos)?,
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for RegionReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+            SeekFrom::End(n) => self.region.len().checked_add_signed(n),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        self.pos = pos;
+        Ok(pos)
+    }
+}
+
+/// Reads into `buf` from `offset` in `file` without using the file position.
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+/// Reads into `buf` from `offset` in `file`.
+///
+/// Windows moves the file position. The archive's own handle always seeks before writing, and views
+/// and owned readers read through a handle of their own, see [`read_handle()`].
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(_file: &File, _buf: &mut [u8], _offset: u64) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Positioned reads aren't supported on this platform",
+    ))
+}
+
+/// Fills `buf` from `offset` in `file`, failing with [`io::ErrorKind::UnexpectedEof`] at the end.
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    while !buf.is_empty() {
+        match read_at(file, buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::entry::Entry;
+
+/// A directory in the tree of entries returned by [`Bindle::tree()`](crate::Bindle::tree).
+///
+/// Entry names are split on every `/`, every component but the last becomes a directory. A name can
+/// be both a file and a directory, such as `docs` next to `docs/index.html`, in which case it shows
+/// up in both maps. Leading, trailing and repeated slashes give empty components, which are kept as
+/// directories or files named `""`, so `a/b` and `a//b` are different files, like in
+/// [`Bindle::children_of()`](crate::Bindle::children_of).
+#[derive(Clone, Debug, Default)]
+pub struct TreeNode {
+    /// Subdirectories by name.
+    pub dirs: BTreeMap<String, TreeNode>,
+    /// Entries directly in this directory by file name, with their index records.
+    pub files: BTreeMap<String, Entry>,
+}
+
+impl TreeNode {
+    /// Returns the directory at `path` below this one, such as `assets/images`.
+    ///
+    /// An empty path returns this directory, a single trailing `/` is ignored.
+    pub fn dir(&self, path: &str) -> Option<&TreeNode> {
+        dir_prefix(path)
+            .split_terminator('/')
+            .try_fold(self, |node, part| node.dirs.get(part))
+    }
+
+    /// Adds the entry stored as `name` below this directory.
+    pub(crate) fn insert(&mut self, name: &str, entry: Entry) {
+        let mut parts = name.split('/');
+        let Some(file) = parts.next_back() else {
+            return;
+        };
+        let dir = parts.fold(self, |node, part| {
+            node.dirs.entry(part.to_string()).or_default()
+        });
+        dir.files.insert(file.to_string(), entry);
+    }
+}
+
+/// Returns the name prefix of the entries in the directory `dir`.
+///
+/// The top level is `""`, any other directory ends with a single `/` added unless `dir` already
+/// ends with one, so `docs` and `docs/` are the same directory.
+pub(crate) fn dir_prefix(dir: &str) -> Cow<'_, str> {
+    match dir {
+        "" => Cow::Borrowed(""),
+        dir if dir.ends_with('/') => Cow::Borrowed(dir),
+        dir => Cow::Owned(format!("{}/", dir)),
+    }
+}
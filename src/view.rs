@@ -0,0 +1,147 @@
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use crate::async_reader::AsyncReader;
+use crate::bindle::{decode_entry, entry_reader};
+use crate::entry::Entry;
+use crate::error::BindleError;
+use crate::limits::Limits;
+use crate::owned_reader::OwnedReader;
+use crate::reader::Reader;
+use crate::storage::Region;
+
+/// The bytes a view reads from, shared with the archive it came from.
+#[derive(Clone)]
+pub(crate) enum ViewData {
+    Mmap(Arc<Mmap>),
+    Memory(Arc<[u8]>),
+    /// A file read with positioned reads, see [`Backend::Pread`](crate::Backend::Pread).
+    File(Arc<File>),
+}
+
+impl ViewData {
+    /// Returns the stored bytes of `entry`.
+    pub(crate) fn stored(&self, entry: &Entry) -> io::Result<Region<'_>> {
+        match self {
+            ViewData::Mmap(mmap) => Region::Memory(mmap).entry(entry),
+            ViewData::Memory(buf) => Region::Memory(buf).entry(entry),
+            ViewData::File(file) => Ok(Region::file(file, entry)),
+        }
+    }
+}
+
+/// A read-only snapshot of an archive that is `Send + Sync`, for sharing between threads.
+///
+/// Created by [`Bindle::view()`](crate::Bindle::view). Holds the memory map and index behind
+/// [`Arc`]s, so cloning is cheap and reads never touch the archive's file handle. Writes stay on the
+/// [`Bindle`](crate::Bindle) the view came from and aren't visible to it.
+#[derive(Clone)]
+pub struct ReadOnlyView {
+    pub(crate) data: ViewData,
+    pub(crate) index: Arc<BTreeMap<String, Entry>>,
+    pub(crate) dict: Option<Arc<[u8]>>,
+    pub(crate) limits: Limits,
+}
+
+impl ReadOnlyView {
+    /// Reads an entry, decompressing if needed.
+    ///
+    /// Returns [`BindleError::NotFound`] if the entry doesn't exist or [`BindleError::CrcMismatch`]
+    /// if CRC32 verification fails.
+    pub fn read(&self, name: &str) -> Result<Cow<'_, [u8]>, BindleError> {
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| BindleError::NotFound(name.to_string()))?;
+        self.limits.check_entry(name, entry)?;
+        decode_entry(self.data.stored(entry)?, name, entry, self.dict.as_deref())
+    }
+
+    /// Returns a streaming reader for an entry.
+    ///
+    /// Call [`Reader::verify_crc32()`] after reading to verify integrity.
+    pub fn reader(&self, name: &str) -> io::Result<Reader<'_>> {
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+        self.limits.check_entry(name, entry)?;
+        entry_reader(self.data.stored(entry)?, name, entry, self.dict.as_deref())
+    }
+
+    /// Returns a streaming reader for an entry that shares the view's data instead of borrowing it.
+    ///
+    /// The reader can be moved to another thread and outlives the view, see [`OwnedReader`].
+    pub fn owned_reader(&self, name: &str) -> io::Result<OwnedReader> {
+        let entry = self
+            .index
+            .get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Entry not found"))?;
+        self.limits.check_entry(name, entry)?;
+        OwnedReader::new(self.data.clone(), name, entry, self.dict.as_deref())
+    }
+
+    /// Reads an entry on tokio's blocking thread pool, available with the `async` feature.
+    ///
+    /// Decompression and CRC32 verification run in [`spawn_blocking`](tokio::task::spawn_blocking),
+    /// so large entries don't stall the runtime. Must be awaited within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub async fn read_async(&self, name: &str) -> io::Result<Vec<u8>> {
+        let view = self.clone();
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || Ok(view.read(&name)?.into_owned()))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// Returns an [`AsyncReader`] streaming an entry, available with the `async` feature.
+    ///
+    /// Returns [`io::ErrorKind::NotFound`] if the entry doesn't exist. Must be called within a tokio
+    /// runtime.
+    #[cfg(feature = "async")]
+    pub fn async_reader(&self, name: &str) -> io::Result<AsyncReader> {
+        AsyncReader::new(self.clone(), name)
+    }
+
+    /// Returns the number of entries in the view.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the view contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns true if an entry with the given name exists.
+    pub fn exists(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    /// Iterates over entry names and their metadata in name order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.index
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Sets the size limits enforced when reading, see [`Limits`].
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Returns the size limits enforced when reading.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Returns a reference to the index snapshot.
+    pub fn index(&self) -> &BTreeMap<String, Entry> {
+        &self.index
+    }
+}
@@ -1,8 +1,71 @@
 use crc32fast::Hasher;
+use flate2::write::GzEncoder;
+use sha2::{Digest, Sha256};
 use std::io::{self, Seek, SeekFrom, Write};
 
+use crate::AUTO_SAMPLE_SIZE;
 use crate::bindle::Bindle;
-use crate::entry::Entry;
+use crate::compress::Compress;
+use crate::crypto;
+use crate::entry::{
+    ENTRY_FLAG_DICT, ENTRY_FLAG_ENCRYPTED, ENTRY_FLAG_LONG_WINDOW, ENTRY_FLAG_SEEKABLE, Entry,
+};
+use crate::seekable::SeekTable;
+use crate::storage::{Sink, Storage};
+
+pub(crate) enum Encoder<'a> {
+    Zstd(zstd::Encoder<'a, Sink>),
+    Gzip(GzEncoder<Sink>),
+}
+
+impl Encoder<'_> {
+    /// Creates the encoder for `compress`, or `None` if data is written uncompressed.
+    ///
+    /// Zstd data is compressed with `dict` if one is given. Output goes to memory if `sealed`, see
+    /// [`new_sink()`].
+    pub(crate) fn new(
+        storage: &Storage,
+        sealed: bool,
+        compress: Compress,
+        level: i32,
+        dict: Option<&[u8]>,
+    ) -> io::Result<Option<Self>> {
+        Ok(match (compress, dict) {
+            (Compress::Zstd, Some(dict)) => Some(Encoder::Zstd(zstd::Encoder::with_dictionary(
+                new_sink(storage, sealed)?,
+                level,
+                dict,
+            )?)),
+            (Compress::Zstd, None) => Some(Encoder::Zstd(zstd::Encoder::new(
+                new_sink(storage, sealed)?,
+                level,
+            )?)),
+            (Compress::Gzip, _) => Some(Encoder::Gzip(GzEncoder::new(
+                new_sink(storage, sealed)?,
+                flate2::Compression::default(),
+            ))),
+            (Compress::None | Compress::Auto, _) => None,
+        })
+    }
+}
+
+/// Returns the sink for a new encoder, an in-memory buffer if the output is `sealed`, encrypted
+/// before it's written, so no plaintext ever reaches the file.
+fn new_sink(storage: &Storage, sealed: bool) -> io::Result<Sink> {
+    match sealed {
+        true => Ok(Sink::Memory(Vec::new())),
+        false => storage.sink(),
+    }
+}
+
+/// Frame bookkeeping for seekable zstd entries.
+pub(crate) struct Frames {
+    pub size: u64,
+    pub level: i32,
+    pub current_len: u64,
+    pub current_start: u64,
+    pub table: Vec<(u32, u32)>,
+}
 
 /// A streaming writer for adding entries to an archive.
 ///
@@ -10,6 +73,10 @@ use crate::entry::Entry;
 ///
 /// The writer must be closed with [`close()`](Writer::close) or will be automatically closed when dropped. After closing, call [`Bindle::save()`] to commit the index.
 ///
+/// Call [`abort()`](Writer::abort) to discard the entry instead. If a write fails, the entry is
+/// discarded when the writer is dropped and [`close()`](Writer::close) returns an error, so a
+/// failed stream never adds a truncated entry.
+///
 /// # Example
 ///
 /// ```no_run
@@ -25,16 +92,31 @@ use crate::entry::Entry;
 /// ```
 pub struct Writer<'a> {
     pub(crate) bindle: &'a mut Bindle,
-    pub(crate) encoder: Option<zstd::Encoder<'a, std::fs::File>>,
+    pub(crate) encoder: Option<Encoder<'a>>,
+    /// Data buffered until [`Compress::Auto`] picks a compression.
+    pub(crate) pending: Option<Vec<u8>>,
+    pub(crate) level: i32,
     pub(crate) name: String,
     pub(crate) start_offset: u64,
     pub(crate) uncompressed_size: u64,
     pub(crate) crc32_hasher: Hasher,
+    pub(crate) sha256_hasher: Option<Sha256>,
+    pub(crate) frames: Option<Frames>,
+    /// Encoded data kept in memory until close encrypts it, set when the archive has a key.
+    pub(crate) sealed: Option<Vec<u8>>,
+    /// Set when the zstd window is larger than decoders accept by default.
+    pub(crate) long_window: bool,
+    /// Set when a write fails, the entry is discarded instead of committed.
+    pub(crate) aborted: bool,
 }
 
 impl<'a> Drop for Writer<'a> {
     fn drop(&mut self) {
-        let _ = self.close_drop();
+        if self.aborted {
+            let _ = self.abort_drop();
+        } else {
+            let _ = self.close_drop();
+        }
     }
 }
 
@@ -52,57 +134,193 @@ impl<'a> std::io::Write for Writer<'a> {
 impl<'a> Writer<'a> {
     pub fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
         if self.name.is_empty() {
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "closed"));
+            return Err(std::io::Error::other("closed"));
         }
+        let result = self.write_data(data);
+        self.aborted |= result.is_err();
+        result
+    }
 
+    fn write_data(&mut self, data: &[u8]) -> io::Result<()> {
         self.uncompressed_size += data.len() as u64;
         self.crc32_hasher.update(data);
+        if let Some(hasher) = &mut self.sha256_hasher {
+            hasher.update(data);
+        }
 
-        match &mut self.encoder {
-            Some(encoder) => {
-                // Compressed: write to zstd encoder
-                encoder.write_all(data)?;
+        let mut data = data;
+        if let Some(pending) = &mut self.pending {
+            // Buffer a full sample, and up to the threshold until more data shows the entry is over it
+            let threshold = self.bindle.auto_compress_threshold;
+            let limit = AUTO_SAMPLE_SIZE.max(threshold);
+            let n = (limit - pending.len()).min(data.len());
+            pending.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if pending.len() < limit || (data.is_empty() && pending.len() <= threshold) {
+                return Ok(());
             }
-            None => {
-                // Uncompressed: write directly to file
-                self.bindle.file.write_all(data)?;
+            self.resolve_pending(usize::MAX)?;
+        }
+
+        let Some(frames) = &self.frames else {
+            return self.write_encoded(data);
+        };
+
+        // Seekable: split the data at frame boundaries, starting a new frame each time one fills up
+        let frame_size = frames.size;
+        while !data.is_empty() {
+            let current_len = self.frames.as_ref().map_or(0, |f| f.current_len);
+            let n = (frame_size - current_len).min(data.len() as u64) as usize;
+            self.write_encoded(&data[..n])?;
+            data = &data[n..];
+
+            if let Some(frames) = &mut self.frames {
+                frames.current_len += n as u64;
+                if frames.current_len == frame_size {
+                    self.finish_frame()?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Picks the compression for [`Compress::Auto`] from the buffered sample and writes it out.
+    ///
+    /// `len` is the full entry size if the writer is closing, see [`Compress::resolve()`].
+    fn resolve_pending(&mut self, len: usize) -> io::Result<()> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(());
+        };
+        let compress = Compress::Auto.resolve(&pending, len, self.bindle.auto_compress_threshold);
+        self.encoder = Encoder::new(
+            &self.bindle.storage,
+            self.sealed.is_some(),
+            compress,
+            self.level,
+            self.bindle.dict.as_deref(),
+        )?;
+        self.write_encoded(&pending)
+    }
+
+    fn write_encoded(&mut self, data: &[u8]) -> io::Result<()> {
+        if let (None, Some(frames)) = (&self.encoder, &self.frames) {
+            // Seekable: the previous frame was finished, start the next one
+            let sink = new_sink(&self.bindle.storage, self.sealed.is_some())?;
+            self.encoder = Some(Encoder::Zstd(zstd::Encoder::new(sink, frames.level)?));
+        }
+
+        match &mut self.encoder {
+            Some(Encoder::Zstd(encoder)) => encoder.write_all(data)?,
+            Some(Encoder::Gzip(encoder)) => encoder.write_all(data)?,
+            None => match &mut self.sealed {
+                Some(sealed) => sealed.extend_from_slice(data),
+                // Uncompressed: write directly to storage
+                None => self.bindle.storage.write_all(data)?,
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Takes back the sink of a finished encoder, returning the position after its output.
+    fn finish_sink(&mut self, sink: Sink) -> io::Result<u64> {
+        match (&mut self.sealed, sink) {
+            (Some(sealed), Sink::Memory(data)) => {
+                sealed.extend_from_slice(&data);
+                Ok(self.start_offset + sealed.len() as u64)
+            }
+            (_, sink) => self.bindle.storage.finish_sink(sink),
+        }
+    }
+
+    /// Returns the position after the data written so far.
+    fn position(&mut self) -> io::Result<u64> {
+        match &self.sealed {
+            Some(sealed) => Ok(self.start_offset + sealed.len() as u64),
+            None => self.bindle.storage.stream_position(),
+        }
+    }
+
+    fn finish_frame(&mut self) -> io::Result<()> {
+        let Some(Encoder::Zstd(encoder)) = self.encoder.take() else {
+            return Ok(());
+        };
+
+        let pos = self.finish_sink(encoder.finish()?)?;
+        let Some(frames) = &mut self.frames else {
+            return Ok(());
+        };
+
+        frames.table.push((
+            (pos - frames.current_start) as u32,
+            frames.current_len as u32,
+        ));
+        frames.current_start = pos;
+        frames.current_len = 0;
+        Ok(())
+    }
+
     fn close_drop(&mut self) -> io::Result<()> {
         if self.name.is_empty() {
             return Ok(());
         }
+        let len = self.pending.as_ref().map_or(0, |pending| pending.len());
+        self.resolve_pending(len)?;
 
-        let (compression_type, current_pos) = match self.encoder.take() {
-            Some(encoder) => {
-                // Compressed: finish encoder and sync position
-                let mut f = encoder.finish()?;
-                let pos = f.stream_position()?;
-                self.bindle.file.seek(SeekFrom::Start(pos))?;
-                (1, pos)
+        let (compression_type, mut current_pos) = if self.frames.is_some() {
+            // Seekable: finish the last frame and append the seek table
+            self.finish_frame()?;
+            let table = self.frames.as_ref().map(|f| f.table.as_slice());
+            match &mut self.sealed {
+                Some(sealed) => SeekTable::write(sealed, table.unwrap_or_default())?,
+                None => SeekTable::write(&mut self.bindle.storage, table.unwrap_or_default())?,
             }
-            None => {
-                // Uncompressed: already wrote directly to file, just get position
-                let pos = self.bindle.file.stream_position()?;
-                (0, pos)
+            (Compress::Zstd, self.position()?)
+        } else {
+            match self.encoder.take() {
+                Some(encoder) => {
+                    // Compressed: finish encoder and sync position
+                    let (compress, sink) = match encoder {
+                        Encoder::Zstd(e) => (Compress::Zstd, e.finish()?),
+                        Encoder::Gzip(e) => (Compress::Gzip, e.finish()?),
+                    };
+                    let pos = self.finish_sink(sink)?;
+                    (compress, pos)
+                }
+                None => {
+                    // Uncompressed: already wrote directly to storage, just get position
+                    let pos = self.position()?;
+                    (Compress::None, pos)
+                }
             }
         };
 
+        // Encrypted: write the nonce, ciphertext and tag of the encoded bytes kept in memory
+        let mut stored_crc32 = None;
+        if let Some(sealed) = self.sealed.take() {
+            let cipher = self
+                .bindle
+                .cipher
+                .as_ref()
+                .ok_or_else(crypto::missing_key)?;
+            let encrypted = crypto::encrypt(cipher, &self.name, &sealed)?;
+            self.bindle.storage.write_all(&encrypted)?;
+            current_pos = self.start_offset + encrypted.len() as u64;
+            stored_crc32 = Some(crc32fast::hash(&encrypted));
+        }
+
         let compressed_size = current_pos - self.start_offset;
 
         // Handle 8-byte alignment padding
         let pad_len = crate::pad::<8, u64>(current_pos);
         if pad_len > 0 {
-            crate::write_padding(&mut self.bindle.file, pad_len as usize)?;
+            crate::write_padding(&mut self.bindle.storage, pad_len as usize)?;
         }
 
         self.bindle.data_end = current_pos + pad_len;
 
-        let crc32_value = self.crc32_hasher.clone().finalize();
+        let crc32_value = stored_crc32.unwrap_or_else(|| self.crc32_hasher.clone().finalize());
 
         let mut entry = Entry::default();
         entry.set_offset(self.start_offset);
@@ -110,20 +328,83 @@ impl<'a> Writer<'a> {
         entry.set_uncompressed_size(self.uncompressed_size);
         entry.set_crc32(crc32_value);
         entry.set_name_len(self.name.len() as u16);
-        entry.compression_type = compression_type;
+        entry.compression_type = compression_type as u8;
+        if self.frames.is_some() {
+            entry.insert_flags(ENTRY_FLAG_SEEKABLE);
+        }
+        if stored_crc32.is_some() {
+            entry.insert_flags(ENTRY_FLAG_ENCRYPTED);
+        }
+        if compression_type == Compress::Zstd && self.frames.is_none() && self.bindle.dict.is_some()
+        {
+            entry.insert_flags(ENTRY_FLAG_DICT);
+        }
+        if compression_type == Compress::Zstd && self.long_window {
+            entry.insert_flags(ENTRY_FLAG_LONG_WINDOW);
+        }
 
-        self.bindle.index.insert(self.name.clone(), entry);
+        let sha256: Option<[u8; 32]> = self.sha256_hasher.take().map(|h| h.finalize().into());
+        if let Some(mut shared) = sha256.and_then(|hash| self.bindle.dedup_target(&hash, &entry)) {
+            // Identical data is already stored, drop the bytes just written and share it
+            shared.set_name_len(self.name.len() as u16);
+            entry = shared;
+            self.bindle.data_end = self.start_offset;
+            self.bindle
+                .storage
+                .seek(SeekFrom::Start(self.start_offset))?;
+        }
+
+        self.bindle.meta.remove(&self.name);
+        self.bindle.insert_entry(self.name.clone(), entry);
+        self.bindle.dirty = true;
+        if let Some(hash) = sha256 {
+            self.bindle.record_sha256(&self.name, hash);
+        }
         self.name.clear(); // Mark as closed
+        Ok(())
+    }
 
-        // Downgrade to shared lock after write completes
-        self.bindle.file.lock_shared()?;
+    /// Discards the entry being written without adding it to the index.
+    ///
+    /// The bytes written so far are left in place and overwritten by the next write, an existing
+    /// entry with the same name is kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use bindle_file::{Bindle, Compress};
+    ///
+    /// let mut archive = Bindle::in_memory();
+    /// let mut writer = archive.writer("partial.bin", Compress::Zstd)?;
+    /// writer.write_all(b"incomplete")?;
+    /// writer.abort()?;
+    /// assert!(!archive.exists("partial.bin"));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn abort(mut self) -> io::Result<()> {
+        self.abort_drop()
+    }
+
+    fn abort_drop(&mut self) -> io::Result<()> {
+        self.name.clear(); // Mark as closed
+        self.encoder = None;
+        self.bindle.data_end = self.start_offset;
+        self.bindle
+            .storage
+            .seek(SeekFrom::Start(self.start_offset))?;
         Ok(())
     }
 
     /// Closes the writer and finalizes the entry.
     ///
     /// Automatically called when the writer is dropped, but calling explicitly allows error handling.
+    /// If an earlier write failed, the entry is discarded and an error returned.
     pub fn close(mut self) -> io::Result<()> {
+        if self.aborted && !self.name.is_empty() {
+            self.abort_drop()?;
+            return Err(io::Error::other("a write failed, the entry was discarded"));
+        }
         self.close_drop()
     }
 }
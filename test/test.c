@@ -30,6 +30,12 @@ TEST test_basic(void) {
   ASSERT(bindle_exists(archive, name));
   ASSERT_EQ(bindle_length(archive), 1);
 
+  // Entry fields
+  ASSERT_EQ(bindle_entry_compressed_size(archive, name), strlen(data));
+  ASSERT(bindle_entry_crc32(archive, name) != 0);
+  ASSERT_EQ(bindle_entry_compressed_size(archive, "missing"), 0);
+  ASSERT_EQ(bindle_entry_crc32(archive, "missing"), 0);
+
   ASSERT(bindle_save(archive));
   bindle_close(archive);
 
@@ -112,10 +118,79 @@ TEST test_remove_vacuum(void) {
   PASS();
 }
 
+TEST test_error_codes(void) {
+  const char *path = "test_c_errors.bndl";
+
+  Bindle *archive = bindle_create(path);
+  ASSERT(archive != NULL);
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeOk);
+
+  size_t len = 0;
+  ASSERT(bindle_read_buffer(archive, "missing", &len) == NULL);
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeNotFound);
+  ASSERT_STR_EQ(bindle_error_message(BindleErrorCodeNotFound),
+                "entry not found");
+
+  ASSERT_FALSE(bindle_add(archive, NULL, NULL, 0, BindleCompressNone));
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeInvalidArgument);
+
+  ASSERT(bindle_add(archive, "a.txt", (unsigned char *)"a", 1,
+                    BindleCompressNone));
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeOk);
+  ASSERT_FALSE(bindle_remove(archive, "missing"));
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeNotFound);
+
+  ASSERT(bindle_load("does/not/exist.bndl") == NULL);
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeNotFound);
+  ASSERT_STR_EQ(bindle_error_message(-1), "unknown error");
+
+  // Entries over the size limit aren't read
+  ASSERT(bindle_set_limits(archive, 0, UINT64_MAX));
+  ASSERT(bindle_save(archive));
+  ASSERT(bindle_read_buffer(archive, "a.txt", &len) == NULL);
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeInvalidFormat);
+
+  bindle_close(archive);
+
+  PASS();
+}
+
+static int filter_calls = 0;
+
+static bool only_c_files(const char *name) {
+  filter_calls++;
+  size_t len = strlen(name);
+  return len > 2 && strcmp(name + len - 2, ".c") == 0;
+}
+
+TEST test_pack_filtered(void) {
+  const char *path = "test_c_pack.bndl";
+
+  Bindle *archive = bindle_create(path);
+  ASSERT(archive != NULL);
+
+  filter_calls = 0;
+  ASSERT(bindle_pack_filtered(archive, ".", BindleCompressZstd, only_c_files));
+  ASSERT(filter_calls > 1);
+  ASSERT_EQ(bindle_length(archive), 1);
+  ASSERT(bindle_exists(archive, "test.c"));
+  ASSERT_FALSE(bindle_exists(archive, "greatest.h"));
+
+  ASSERT_FALSE(bindle_pack_filtered(archive, ".", BindleCompressNone, NULL));
+  ASSERT_EQ(bindle_last_error(), BindleErrorCodeInvalidArgument);
+
+  ASSERT(bindle_save(archive));
+  bindle_close(archive);
+
+  PASS();
+}
+
 SUITE(c_api_suite) {
   RUN_TEST(test_basic);
   RUN_TEST(test_writer_reader);
   RUN_TEST(test_remove_vacuum);
+  RUN_TEST(test_error_codes);
+  RUN_TEST(test_pack_filtered);
 }
 
 GREATEST_MAIN_DEFS();
@@ -0,0 +1,297 @@
+#![cfg(feature = "cli")]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use bindle_file::{Bindle, Compress};
+
+fn archive(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bindle-cli-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn read_to_stdout_is_binary_safe() {
+    let path = archive("binary.bndl");
+    let payload = b"line one\r\nline two\n\0\0\xff\xfe\r\r\n\x1a end".repeat(100);
+    {
+        let mut b = Bindle::open(&path).unwrap();
+        b.add("raw.bin", &payload, Compress::None).unwrap();
+        b.add("packed.bin", &payload, Compress::Zstd).unwrap();
+        b.save().unwrap();
+    }
+
+    for name in ["raw.bin", "packed.bin"] {
+        for extra in [&[][..], &["--output", "-"][..]] {
+            let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+                .arg("read")
+                .arg(&path)
+                .arg(name)
+                .args(extra)
+                .output()
+                .unwrap();
+            assert!(
+                out.status.success(),
+                "{}",
+                String::from_utf8_lossy(&out.stderr)
+            );
+            assert_eq!(out.stdout, payload);
+        }
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn read_many_into_output_dir() {
+    let path = archive("many.bndl");
+    let out_dir = std::env::temp_dir().join(format!("bindle-cli-{}-many", std::process::id()));
+    let _ = std::fs::remove_dir_all(&out_dir);
+    {
+        let mut b = Bindle::open(&path).unwrap();
+        b.add("docs/a.txt", b"aaa", Compress::None).unwrap();
+        b.add("docs/img/b.png", b"bbb", Compress::Zstd).unwrap();
+        b.save().unwrap();
+    }
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("read")
+        .arg(&path)
+        .args(["docs/a.txt", "missing.txt", "docs/img/b.png"])
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    // The missing entry is reported, the others are still written
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("MISSING 'missing.txt'"));
+    assert_eq!(std::fs::read(out_dir.join("docs/a.txt")).unwrap(), b"aaa");
+    assert_eq!(
+        std::fs::read(out_dir.join("docs/img/b.png")).unwrap(),
+        b"bbb"
+    );
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("read")
+        .arg(&path)
+        .args(["docs/img/b.png", "docs/a.txt"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert_eq!(out.stdout, b"bbbaaa");
+
+    std::fs::remove_dir_all(&out_dir).unwrap();
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn list_and_stats_as_json() {
+    let path = archive("json.bndl");
+    {
+        let mut b = Bindle::open(&path).unwrap();
+        b.add("plain \"quoted\".txt", b"abcd", Compress::None)
+            .unwrap();
+        b.add("packed.txt", &b"x".repeat(1000), Compress::Zstd)
+            .unwrap();
+        b.save().unwrap();
+    }
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("list")
+        .arg(&path)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.starts_with("[{\"name\":\"packed.txt\",\"size\":1000,"));
+    assert!(stdout.contains(
+        "{\"name\":\"plain \\\"quoted\\\".txt\",\"size\":4,\"packed\":4,\"ratio\":1,\"compression\":\"none\"}"
+    ));
+    assert!(stdout.contains("\"compression\":\"zstd\""));
+    assert!(stdout.ends_with("}]\n"));
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("stats")
+        .arg(&path)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.starts_with("{\"format\":4,"));
+    assert!(stdout.contains("\"entries\":2,\"size\":1004,"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn status_lines_and_exit_codes() {
+    let path = archive("status.bndl");
+    let bindle = || Command::new(env!("CARGO_BIN_EXE_bindle"));
+
+    // Status lines go to stderr, --quiet drops them
+    let out = bindle()
+        .arg("add")
+        .arg(&path)
+        .args(["a.txt", "--data", "aaa"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("OK"));
+
+    let out = bindle()
+        .args(["--quiet", "add"])
+        .arg(&path)
+        .args(["b.txt", "--data", "bbb"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty() && out.stderr.is_empty());
+
+    let out = bindle()
+        .arg("read")
+        .arg(&path)
+        .args(["a.txt", "b.txt", "-q"])
+        .output()
+        .unwrap();
+    assert_eq!(out.stdout, b"aaabbb");
+
+    // Missing entries get their own exit codes
+    let out = bindle()
+        .arg("read")
+        .arg(&path)
+        .args(["a.txt", "missing.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(out.stdout, b"aaa");
+
+    let out = bindle()
+        .arg("remove")
+        .arg(&path)
+        .arg("missing.txt")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(4));
+
+    let out = bindle()
+        .arg("read")
+        .arg("/nonexistent/archive.bndl")
+        .arg("a.txt")
+        .output()
+        .unwrap();
+    assert_eq!(out.status.code(), Some(1));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn read_only_commands_read_archive_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut b = Bindle::in_memory();
+    b.add("docs/a.txt", b"aaa", Compress::Zstd).unwrap();
+    b.add("b.txt", b"bbb", Compress::None).unwrap();
+    b.save().unwrap();
+    let bytes = b.into_bytes().unwrap();
+
+    let run = |args: &[&str]| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_bindle"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Commands that fail early may exit before reading everything
+        let _ = child.stdin.take().unwrap().write_all(&bytes);
+        child.wait_with_output().unwrap()
+    };
+
+    let out = run(&["list", "-", "--names"]);
+    assert!(out.status.success());
+    assert_eq!(out.stdout, b"b.txt\ndocs/a.txt\n");
+
+    let out = run(&["read", "-", "docs/a.txt", "b.txt"]);
+    assert!(out.status.success());
+    assert_eq!(out.stdout, b"aaabbb");
+
+    let out = run(&["verify", "-"]);
+    assert!(out.status.success());
+
+    let out = run(&["stats", "-", "--format", "json"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("\"entries\":2,"));
+
+    // Commands that write need a real file
+    let out = run(&["remove", "-", "b.txt"]);
+    assert!(!out.status.success());
+    assert!(!std::path::Path::new("-").exists());
+}
+
+#[test]
+fn split_and_join() {
+    let path = archive("split.bndl");
+    let prefix = archive("split-part");
+    let joined = archive("split-joined.bndl");
+    {
+        let mut b = Bindle::open(&path).unwrap();
+        for i in 0..50u32 {
+            let data: Vec<u8> = (0..200u32).flat_map(|j| (i * j).to_le_bytes()).collect();
+            b.add(&format!("{:02}.bin", i), &data, Compress::None)
+                .unwrap();
+        }
+        b.save().unwrap();
+    }
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("split")
+        .arg(&path)
+        .arg(&prefix)
+        .args(["--max-bytes", "4096"])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let volumes: Vec<PathBuf> = String::from_utf8(out.stderr)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix("VOLUME "))
+        .map(PathBuf::from)
+        .collect();
+    assert!(volumes.len() > 1);
+    for volume in &volumes {
+        assert!(std::fs::metadata(volume).unwrap().len() <= 4096);
+    }
+
+    let out = Command::new(env!("CARGO_BIN_EXE_bindle"))
+        .arg("join")
+        .arg(&joined)
+        .args(&volumes)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let original = Bindle::open_readonly(&path).unwrap();
+    let restored = Bindle::open_readonly(&joined).unwrap();
+    assert_eq!(
+        restored.names().collect::<Vec<_>>(),
+        original.names().collect::<Vec<_>>()
+    );
+    for name in original.names() {
+        assert_eq!(restored.read(name).unwrap(), original.read(name).unwrap());
+    }
+
+    for file in volumes.iter().chain([&path, &joined]) {
+        let _ = std::fs::remove_file(file);
+    }
+}